@@ -0,0 +1,326 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which read and write the batch files produced by
+//! `genesis` and `--output-batch`
+
+use crate::error::CliError;
+use crate::transaction::placeholder_signature;
+
+use clap::ArgMatches;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use protobuf::{Message, RepeatedField};
+use sawtooth_sdk::messages::batch::{Batch, BatchList};
+use sawtooth_sdk::messages::transaction::Transaction;
+use serde_derive::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// How a `BatchList` is serialized to a batch file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BatchFormat {
+    /// The protobuf bytes of the `BatchList`, as read by `sawtooth batch submit`
+    Protobuf,
+    /// The protobuf bytes of the `BatchList`, base64 encoded
+    Base64,
+    /// A JSON document giving the id of each batch and transaction, with the
+    /// headers and payloads base64 encoded
+    Json,
+}
+
+impl BatchFormat {
+    /// Returns the batch format given by `--batch-format`, defaulting to
+    /// protobuf
+    ///
+    /// # Errors
+    ///
+    /// If the format is unknown, a `CliError::InvalidInputError` is returned.
+    pub fn from_args(args: &ArgMatches) -> Result<BatchFormat, CliError> {
+        match args.value_of("batch_format") {
+            None | Some("protobuf") => Ok(BatchFormat::Protobuf),
+            Some("base64") => Ok(BatchFormat::Base64),
+            Some("json") => Ok(BatchFormat::Json),
+            Some(format) => Err(CliError::InvalidInputError(format!(
+                "Unknown batch format {:?}. Expected protobuf, base64 or json",
+                format
+            ))),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonBatchList {
+    batches: Vec<JsonBatch>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonBatch {
+    header_signature: String,
+    header: String,
+    trace: bool,
+    transactions: Vec<JsonTransaction>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTransaction {
+    header_signature: String,
+    header: String,
+    payload: String,
+}
+
+/// Serializes the batch list in the given format
+///
+/// # Errors
+///
+/// If the batch list cannot be serialized, a `CliError::ProtobufError` or
+/// `CliError::UserError` is returned.
+pub fn encode_batch_list(batch_list: &BatchList, format: BatchFormat) -> Result<Vec<u8>, CliError> {
+    match format {
+        BatchFormat::Protobuf => Ok(batch_list.write_to_bytes()?),
+        BatchFormat::Base64 => Ok(base64::encode(batch_list.write_to_bytes()?).into_bytes()),
+        BatchFormat::Json => {
+            let batches = batch_list
+                .get_batches()
+                .iter()
+                .map(|batch| JsonBatch {
+                    header_signature: batch.get_header_signature().to_string(),
+                    header: base64::encode(batch.get_header()),
+                    trace: batch.get_trace(),
+                    transactions: batch
+                        .get_transactions()
+                        .iter()
+                        .map(|txn| JsonTransaction {
+                            header_signature: txn.get_header_signature().to_string(),
+                            header: base64::encode(txn.get_header()),
+                            payload: base64::encode(txn.get_payload()),
+                        })
+                        .collect(),
+                })
+                .collect();
+            serde_json::to_vec_pretty(&JsonBatchList { batches }).map_err(|err| {
+                CliError::UserError(format!("Unable to serialize batch list: {}", err))
+            })
+        }
+    }
+}
+
+/// Checks that a batch file is not about to overwrite an existing file
+///
+/// # Errors
+///
+/// If the file exists, a `CliError::InvalidInputError` is returned.
+pub fn check_new_batch_file(path: &str) -> Result<(), CliError> {
+    if Path::new(path).exists() {
+        return Err(CliError::InvalidInputError(format!(
+            "{} already exists. Remove it, or write the batches to another file",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// Writes the batch list to the given file in the given format
+///
+/// With `manifest`, a `BatchManifest` of the file is also written next to it,
+/// to `<path>.json`.
+///
+/// # Errors
+///
+/// If the batch list cannot be serialized, the error from `encode_batch_list`
+/// is returned. If a file cannot be written, a `CliError::IoError` is
+/// returned.
+pub fn write_batch_file(
+    batch_list: &BatchList,
+    path: &str,
+    format: BatchFormat,
+    manifest: bool,
+) -> Result<(), CliError> {
+    let bytes = encode_batch_list(batch_list, format)?;
+    fs::write(path, &bytes)?;
+
+    if manifest {
+        let manifest = BatchManifest::new(batch_list, &bytes)?;
+        let manifest_bytes = serde_json::to_vec_pretty(&manifest).map_err(|err| {
+            CliError::UserError(format!("Unable to serialize batch manifest: {}", err))
+        })?;
+        fs::write(format!("{}.json", path), manifest_bytes)?;
+    }
+
+    Ok(())
+}
+
+/// What a batch file holds, so that it can be checked and tracked without
+/// decoding it
+#[derive(Debug, Serialize)]
+struct BatchManifest {
+    /// The SHA-256 of the file's bytes, in hex
+    sha256: String,
+    batches: Vec<BatchManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct BatchManifestEntry {
+    id: String,
+    /// Whether the batch is signed. The ids of a batch written with
+    /// `--sign-later`, and of its transactions, are placeholders which change
+    /// once it is signed with `batch sign`
+    signed: bool,
+    /// The SHA-256 of the batch's serialized protobuf bytes, in hex
+    sha256: String,
+    transaction_ids: Vec<String>,
+}
+
+impl BatchManifest {
+    fn new(batch_list: &BatchList, file_bytes: &[u8]) -> Result<BatchManifest, CliError> {
+        let batches = batch_list
+            .get_batches()
+            .iter()
+            .map(|batch| {
+                Ok(BatchManifestEntry {
+                    id: batch.get_header_signature().to_string(),
+                    signed: batch.get_header_signature()
+                        != placeholder_signature(batch.get_header()),
+                    sha256: sha256_hex(&batch.write_to_bytes()?),
+                    transaction_ids: batch
+                        .get_transactions()
+                        .iter()
+                        .map(|txn| txn.get_header_signature().to_string())
+                        .collect(),
+                })
+            })
+            .collect::<Result<_, CliError>>()?;
+
+        Ok(BatchManifest {
+            sha256: sha256_hex(file_bytes),
+            batches,
+        })
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    let mut sha = Sha256::new();
+    sha.input(bytes);
+    sha.result_str()
+}
+
+/// Parses a batch list serialized in the given format
+///
+/// # Errors
+///
+/// If the bytes are not a batch list in the given format, a
+/// `CliError::InvalidInputError` is returned.
+pub fn decode_batch_list(bytes: &[u8], format: BatchFormat) -> Result<BatchList, CliError> {
+    let invalid = |err: String| {
+        CliError::InvalidInputError(format!("Unable to parse {:?} batch list: {}", format, err))
+    };
+    let decode = |text: &str| base64::decode(text.trim()).map_err(|err| invalid(err.to_string()));
+
+    match format {
+        BatchFormat::Protobuf => {
+            protobuf::parse_from_bytes(bytes).map_err(|err| invalid(err.to_string()))
+        }
+        BatchFormat::Base64 => {
+            let text = std::str::from_utf8(bytes).map_err(|err| invalid(err.to_string()))?;
+            protobuf::parse_from_bytes(&decode(text)?).map_err(|err| invalid(err.to_string()))
+        }
+        BatchFormat::Json => {
+            let json: JsonBatchList =
+                serde_json::from_slice(bytes).map_err(|err| invalid(err.to_string()))?;
+            let mut batches = vec![];
+            for json_batch in json.batches {
+                let mut transactions = vec![];
+                for json_txn in json_batch.transactions {
+                    let mut txn = Transaction::new();
+                    txn.set_header_signature(json_txn.header_signature);
+                    txn.set_header(decode(&json_txn.header)?);
+                    txn.set_payload(decode(&json_txn.payload)?);
+                    transactions.push(txn);
+                }
+
+                let mut batch = Batch::new();
+                batch.set_header_signature(json_batch.header_signature);
+                batch.set_header(decode(&json_batch.header)?);
+                batch.set_trace(json_batch.trace);
+                batch.set_transactions(RepeatedField::from_vec(transactions));
+                batches.push(batch);
+            }
+
+            let mut batch_list = BatchList::new();
+            batch_list.set_batches(RepeatedField::from_vec(batches));
+            Ok(batch_list)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch_list() -> BatchList {
+        let mut txn = Transaction::new();
+        txn.set_header_signature(String::from("txn-id"));
+        txn.set_header(vec![1, 2, 3]);
+        txn.set_payload(vec![4, 5, 6]);
+
+        let mut batch = Batch::new();
+        batch.set_header_signature(String::from("batch-id"));
+        batch.set_header(vec![7, 8, 9]);
+        batch.set_transactions(RepeatedField::from_vec(vec![txn]));
+
+        let mut batch_list = BatchList::new();
+        batch_list.set_batches(RepeatedField::from_vec(vec![batch]));
+        batch_list
+    }
+
+    #[test]
+    fn batch_list_round_trip_test() {
+        for format in &[
+            BatchFormat::Protobuf,
+            BatchFormat::Base64,
+            BatchFormat::Json,
+        ] {
+            let bytes = encode_batch_list(&batch_list(), *format).unwrap();
+            assert_eq!(decode_batch_list(&bytes, *format).unwrap(), batch_list());
+        }
+    }
+
+    #[test]
+    fn batch_manifest_test() {
+        let manifest = BatchManifest::new(&batch_list(), b"").unwrap();
+        assert_eq!(
+            manifest.sha256,
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(manifest.batches.len(), 1);
+        assert_eq!(manifest.batches[0].id, "batch-id");
+        assert!(manifest.batches[0].signed);
+        assert_eq!(manifest.batches[0].transaction_ids, vec!["txn-id"]);
+        assert_eq!(
+            manifest.batches[0].sha256,
+            sha256_hex(&batch_list().get_batches()[0].write_to_bytes().unwrap())
+        );
+    }
+
+    #[test]
+    fn unsigned_batch_manifest_test() {
+        let mut batch_list = batch_list();
+        let placeholder = placeholder_signature(batch_list.get_batches()[0].get_header());
+        batch_list.mut_batches().as_mut_slice()[0].set_header_signature(placeholder.clone());
+
+        let manifest = BatchManifest::new(&batch_list, b"").unwrap();
+        assert_eq!(manifest.batches[0].id, placeholder);
+        assert!(!manifest.batches[0].signed);
+    }
+}
@@ -1,117 +1,213 @@
+use crate::commands;
+use crate::commands::CommandOutcome;
 use crate::error::CliError;
 use crate::key;
 use crate::submit;
-use crate::transaction::{create_batch, create_batch_list_from_one, create_transaction};
+use crate::transaction::{
+    create_batch, create_batch_list, create_batch_list_from_one, create_batch_with_transactions,
+    create_transaction, TransactionOptions,
+};
 
-use chrono::NaiveDateTime;
 use clap::ArgMatches;
 use common::addressing;
 use common::proto::payload::AccreditCertifyingBodyAction;
 use common::proto::payload::{CertificateRegistryPayload, CertificateRegistryPayload_Action};
+use sawtooth_sdk::messages::transaction::Transaction;
 use sawtooth_sdk::signing;
-use std::{thread, time};
+use serde_derive::Deserialize;
 
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     match args.subcommand() {
         ("create", Some(args)) => run_create_command(args),
+        ("batch_create", Some(args)) => run_batch_create_command(args),
         _ => Err(CliError::InvalidInputError(String::from(
             "Invalid subcommand. Pass --help for usage",
         ))),
     }
 }
 
-fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
-    let certifying_body_id = args.value_of("certifying_body_id").unwrap();
-    let standards_body_id = args.value_of("standards_body_id").unwrap();
-    let standard_id = args.value_of("standard_id").unwrap();
-    let valid_from = args.value_of("valid_from").unwrap();
-    let valid_to = args.value_of("valid_to").unwrap();
-    let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+fn run_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let certifying_body_id = commands::require_arg(args, "certifying_body_id")?;
+    let standards_body_id = commands::require_arg(args, "standards_body_id")?;
+    let standard_id = commands::require_arg(args, "standard_id")?;
+    commands::validate_id("certifying body", certifying_body_id)?;
+    commands::validate_id("standards body", standards_body_id)?;
+    commands::validate_id("standard", standard_id)?;
+    let valid_from = commands::parse_timestamp(commands::require_arg(args, "valid_from")?)? as u64;
+    let valid_to = commands::parse_timestamp(commands::require_arg(args, "valid_to")?)? as u64;
+    let key = key::signing_key_for_org(args, standards_body_id)?;
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
 
-    //check valid_from is valid timestamp
-    if NaiveDateTime::parse_from_str(valid_from, "%s").is_err() {
-        return Err(CliError::UserError(
-            "Valid from date is invalid. Please provide time in seconds since Unix epoch"
-                .to_string(),
-        ));
-    }
-    //check valid_to is valid timestamp
-    if NaiveDateTime::parse_from_str(valid_to, "%s").is_err() {
-        return Err(CliError::UserError(
-            "Valid to date is invalid. Please provide time in seconds since Unix epoch".to_string(),
-        ));
+    let payload =
+        create_accreditation_payload(standard_id, certifying_body_id, valid_from, valid_to);
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
     }
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context("secp256k1")?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let signing_context = key::SigningContext::new(key.as_deref(), &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
-    let payload = create_accreditation_payload(
+    let (inputs, outputs) = create_accreditation_transaction_addresses(
+        &signer,
         standard_id,
         certifying_body_id,
-        valid_from.parse::<u64>().unwrap(),
-        valid_to.parse::<u64>().unwrap(),
-    );
-
-    let standard_address = addressing::make_standard_address(&standard_id);
-    let agent_address = addressing::make_agent_address(&signer.get_public_key()?.as_hex());
-    let certifying_body_address = addressing::make_organization_address(&certifying_body_id);
-    let standards_body_address = addressing::make_organization_address(&standards_body_id);
+        standards_body_id,
+    )?;
 
     let txn = create_transaction(
         &payload,
         &signer,
-        vec![
-            standard_address,
-            agent_address,
-            certifying_body_address.clone(),
-            standards_body_address,
-        ],
-        vec![certifying_body_address],
+        batcher.as_ref(),
+        inputs,
+        outputs,
+        vec![],
+        &options,
     )?;
-    let batch = create_batch(txn, &signer)?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!(
-                    "Accredited standards body {} with standard {} from {} to {}",
-                    standard_id, standard_id, valid_from, valid_to
-                );
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                status = submit::wait_for_status(url, &status.link)?;
-            }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    let mut outcome = CommandOutcome::submitted(
+        Some(certifying_body_id.to_string()),
+        &batch_status,
+        accredited_message(certifying_body_id, standard_id, valid_from, valid_to),
+    );
+    for (name, value) in &[
+        ("certifying_body_id", certifying_body_id.to_string()),
+        ("standards_body_id", standards_body_id.to_string()),
+        ("standard_id", standard_id.to_string()),
+        ("valid_from", valid_from.to_string()),
+        ("valid_to", valid_to.to_string()),
+    ] {
+        outcome.details.insert(name.to_string(), value.clone());
+    }
+    Ok(outcome)
+}
+
+/// Returns the message reported once a certifying body has been accredited
+fn accredited_message(
+    certifying_body_id: &str,
+    standard_id: &str,
+    valid_from: u64,
+    valid_to: u64,
+) -> String {
+    format!(
+        "Certifying body {} accredited for standard {} from {} to {}",
+        certifying_body_id, standard_id, valid_from, valid_to
+    )
+}
+
+/// An entry of the JSON array read by `accreditation batch_create`
+///
+/// The timestamps take the same forms as on the command line, and may also be
+/// given as JSON numbers of seconds since the Unix epoch.
+#[derive(Deserialize, Debug)]
+struct AccreditationEntry {
+    certifying_body_id: String,
+    standards_body_id: String,
+    standard_id: String,
+    valid_from: serde_json::Value,
+    valid_to: serde_json::Value,
+}
+
+impl AccreditationEntry {
+    /// Returns the key identifying the entry in the `--error-log`
+    fn key(&self) -> String {
+        format!("{}:{}", self.certifying_body_id, self.standard_id)
+    }
+}
+
+fn run_batch_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let key = args.value_of("key");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+
+    // Read accreditations from provided batch file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let entries: Vec<AccreditationEntry> = commands::read_batch_file(args)?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
+    let entry_keys: Vec<String> = entries.iter().map(AccreditationEntry::key).collect();
+
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+
+    // Validate every entry and build its transaction before submitting any
+    eprintln!("Creating transactions for {}", filepath);
+    let mut txn_list: Vec<Transaction> = vec![];
+    let mut txn_entries: Vec<(String, &str)> = vec![];
+    let mut accredited = vec![];
+    for (entry, entry_key) in entries.iter().zip(&entry_keys) {
+        if !entry_filter.includes(entry_key) {
+            continue;
+        }
+        commands::validate_id("certifying body", &entry.certifying_body_id)?;
+        commands::validate_id("standards body", &entry.standards_body_id)?;
+        commands::validate_id("standard", &entry.standard_id)?;
+        let valid_from = commands::entry_timestamp(entry_key, "valid_from", &entry.valid_from)?;
+        let valid_to = commands::entry_timestamp(entry_key, "valid_to", &entry.valid_to)?;
+        if valid_to <= valid_from {
+            return Err(CliError::InvalidInputError(format!(
+                "Accreditation {} has a valid_to of {}, which is not after its valid_from of {}",
+                entry_key, valid_to, valid_from
+            )));
         }
+
+        let payload = create_accreditation_payload(
+            &entry.standard_id,
+            &entry.certifying_body_id,
+            valid_from,
+            valid_to,
+        );
+        let (inputs, outputs) = create_accreditation_transaction_addresses(
+            &signer,
+            &entry.standard_id,
+            &entry.certifying_body_id,
+            &entry.standards_body_id,
+        )?;
+        let txn = create_transaction(
+            &payload,
+            &signer,
+            batcher.as_ref(),
+            inputs,
+            outputs,
+            vec![],
+            &options,
+        )?;
+        txn_entries.push((txn.header_signature.clone(), entry_key.as_str()));
+        txn_list.push(txn);
+        accredited.push(accredited_message(
+            &entry.certifying_body_id,
+            &entry.standard_id,
+            valid_from,
+            valid_to,
+        ));
     }
+
+    if txn_list.is_empty() {
+        return Ok(commands::nothing_to_submit(filepath, "accreditations"));
+    }
+
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
+    let batch_list = create_batch_list(vec![batch]);
+
+    let batch_status =
+        client.submit_batch_and_wait(&batch_list, &txn_entries, max_invalid_display)?;
+
+    let mut outcome = CommandOutcome::submitted(None, &batch_status, accredited.join("\n"));
+    outcome.details = txn_entries
+        .iter()
+        .map(|(txn_id, entry_key)| (entry_key.to_string(), txn_id.clone()))
+        .collect();
+    Ok(outcome)
 }
 
-fn create_accreditation_payload(
+pub fn create_accreditation_payload(
     standard_id: &str,
     certifying_body_id: &str,
     valid_from: u64,
@@ -128,3 +224,26 @@ fn create_accreditation_payload(
     payload.set_accredit_certifying_body_action(accreditation);
     payload
 }
+
+/// Returns the input and output addresses of a transaction, signed by an agent
+/// of the standards body, accrediting a certifying body for a standard
+pub fn create_accreditation_transaction_addresses(
+    signer: &signing::Signer,
+    standard_id: &str,
+    certifying_body_id: &str,
+    standards_body_id: &str,
+) -> Result<(Vec<String>, Vec<String>), CliError> {
+    let standard_address = addressing::make_standard_address(standard_id);
+    let agent_address = addressing::make_agent_address(&signer.get_public_key()?.as_hex());
+    let certifying_body_address = addressing::make_organization_address(certifying_body_id);
+    let standards_body_address = addressing::make_organization_address(standards_body_id);
+    Ok((
+        vec![
+            standard_address,
+            agent_address,
+            certifying_body_address.clone(),
+            standards_body_address,
+        ],
+        vec![certifying_body_address],
+    ))
+}
@@ -0,0 +1,45 @@
+use crate::commands;
+use crate::commands::CommandOutcome;
+use crate::error::CliError;
+use crate::key;
+
+use clap::ArgMatches;
+use common::addressing;
+
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let (make_address, id): (fn(&str) -> String, String) = match args.subcommand() {
+        ("agent", Some(args)) => (
+            addressing::make_agent_address,
+            key::normalize_public_key(commands::require_arg(args, "public_key")?)?,
+        ),
+        ("organization", Some(args)) => (
+            addressing::make_organization_address,
+            commands::require_arg(args, "id")?.to_string(),
+        ),
+        ("certificate", Some(args)) => (
+            addressing::make_certificate_address,
+            commands::require_arg(args, "id")?.to_string(),
+        ),
+        ("standard", Some(args)) => (
+            addressing::make_standard_address,
+            commands::require_arg(args, "id")?.to_string(),
+        ),
+        ("assertion", Some(args)) => (
+            addressing::make_assertion_address,
+            commands::require_arg(args, "id")?.to_string(),
+        ),
+        _ => {
+            return Err(CliError::InvalidInputError(String::from(
+                "Invalid subcommand. Pass --help for usage",
+            )))
+        }
+    };
+
+    commands::validate_id(args.subcommand_name().unwrap_or("object"), &id)?;
+    let address = make_address(&id);
+
+    let mut outcome = CommandOutcome::completed(address.clone());
+    outcome.id = Some(id);
+    outcome.details.insert(String::from("address"), address);
+    Ok(outcome)
+}
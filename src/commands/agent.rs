@@ -1,32 +1,44 @@
+use crate::commands;
+use crate::commands::CommandOutcome;
 use crate::error::CliError;
 use crate::key;
-use crate::submit;
-use crate::transaction::{create_batch, create_batch_list_from_one, create_transaction};
+use crate::submit::{self, WRITTEN_STATUS};
+use crate::transaction::{
+    create_batch, create_batch_list, create_batch_list_from_one, create_batch_with_transactions,
+    create_transaction, TransactionOptions,
+};
 
 use clap::ArgMatches;
 use common::addressing;
 use common::proto::organization::Organization_Authorization_Role;
 use common::proto::payload::{AuthorizeAgentAction, CreateAgentAction};
 use common::proto::payload::{CertificateRegistryPayload, CertificateRegistryPayload_Action};
-use sawtooth_sdk::messages::batch::BatchList;
-use sawtooth_sdk::signing;
-use std::time::{SystemTime, UNIX_EPOCH};
-use std::{thread, time};
+use sawtooth_sdk::messages::batch::{Batch, BatchList};
+use sawtooth_sdk::messages::transaction::Transaction;
+use sawtooth_sdk::signing::{self, CryptoFactory, Signer};
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     match args.subcommand() {
         ("create", Some(args)) => run_create_command(args),
         ("authorize", Some(args)) => run_authorize_command(args),
+        ("batch_authorize", Some(args)) => run_batch_authorize_command(args),
         _ => Err(CliError::InvalidInputError(String::from(
             "Invalid subcommand. Pass --help for usage",
         ))),
     }
 }
 
-fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
-    let name = args.value_of("name").unwrap();
+fn run_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let name = commands::require_arg(args, "name")?;
+    if args.is_present("repeat") {
+        return run_repeated_create_command(args, name);
+    }
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
     let start = SystemTime::now();
     let since_the_epoch = start
         .duration_since(UNIX_EPOCH)
@@ -34,88 +46,387 @@ fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
 
     let ms_since_epoch = since_the_epoch.as_secs();
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context("secp256k1")?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
-
     let payload = create_agent_payload(name, ms_since_epoch);
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
+    }
+
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+
     let header_input = create_agent_transaction_addresses(&signer.get_public_key()?.as_hex());
     let header_output = header_input.clone();
-    let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        &options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let public_key = context.get_public_key(&private_key)?.as_hex();
-    agent_status_handler(&public_key, "create", url, &batch_list)
+    let public_key = signer.get_public_key()?.as_hex();
+    agent_status_handler(&public_key, "create", &client, &batch_list)
+}
+
+/// Creates `--repeat` agents, named after `name` with a numbered suffix, for
+/// load testing a network
+///
+/// Agents are identified by their public key, so each agent is signed by a
+/// fresh random key, which is thrown away afterwards. The command's key
+/// batches the transactions, `--batch-size` at a time. Every batch is signed
+/// before the first is submitted, so that only the submissions are timed.
+fn run_repeated_create_command(args: &ArgMatches, name: &str) -> Result<CommandOutcome, CliError> {
+    let repeat = match value_t!(args, "repeat", usize) {
+        Ok(repeat) if repeat > 0 => repeat,
+        _ => {
+            return Err(CliError::InvalidInputError(String::from(
+                "Invalid value for --repeat. Please provide a positive number",
+            )))
+        }
+    };
+    // Each agent is signed by a key which is thrown away, so its transaction
+    // could never be signed later
+    if args.is_present("sign_later") {
+        return Err(CliError::InvalidInputError(String::from(
+            "--sign-later cannot be used with --repeat, whose agents are signed by keys which are not kept",
+        )));
+    }
+    if args.is_present("payload_only") {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_secs();
+        let payloads: Vec<_> = (1..=repeat)
+            .map(|index| create_agent_payload(&format!("{}-{}", name, index), timestamp))
+            .collect();
+        return commands::payload_only_outcome(&payloads.iter().collect::<Vec<_>>());
+    }
+    let batch_size = commands::batch_size_from_args(args)?;
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+
+    let signing_context = key::SigningContext::new(args.value_of("key"), &options)?;
+    let batch_signer = signing_context
+        .batcher()
+        .unwrap_or_else(|| signing_context.signer());
+    let batches = create_repeated_agent_batches(name, repeat, batch_size, &batch_signer, &options)?;
+
+    let started = Instant::now();
+    let mut committed = 0;
+    let mut batch_status = None;
+    for batch in &batches {
+        let status = client.submit_and_wait(&create_batch_list_from_one(batch.clone()))?;
+        if status.status == "COMMITTED" {
+            committed += batch.get_transactions().len();
+        }
+        batch_status = Some(status);
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let written = batch_status
+        .as_ref()
+        .map_or(false, |status| status.status == WRITTEN_STATUS);
+    let message = if written {
+        format!(
+            "Wrote {} agents in {} batches to the --output-batch file without submitting them",
+            repeat,
+            batches.len()
+        )
+    } else if committed > 0 {
+        format!(
+            "Committed {} agents in {} batches in {:.1}s, {:.1} agents per second",
+            committed,
+            batches.len(),
+            elapsed,
+            committed as f64 / elapsed
+        )
+    } else {
+        format!(
+            "Submitted {} agents in {} batches in {:.1}s",
+            repeat,
+            batches.len(),
+            elapsed
+        )
+    };
+    let mut outcome = CommandOutcome::completed(message);
+    if let Some(batch_status) = batch_status {
+        outcome.batch_id = Some(batch_status.id);
+        outcome.status = batch_status.status;
+    }
+    outcome
+        .details
+        .insert(String::from("agents"), repeat.to_string());
+    outcome
+        .details
+        .insert(String::from("batches"), batches.len().to_string());
+    outcome
+        .details
+        .insert(String::from("committed"), committed.to_string());
+    outcome
+        .details
+        .insert(String::from("elapsed_seconds"), format!("{:.3}", elapsed));
+    Ok(outcome)
+}
+
+/// Returns batches of `batch_size` transactions creating `repeat` agents,
+/// named `name-1` to `name-<repeat>`, each signed by a new random key and
+/// batched by `batch_signer`
+///
+/// # Errors
+///
+/// If a key cannot be generated, or a transaction or batch signed, a
+/// `CliError::SigningError` is returned.
+fn create_repeated_agent_batches(
+    name: &str,
+    repeat: usize,
+    batch_size: usize,
+    batch_signer: &Signer,
+    options: &TransactionOptions,
+) -> Result<Vec<Batch>, CliError> {
+    let context = signing::create_context(&options.signing_algorithm)?;
+    let factory = CryptoFactory::new(&*context);
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_secs();
+
+    let indices: Vec<usize> = (1..=repeat).collect();
+    indices
+        .chunks(batch_size)
+        .map(|chunk| {
+            let mut txns = vec![];
+            for index in chunk {
+                let private_key = context.new_random_private_key()?;
+                let signer = factory.new_signer(&*private_key);
+                let addresses =
+                    create_agent_transaction_addresses(&signer.get_public_key()?.as_hex());
+                txns.push(create_transaction(
+                    &create_agent_payload(&format!("{}-{}", name, index), timestamp),
+                    &signer,
+                    Some(batch_signer),
+                    addresses.clone(),
+                    addresses,
+                    vec![],
+                    options,
+                )?);
+            }
+            create_batch_with_transactions(txns, batch_signer, None)
+        })
+        .collect()
 }
 
-fn run_authorize_command(args: &ArgMatches) -> Result<(), CliError> {
-    let agent_to_be_authorized = args.value_of("authorize_agent").unwrap(); // Pub key of agent we want to authorize
-    let org_id = args.value_of("org_id").unwrap();
-    let role = args.value_of("role").unwrap();
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+fn run_authorize_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    // Pub key of agent we want to authorize
+    let agent_to_be_authorized =
+        &key::normalize_public_key(commands::require_arg(args, "authorize_agent")?)?;
+    let org_id = commands::require_arg(args, "org_id")?;
+    commands::validate_id("organization", org_id)?;
+    let roles: Vec<&str> = commands::require_values(args, "role")?.collect();
+    // Build the payloads up front, so that every role is validated before
+    // anything is signed
+    let payloads = authorize_agent_payloads(agent_to_be_authorized, &roles)?;
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&payloads.iter().collect::<Vec<_>>());
+    }
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
     let key = args.value_of("key"); // Priv key file of the agent doing the authorizing
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context("secp256k1")?;
-    let public_key = context.get_public_key(&private_key)?.as_hex();
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
-
-    let payload = authorize_agent_payload(agent_to_be_authorized, role);
-    let addresses_input =
-        authorize_agent_transaction_addresses_input(&public_key, &org_id, &agent_to_be_authorized);
-    let addresses_output = vec![
-        addressing::make_organization_address(&org_id),
-        addressing::make_agent_address(&agent_to_be_authorized),
-    ];
-
-    let txn = create_transaction(&payload, &signer, addresses_input, addresses_output)?;
-    let batch = create_batch(txn, &signer)?;
+    if args.is_present("check") && !client.organization_exists(org_id)? {
+        return Err(CliError::UserError(format!(
+            "Organization {} does not exist",
+            org_id
+        )));
+    }
+
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+    let public_key = signer.get_public_key()?.as_hex();
+
+    // An authorization grants a single role, so each role gets its own
+    // transaction, all in one batch so that they are committed together
+    let mut txns = vec![];
+    for payload in payloads {
+        let addresses_input = authorize_agent_transaction_addresses_input(
+            &public_key,
+            &org_id,
+            &agent_to_be_authorized,
+        );
+        let addresses_output = vec![
+            addressing::make_organization_address(&org_id),
+            addressing::make_agent_address(&agent_to_be_authorized),
+        ];
+        txns.push(create_transaction(
+            &payload,
+            &signer,
+            batcher.as_ref(),
+            addresses_input,
+            addresses_output,
+            vec![],
+            &options,
+        )?);
+    }
+    let batch = create_batch_with_transactions(txns, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    agent_status_handler(&public_key, "authorize", url, &batch_list)
+    agent_status_handler(&public_key, "authorize", &client, &batch_list)
+}
+
+/// An entry of the JSON file read by `agent batch_authorize`, keyed by the
+/// public key of the agent being authorized
+#[derive(Deserialize, Debug)]
+struct AgentAuthorization {
+    org_id: String,
+    role: String,
+}
+
+fn run_batch_authorize_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+    let key = args.value_of("key"); // Priv key file of the agent doing the authorizing
+
+    // Read authorizations from provided batch file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let authorizations: BTreeMap<String, AgentAuthorization> = commands::read_batch_file(args)?;
+    let authorizations = authorizations
+        .into_iter()
+        .map(|(public_key, authorization)| {
+            Ok((key::normalize_public_key(&public_key)?, authorization))
+        })
+        .collect::<Result<BTreeMap<_, _>, CliError>>()?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
+
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+    let public_key = signer.get_public_key()?.as_hex();
+
+    // Loop through map of authorizations and populate list of transactions,
+    // remembering which agent each transaction authorizes
+    eprintln!("Creating transactions for {}", filepath);
+    let mut txn_list: Vec<Transaction> = vec![];
+    let mut txn_agents: Vec<(String, &str)> = vec![];
+    for (agent_to_be_authorized, authorization) in &authorizations {
+        if !entry_filter.includes(agent_to_be_authorized) {
+            continue;
+        }
+        commands::validate_id("organization", &authorization.org_id)?;
+
+        let payload = authorize_agent_payload(agent_to_be_authorized, &authorization.role)
+            .map_err(|_| {
+                CliError::InvalidInputError(format!(
+                    "Invalid role {:?} for agent {}. Valid roles are 1 (ADMIN) or 2 (TRANSACTOR)",
+                    authorization.role, agent_to_be_authorized
+                ))
+            })?;
+        let addresses_input = authorize_agent_transaction_addresses_input(
+            &public_key,
+            &authorization.org_id,
+            agent_to_be_authorized,
+        );
+        let addresses_output = vec![
+            addressing::make_organization_address(&authorization.org_id),
+            addressing::make_agent_address(agent_to_be_authorized),
+        ];
+
+        let txn = create_transaction(
+            &payload,
+            &signer,
+            batcher.as_ref(),
+            addresses_input,
+            addresses_output,
+            vec![],
+            &options,
+        )?;
+        txn_agents.push((
+            txn.header_signature.clone(),
+            agent_to_be_authorized.as_str(),
+        ));
+        txn_list.push(txn);
+    }
+
+    if txn_list.is_empty() {
+        return Ok(commands::nothing_to_submit(filepath, "authorizations"));
+    }
+
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
+    let batch_list = create_batch_list(vec![batch]);
+
+    submit_authorizations(
+        &client,
+        &batch_list,
+        &txn_agents,
+        &authorizations,
+        max_invalid_display,
+    )
+}
+
+/// Submits a batch of agent authorizations, reporting each agent which could
+/// not be authorized if the batch is rejected
+///
+/// `txn_agents` pairs the id of each transaction with the public key of the
+/// agent it authorizes.
+fn submit_authorizations(
+    client: &submit::ApiClient,
+    batch_list: &BatchList,
+    txn_agents: &[(String, &str)],
+    authorizations: &BTreeMap<String, AgentAuthorization>,
+    max_invalid_display: usize,
+) -> Result<CommandOutcome, CliError> {
+    let status = client.submit_and_get_status(batch_list)?;
+    client.push_metrics(batch_list, &status);
+
+    if status.status == "INVALID" {
+        client.log_invalid_transactions(&status, txn_agents)?;
+        submit::report_invalid_transactions(&status, max_invalid_display, |invalid_transaction| {
+            let agent = txn_agents
+                .iter()
+                .find(|(txn_id, _)| *txn_id == invalid_transaction.id)
+                .map(|(_, agent)| *agent)
+                .unwrap_or("unknown");
+            format!(
+                "Agent {} could not be authorized: {}",
+                agent, invalid_transaction.message
+            )
+        });
+        return Err(submit::invalid_transaction_error(&status));
+    }
+
+    let message = txn_agents
+        .iter()
+        .map(|(_, agent)| {
+            format!(
+                "Agent {} has been authorized for organization {}",
+                agent, authorizations[*agent].org_id
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(CommandOutcome::submitted(None, &status, message))
 }
 
 fn agent_status_handler(
     public_key: &str,
     action: &str,
-    url: &str,
+    client: &submit::ApiClient,
     batch_list: &BatchList,
-) -> Result<(), CliError> {
-    let mut agent_status = submit::submit_batch_list(url, batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match agent_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Agent {} has been {}d", public_key, action);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    agent_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                agent_status = submit::wait_for_status(&url, &agent_status.link)?;
-            }
-        }
-    }
+) -> Result<CommandOutcome, CliError> {
+    let batch_status = client.submit_and_wait(batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(public_key.to_string()),
+        &batch_status,
+        format!("Agent {} has been {}d", public_key, action),
+    ))
 }
 
 /// Returns a payload for creating an Agent
@@ -131,23 +442,55 @@ pub fn create_agent_payload(name: &str, timestamp: u64) -> CertificateRegistryPa
 }
 
 /// Returns a payload for to authorize an Agent
-fn authorize_agent_payload(pub_key: &str, role: &str) -> CertificateRegistryPayload {
+///
+/// # Errors
+///
+/// If the role is not 1 (ADMIN) or 2 (TRANSACTOR), a `CliError::UserError` is
+/// returned.
+pub fn authorize_agent_payload(
+    pub_key: &str,
+    role: &str,
+) -> Result<CertificateRegistryPayload, CliError> {
     let mut agent = AuthorizeAgentAction::new();
     agent.set_public_key(String::from(pub_key));
     match role {
         "1" => agent.set_role(Organization_Authorization_Role::ADMIN),
         "2" => agent.set_role(Organization_Authorization_Role::TRANSACTOR),
-        x => Err(CliError::UserError(format!(
-            "Unexpected invalid role {:?}",
-            x
-        )))
-        .unwrap(),
+        x => {
+            return Err(CliError::UserError(format!(
+                "Unexpected invalid role {:?}",
+                x
+            )))
+        }
     }
 
     let mut payload = CertificateRegistryPayload::new();
     payload.action = CertificateRegistryPayload_Action::AUTHORIZE_AGENT;
     payload.set_authorize_agent(agent);
-    payload
+    Ok(payload)
+}
+
+/// Returns a payload authorizing an Agent for each of the given roles
+///
+/// # Errors
+///
+/// If a role is invalid or given more than once, a `CliError::UserError` is
+/// returned.
+fn authorize_agent_payloads(
+    pub_key: &str,
+    roles: &[&str],
+) -> Result<Vec<CertificateRegistryPayload>, CliError> {
+    let mut payloads = vec![];
+    for (index, role) in roles.iter().enumerate() {
+        if roles[..index].contains(role) {
+            return Err(CliError::UserError(format!(
+                "Role {} is given more than once",
+                role
+            )));
+        }
+        payloads.push(authorize_agent_payload(pub_key, role)?);
+    }
+    Ok(payloads)
 }
 
 pub fn create_agent_transaction_addresses(public_key: &str) -> Vec<String> {
@@ -155,7 +498,7 @@ pub fn create_agent_transaction_addresses(public_key: &str) -> Vec<String> {
     vec![agent_address]
 }
 
-fn authorize_agent_transaction_addresses_input(
+pub fn authorize_agent_transaction_addresses_input(
     authorizer_public_key: &str,
     org_id: &str,
     authee_pub_key: &str,
@@ -165,3 +508,109 @@ fn authorize_agent_transaction_addresses_input(
     let authee_agent_address = addressing::make_agent_address(authee_pub_key);
     vec![authorizer_agent_address, org_address, authee_agent_address]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sawtooth_sdk::messages::transaction::TransactionHeader;
+
+    const PUBLIC_KEY: &str = "02a0c4bdbd59a4c87c1bd4b2d6a5e8a7e3d4e0a35d0d27a1ea34b9f82b42ee7f1c";
+
+    #[test]
+    fn create_repeated_agent_batches_test() {
+        let context = signing::create_context(key::SECP_256K1).unwrap();
+        let private_key = context.new_random_private_key().unwrap();
+        let batch_signer = CryptoFactory::new(&*context).new_signer(&*private_key);
+        let batcher_public_key = batch_signer.get_public_key().unwrap().as_hex();
+
+        let batches = create_repeated_agent_batches(
+            "load",
+            5,
+            2,
+            &batch_signer,
+            &TransactionOptions::default(),
+        )
+        .unwrap();
+        let batch_sizes: Vec<usize> = batches
+            .iter()
+            .map(|batch| batch.get_transactions().len())
+            .collect();
+        assert_eq!(batch_sizes, vec![2, 2, 1]);
+
+        let mut signers = vec![];
+        for txn in batches.iter().flat_map(|batch| batch.get_transactions()) {
+            let header: TransactionHeader = protobuf::parse_from_bytes(txn.get_header()).unwrap();
+            assert_eq!(header.get_batcher_public_key(), batcher_public_key);
+            signers.push(header.get_signer_public_key().to_string());
+        }
+        signers.sort();
+        signers.dedup();
+        assert_eq!(signers.len(), 5);
+    }
+
+    #[test]
+    fn agent_status_handler_test() {
+        let batch_list = submit::mock::batch_list();
+
+        let client = submit::mock::client(&["PENDING", "COMMITTED"]);
+        let outcome = agent_status_handler(PUBLIC_KEY, "authorize", &client, &batch_list).unwrap();
+        assert_eq!(outcome.status, "COMMITTED");
+        assert_eq!(
+            outcome.message,
+            format!("Agent {} has been authorized", PUBLIC_KEY)
+        );
+
+        let client = submit::mock::client(&["INVALID"]);
+        assert!(agent_status_handler(PUBLIC_KEY, "authorize", &client, &batch_list).is_err());
+    }
+
+    #[test]
+    fn submit_authorizations_test() {
+        let batch_list = submit::mock::batch_list();
+        let txn_agents = vec![(String::from("txn-id"), PUBLIC_KEY)];
+        let mut authorizations = BTreeMap::new();
+        authorizations.insert(
+            PUBLIC_KEY.to_string(),
+            AgentAuthorization {
+                org_id: String::from("org-1"),
+                role: String::from("2"),
+            },
+        );
+
+        let client = submit::mock::client(&["COMMITTED"]);
+        let outcome =
+            submit_authorizations(&client, &batch_list, &txn_agents, &authorizations, 10).unwrap();
+        assert_eq!(
+            outcome.message,
+            format!(
+                "Agent {} has been authorized for organization org-1",
+                PUBLIC_KEY
+            )
+        );
+
+        let client = submit::mock::client(&["INVALID"]);
+        match submit_authorizations(&client, &batch_list, &txn_agents, &authorizations, 10) {
+            Err(CliError::InvalidTransactionError(message)) => assert_eq!(message, "Invalid"),
+            result => panic!("Expected an invalid transaction error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn authorize_agent_payloads_test() {
+        let payloads = authorize_agent_payloads(PUBLIC_KEY, &["1", "2"]).unwrap();
+        let roles: Vec<_> = payloads
+            .iter()
+            .map(|payload| payload.get_authorize_agent().get_role())
+            .collect();
+        assert_eq!(
+            roles,
+            vec![
+                Organization_Authorization_Role::ADMIN,
+                Organization_Authorization_Role::TRANSACTOR
+            ]
+        );
+
+        assert!(authorize_agent_payloads(PUBLIC_KEY, &["1", "3"]).is_err());
+        assert!(authorize_agent_payloads(PUBLIC_KEY, &["2", "2"]).is_err());
+    }
+}
@@ -1,9 +1,13 @@
+use crate::commands;
+use crate::commands::{CommandOutcome, Listing};
+use crate::contact;
 use crate::error::CliError;
 use crate::key;
+use crate::render::TableRow;
 use crate::submit;
 use crate::transaction::{
     create_batch, create_batch_list, create_batch_list_from_one, create_batch_with_transactions,
-    create_transaction,
+    create_transaction, TransactionOptions,
 };
 
 use clap::ArgMatches;
@@ -20,14 +24,12 @@ use crypto::sha2::Sha256;
 use sawtooth_sdk::messages::batch::BatchList;
 use sawtooth_sdk::messages::transaction::Transaction;
 use sawtooth_sdk::signing;
-use std::fs::File;
-use std::io::prelude::*;
-use std::{thread, time};
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::BufRead;
 use uuid::Uuid;
 
-const SECP_256K1: &str = "secp256k1";
-
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     match args.subcommand() {
         ("factory", Some(args)) => match args.subcommand() {
             ("create", Some(args)) => run_factory_create_command(args),
@@ -50,21 +52,27 @@ pub fn run(args: &ArgMatches) -> Result<(), CliError> {
             ))),
         },
         ("transfer", Some(args)) => run_transfer_command(args),
+        ("batch_transfer", Some(args)) => run_batch_transfer_command(args),
+        ("list", Some(args)) => run_list_command(args),
+        ("show", Some(args)) => run_show_command(args),
         _ => Err(CliError::InvalidInputError(String::from(
             "Invalid subcommand. Pass --help for usage",
         ))),
     }
 }
 
-fn run_factory_create_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_factory_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract arg values
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-    let name = args.value_of("name").unwrap();
-    let asserter_organization_id = args.value_of("asserter_organization_id").unwrap();
-    let contact_name = args.value_of("contact_name").unwrap();
-    let contact_phone_number = args.value_of("contact_phone_number").unwrap();
-    let contact_language_code = args.value_of("contact_language_code").unwrap();
+    let client = submit::ApiClient::from_args(args)?;
+    let name = commands::require_arg(args, "name")?;
+    let asserter_organization_id = commands::require_arg(args, "asserter_organization_id")?;
+    let contact = factory_contact(
+        args.value_of("contact_name"),
+        args.value_of("contact_phone_number"),
+        || contact::language_code_from_args(args),
+        args.is_present("strict_contact"),
+    )?;
     let street = args.value_of("street_address");
     let city = args.value_of("city");
     let state_province = args.value_of("state_province");
@@ -73,210 +81,342 @@ fn run_factory_create_command(args: &ArgMatches) -> Result<(), CliError> {
 
     let factory_uuid = Uuid::new_v4().to_string();
     let factory_organization_id = args.value_of("factory_id").unwrap_or(&factory_uuid);
+    validate_factory_assertion_ids(factory_organization_id, asserter_organization_id)?;
 
     // Generate new assertion ID
     let assertion_id = Uuid::new_v4().to_string();
 
     // Validate factory-specifc args
-    match street {
-        None => Err(CliError::InvalidInputError(
-            "A street address is required for a factory".to_string(),
-        )),
-        val => Ok(val),
-    }?;
-    match city {
-        None => Err(CliError::InvalidInputError(
-            "A city is required for a factory".to_string(),
-        )),
-        val => Ok(val),
-    }?;
-    match country {
-        None => Err(CliError::InvalidInputError(
-            "A country is required for a factory".to_string(),
-        )),
-        val => Ok(val),
-    }?;
+    let street = street.ok_or_else(|| {
+        CliError::InvalidInputError("A street address is required for a factory".to_string())
+    })?;
+    let city = city.ok_or_else(|| {
+        CliError::InvalidInputError("A city is required for a factory".to_string())
+    })?;
+    let country = country.ok_or_else(|| {
+        CliError::InvalidInputError("A country is required for a factory".to_string())
+    })?;
 
     // Build create organization action payload
     let create_org_action_payload = build_create_organization_action_payload(
         &factory_organization_id,
         Organization_Type::FACTORY,
         name,
-        contact_name,
-        contact_phone_number,
-        contact_language_code,
-        street.unwrap(),
-        city.unwrap(),
+        contact,
+        street,
+        city,
         state_province,
-        country.unwrap(),
+        country,
         postal_code,
     );
 
     let assertion_payload =
         create_factory_assertion_payload(&assertion_id, create_org_action_payload);
 
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&assertion_payload]);
+    }
+
     submit_factory_assertion_transaction(
         assertion_payload,
         &assertion_id,
         &asserter_organization_id,
         &factory_organization_id,
         key,
-        url,
+        &client,
+        &TransactionOptions::from_args(args)?,
     )
 }
 
-fn run_factory_batch_create_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_factory_batch_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    if args.is_present("json_lines") {
+        return run_factory_json_lines_command(args);
+    }
+
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+    let strict_contact = args.is_present("strict_contact");
 
-    // Define uninitialized arguments
-    let mut factory_organization_id: &str;
-    let mut asserter_organization_id: &str;
-    let mut name: &str;
-    let mut contact_name: &str;
-    let mut contact_phone_number: &str;
-    let mut contact_language_code: &str;
-    let mut street_address: &str;
-    let mut city: &str;
-    let mut country: &str;
-    let mut state_province: Option<&str>;
-    let mut postal_code: Option<&str>;
-    let mut assertion_id = String::from("");
-
-    // Read factories from provided JSON batch file
-    let filepath = args.value_of("filepath").unwrap();
-    let mut file = File::open(filepath)?;
-    let mut data: String = String::new();
-    file.read_to_string(&mut data)?;
-    let factories: serde_json::Value = serde_json::from_str(&data).expect("Unable to parse");
+    // Read factories from provided batch file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let factories: serde_json::Map<String, serde_json::Value> = commands::read_batch_file(args)?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
 
     // Create signing key
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     // Loop through map of factories and populate list of transactions
-    println!("Creating transactions for {}", filepath);
+    eprintln!("Creating transactions for {}", filepath);
     let mut txn_list: Vec<Transaction> = vec![];
-    for (key, value) in factories.as_object().unwrap() {
-        // Gather information and initialize defined variables from above
-        factory_organization_id = key.as_str();
-        asserter_organization_id = value
-            .get("asserter_organization_id")
-            .unwrap()
-            .as_str()
-            .unwrap();
-        name = value.get("name").unwrap().as_str().unwrap();
-        contact_name = value.get("contact_name").unwrap().as_str().unwrap();
-        contact_phone_number = value.get("contact_phone_number").unwrap().as_str().unwrap();
-        contact_language_code = value
-            .get("contact_language_code")
-            .unwrap()
-            .as_str()
-            .unwrap();
-        street_address = value.get("street_address").unwrap().as_str().unwrap();
-        city = value.get("city").unwrap().as_str().unwrap();
-        country = value.get("country").unwrap().as_str().unwrap();
-        state_province = value.get("state_province").unwrap().as_str();
-        postal_code = value.get("postal_code").unwrap().as_str();
+    let mut assertion_ids = BTreeMap::new();
+    let mut txn_entries: Vec<(String, &str)> = vec![];
+    let mut progress = commands::ProgressCounter::from_args(args, Some(factories.len()))?;
+    for (key, value) in &factories {
+        if !entry_filter.includes(key) {
+            continue;
+        }
 
-        // Generate new assertion ID
-        assertion_id = Uuid::new_v4().to_string();
-
-        // Build create organization action payload
-        let create_org_action_payload = build_create_organization_action_payload(
-            &factory_organization_id,
-            Organization_Type::FACTORY,
-            name,
-            contact_name,
-            contact_phone_number,
-            contact_language_code,
-            street_address,
-            city,
-            state_province,
-            country,
-            postal_code,
-        );
+        let (txn, assertion_id) = create_factory_assertion_entry(
+            key,
+            value,
+            &signer,
+            batcher.as_ref(),
+            &options,
+            strict_contact,
+        )?;
+        txn_entries.push((txn.header_signature.clone(), key.as_str()));
+        txn_list.push(txn);
+        assertion_ids.insert(key.to_string(), assertion_id);
+        progress.increment();
+    }
+
+    if txn_list.is_empty() {
+        return Ok(commands::nothing_to_submit(filepath, "factories"));
+    }
 
-        // Create cert registry payload
-        let assertion_cert_registry_payload =
-            create_factory_assertion_payload(&assertion_id, create_org_action_payload);
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
+    let batch_list = create_batch_list(vec![batch]);
+
+    eprintln!("Submitting batch list for processing");
+    submit_assertions_batch_list(
+        "factory",
+        assertion_ids,
+        batch_list,
+        &txn_entries,
+        &client,
+        max_invalid_display,
+    )
+}
+
+/// Creates factory assertions from a file holding one JSON factory per line,
+/// each with its factory id under "id"
+///
+/// The factories are read and submitted `--batch-size` at a time, so that the
+/// whole file is never held in memory.
+fn run_factory_json_lines_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    // Extract system arguments
+    let key = args.value_of("key");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+    let batch_size = commands::batch_size_from_args(args)?;
+    let strict_contact = args.is_present("strict_contact");
 
-        // Create a transaction address for the transaction
-        let (header_input, header_output) = create_factory_assertion_transaction_addresses(
+    let filepath = commands::require_arg(args, "filepath")?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
+
+    // Create signing key
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+
+    eprintln!("Creating transactions for {}", filepath);
+    let mut chunk: Vec<(Transaction, String)> = vec![];
+    let mut assertion_ids = BTreeMap::new();
+    let mut batch_status = None;
+    let mut transaction_ids = vec![];
+    let mut progress = commands::ProgressCounter::from_args(args, None)?;
+    for (index, line) in commands::open_input_file(filepath)?.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let invalid_line = |problem: String| {
+            CliError::InvalidInputError(format!(
+                "Unable to parse line {} of {}: {}",
+                index + 1,
+                filepath,
+                problem
+            ))
+        };
+        let factory: serde_json::Value =
+            serde_json::from_str(&line).map_err(|err| invalid_line(err.to_string()))?;
+        let factory_id = factory
+            .get("id")
+            .and_then(|id| id.as_str())
+            .ok_or_else(|| invalid_line(String::from("the factory has no id")))?
+            .to_string();
+        if !entry_filter.includes(&factory_id) {
+            continue;
+        }
+
+        let (txn, assertion_id) = create_factory_assertion_entry(
+            &factory_id,
+            &factory,
             &signer,
-            &assertion_id,
-            &asserter_organization_id,
-            factory_organization_id,
+            batcher.as_ref(),
+            &options,
+            strict_contact,
         )?;
+        chunk.push((txn, factory_id.clone()));
+        assertion_ids.insert(factory_id, assertion_id);
+        progress.increment();
+
+        if chunk.len() == batch_size {
+            let status = submit_transaction_chunk(
+                chunk.split_off(0),
+                &signer,
+                batcher.as_ref(),
+                &client,
+                max_invalid_display,
+            )?;
+            transaction_ids.extend(status.transaction_ids.iter().cloned());
+            batch_status = Some(status);
+        }
+    }
 
-        let txn = create_transaction(
-            &assertion_cert_registry_payload,
+    if !chunk.is_empty() {
+        let status = submit_transaction_chunk(
+            chunk,
             &signer,
-            header_input,
-            header_output,
+            batcher.as_ref(),
+            &client,
+            max_invalid_display,
         )?;
-        txn_list.push(txn);
+        transaction_ids.extend(status.transaction_ids.iter().cloned());
+        batch_status = Some(status);
     }
 
-    println!("Creating batch list for transactions");
-    let batch = create_batch_with_transactions(txn_list, &signer)?;
-    let batch_list = create_batch_list(vec![batch]);
+    let batch_status = match batch_status {
+        Some(batch_status) => batch_status,
+        None => return Ok(commands::nothing_to_submit(filepath, "factories")),
+    };
+    // Report the transactions of every batch, not just the last one
+    let mut outcome = assertions_outcome("factory", assertion_ids, &batch_status);
+    outcome.transaction_ids = transaction_ids;
+    Ok(outcome)
+}
+
+/// Creates the transaction asserting the factory given by an entry of a batch
+/// file, returning it with the id of the new assertion
+fn create_factory_assertion_entry(
+    factory_organization_id: &str,
+    value: &serde_json::Value,
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    options: &TransactionOptions,
+    strict_contact: bool,
+) -> Result<(Transaction, String), CliError> {
+    // Gather information
+    let field = |field| commands::batch_entry_field(factory_organization_id, value, field);
+    let optional_field = |field| value.get(field).and_then(serde_json::Value::as_str);
+    let asserter_organization_id = field("asserter_organization_id")?;
+    validate_factory_assertion_ids(factory_organization_id, asserter_organization_id)?;
+    let name = field("name")?;
+    let contact = factory_contact(
+        optional_field("contact_name"),
+        optional_field("contact_phone_number"),
+        || field("contact_language_code"),
+        strict_contact,
+    )?;
+    let street_address = field("street_address")?;
+    let city = field("city")?;
+    let country = field("country")?;
+    let state_province = optional_field("state_province");
+    let postal_code = optional_field("postal_code");
+
+    // Generate new assertion ID
+    let assertion_id = Uuid::new_v4().to_string();
+
+    // Build create organization action payload
+    let create_org_action_payload = build_create_organization_action_payload(
+        factory_organization_id,
+        Organization_Type::FACTORY,
+        name,
+        contact,
+        street_address,
+        city,
+        state_province,
+        country,
+        postal_code,
+    );
+
+    // Create cert registry payload
+    let assertion_cert_registry_payload =
+        create_factory_assertion_payload(&assertion_id, create_org_action_payload);
+
+    // Create a transaction address for the transaction
+    let (header_input, header_output) = create_factory_assertion_transaction_addresses(
+        signer,
+        &assertion_id,
+        asserter_organization_id,
+        factory_organization_id,
+    )?;
+
+    let txn = create_transaction(
+        &assertion_cert_registry_payload,
+        signer,
+        batcher,
+        header_input,
+        header_output,
+        vec![],
+        options,
+    )?;
+    Ok((txn, assertion_id))
+}
 
-    println!("Submitting batch list for processing");
-    submit_assertions_batch_list(assertion_id, batch_list, url)
+/// Submits a batch of the given transactions, each paired with the key of the
+/// batch file entry it was created for, and waits for it to be committed
+fn submit_transaction_chunk(
+    chunk: Vec<(Transaction, String)>,
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    client: &submit::ApiClient,
+    max_invalid_display: usize,
+) -> Result<submit::Status, CliError> {
+    let (txns, keys): (Vec<Transaction>, Vec<String>) = chunk.into_iter().unzip();
+    let txn_entries: Vec<(String, &str)> = txns
+        .iter()
+        .zip(&keys)
+        .map(|(txn, key)| (txn.header_signature.clone(), key.as_str()))
+        .collect();
+
+    eprintln!("Submitting a batch of {} transactions", txns.len());
+    let batch = create_batch_with_transactions(txns, signer, batcher)?;
+    let batch_list = create_batch_list(vec![batch]);
+    client.submit_batch_and_wait(&batch_list, &txn_entries, max_invalid_display)
 }
 
-fn run_certificate_create_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_certificate_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
 
     // Extract required arguments
-    let asserter_organization_id = args.value_of("asserter_organization_id").unwrap();
-    let factory_id = args.value_of("factory_id").unwrap();
-    let valid_from = args.value_of("valid_from").unwrap();
-    let valid_to = args.value_of("valid_to").unwrap();
-    let standard_id = args.value_of("standard_id").unwrap();
+    let asserter_organization_id = commands::require_arg(args, "asserter_organization_id")?;
+    let factory_id = commands::require_arg(args, "factory_id")?;
+    let valid_from =
+        commands::parse_timestamp(commands::require_arg(args, "valid_from")?)?.to_string();
+    let valid_to = commands::valid_to_from_args(args, &valid_from)?;
+    let standard_id = commands::require_arg(args, "standard_id")?;
 
     // Extract optional arguments
     // We use randomly generated uuid if no id was supplied
     let certificate_uuid = Uuid::new_v4().to_string();
     let certificate_id = args.value_of("id").unwrap_or(&certificate_uuid);
-    let cert_data: Result<Vec<Certificate_CertificateData>, CliError> = args
-        .values_of("cert_data")
-        .map(|values| {
-            values
-                .map(|cert_data| {
-                    let cd: Vec<&str> = cert_data.split(':').collect();
-                    match (cd.get(0), cd.get(1)) {
-                        (Some(field), Some(data)) => {
-                            let mut ccd: Certificate_CertificateData =
-                                Certificate_CertificateData::new();
-                            ccd.set_field(field.to_string());
-                            ccd.set_data(data.to_string());
-                            Ok(ccd)
-                        }
-                        _ => Err(CliError::InvalidInputError(String::from(
-                            "Invalid format for cert_data",
-                        ))),
-                    }
-                })
-                .collect()
-        })
-        .unwrap_or_else(|| Ok(vec![]));
+    let cert_data = commands::parse_cert_data(args.values_of("cert_data").into_iter().flatten())?;
+
+    commands::validate_id("asserter organization", asserter_organization_id)?;
+    commands::validate_id("factory", factory_id)?;
+    commands::validate_id("standard", standard_id)?;
+    commands::validate_id("certificate", certificate_id)?;
 
     // Build create_certificate_action payload
     let create_certificate_action_payload = build_create_certificate_action_payload(
         &certificate_id,
         factory_id,
         standard_id,
-        cert_data?,
-        valid_from,
-        valid_to,
-    );
+        cert_data,
+        &valid_from,
+        &valid_to,
+    )?;
 
     // Generate an assertion ID for this assertion
     // Create assertion payload to be submitted
@@ -284,6 +424,10 @@ fn run_certificate_create_command(args: &ArgMatches) -> Result<(), CliError> {
     let assertion_payload =
         create_certificate_assertion_payload(&assertion_id, create_certificate_action_payload);
 
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&assertion_payload]);
+    }
+
     // Submit assertion payload as a transaction
     submit_certificate_assertion_transaction(
         assertion_payload,
@@ -293,55 +437,64 @@ fn run_certificate_create_command(args: &ArgMatches) -> Result<(), CliError> {
         factory_id,
         standard_id,
         key,
-        url,
+        &client,
+        &TransactionOptions::from_args(args)?,
     )
 }
 
-fn run_certificate_batch_create_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_certificate_batch_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
 
     // Define uninitialized arguments
     let mut certificate_id: &str;
     let mut asserter_organization_id: &str;
     let mut factory_organization_id: &str;
-    let mut valid_from: &str;
-    let mut valid_to: &str;
     let mut standard_id: &str;
-    let mut assertion_id = String::from("");
 
-    // Read certificates from provided JSON batch file
-    let filepath = args.value_of("filepath").unwrap();
-    let mut file = File::open(filepath)?;
-    let mut data: String = String::new();
-    file.read_to_string(&mut data)?;
-    let certificates: serde_json::Value = serde_json::from_str(&data).expect("Unable to parse");
+    // Read certificates from provided batch file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let certificates: serde_json::Map<String, serde_json::Value> = commands::read_batch_file(args)?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
 
     // Create signing key
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     // Loop through map of certificates and populate list of transactions
-    println!("Creating transactions for {}", filepath);
+    eprintln!("Creating transactions for {}", filepath);
     let mut txn_list: Vec<Transaction> = vec![];
-    for (key, value) in certificates.as_object().unwrap() {
+    let mut assertion_ids = BTreeMap::new();
+    let mut txn_entries: Vec<(String, &str)> = vec![];
+    let mut progress = commands::ProgressCounter::from_args(args, Some(certificates.len()))?;
+    for (key, value) in &certificates {
+        if !entry_filter.includes(key) {
+            continue;
+        }
+
         // Gather information and initialize defined variables from above
         certificate_id = key.as_str();
-        asserter_organization_id = value
-            .get("asserter_organization_id")
-            .unwrap()
-            .as_str()
-            .unwrap();
-        factory_organization_id = value.get("factory_id").unwrap().as_str().unwrap();
-        standard_id = value.get("standard_id").unwrap().as_str().unwrap();
-        valid_from = value.get("valid_from").unwrap().as_str().unwrap();
-        valid_to = value.get("valid_to").unwrap().as_str().unwrap();
+        asserter_organization_id =
+            commands::batch_entry_field(key, value, "asserter_organization_id")?;
+        factory_organization_id = commands::batch_entry_field(key, value, "factory_id")?;
+        standard_id = commands::batch_entry_field(key, value, "standard_id")?;
+        let timestamp = |field: &str| {
+            let timestamp = value.get(field).unwrap_or(&serde_json::Value::Null);
+            commands::entry_timestamp(key, field, timestamp).map(|timestamp| timestamp.to_string())
+        };
+        let valid_from = timestamp("valid_from")?;
+        let valid_to = timestamp("valid_to")?;
+        commands::validate_id("certificate", certificate_id)?;
+        commands::validate_id("asserter organization", asserter_organization_id)?;
+        commands::validate_id("factory", factory_organization_id)?;
+        commands::validate_id("standard", standard_id)?;
 
         // Generate new assertion ID
-        assertion_id = Uuid::new_v4().to_string();
+        let assertion_id = Uuid::new_v4().to_string();
 
         // Build create_certificate_action payload
         let create_certificate_action_payload = build_create_certificate_action_payload(
@@ -349,9 +502,9 @@ fn run_certificate_batch_create_command(args: &ArgMatches) -> Result<(), CliErro
             factory_organization_id,
             standard_id,
             vec![],
-            valid_from,
-            valid_to,
-        );
+            &valid_from,
+            &valid_to,
+        )?;
 
         // Create assertion payload to be submitted
         let assertion_payload =
@@ -367,30 +520,52 @@ fn run_certificate_batch_create_command(args: &ArgMatches) -> Result<(), CliErro
             standard_id,
         )?;
 
-        let txn = create_transaction(&assertion_payload, &signer, header_input, header_output)?;
+        let txn = create_transaction(
+            &assertion_payload,
+            &signer,
+            batcher.as_ref(),
+            header_input,
+            header_output,
+            vec![],
+            &options,
+        )?;
+        txn_entries.push((txn.header_signature.clone(), key.as_str()));
         txn_list.push(txn);
+        assertion_ids.insert(key.to_string(), assertion_id);
+        progress.increment();
+    }
+
+    if txn_list.is_empty() {
+        return Ok(commands::nothing_to_submit(filepath, "certificates"));
     }
 
-    println!("Creating batch list for transactions");
-    let batch = create_batch_with_transactions(txn_list, &signer)?;
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list(vec![batch]);
 
-    println!("Submitting batch list for processing");
-    submit_assertions_batch_list(assertion_id, batch_list, url)
+    eprintln!("Submitting batch list for processing");
+    submit_assertions_batch_list(
+        "certificate",
+        assertion_ids,
+        batch_list,
+        &txn_entries,
+        &client,
+        max_invalid_display,
+    )
 }
 
-fn run_standard_create_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_standard_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
 
     // Extract required arguments
-    let asserter_organization_id = args.value_of("asserter_organization_id").unwrap();
-    let standard_name = args.value_of("name").unwrap();
-    let version = args.value_of("version").unwrap();
-    let description = args.value_of("description").unwrap();
-    let link = args.value_of("link").unwrap();
-    let approval_date = args.value_of("approval_date").unwrap();
+    let asserter_organization_id = commands::require_arg(args, "asserter_organization_id")?;
+    let standard_name = commands::require_arg(args, "name")?;
+    let version = commands::require_arg(args, "version")?;
+    let description = commands::require_arg(args, "description")?;
+    let link = commands::require_arg(args, "link")?;
+    let approval_date = commands::parse_timestamp(commands::require_arg(args, "approval_date")?)?;
 
     // Extract optional arguments
     // We hash the name to produce an id if no id was supplied
@@ -398,6 +573,8 @@ fn run_standard_create_command(args: &ArgMatches) -> Result<(), CliError> {
     standard_id_sha.input_str(standard_name);
     let standard_id_hash = standard_id_sha.result_str();
     let standard_id = args.value_of("id").unwrap_or(&standard_id_hash);
+    commands::validate_id("asserter organization", asserter_organization_id)?;
+    commands::validate_id("standard", standard_id)?;
 
     // Build create_standard_action payload
     let create_standard_action_payload = build_create_standard_action_payload(
@@ -406,7 +583,7 @@ fn run_standard_create_command(args: &ArgMatches) -> Result<(), CliError> {
         version,
         description,
         link,
-        approval_date.parse::<u64>().unwrap(),
+        approval_date as u64,
     );
 
     // Generate an assertion ID for this assertion
@@ -415,6 +592,10 @@ fn run_standard_create_command(args: &ArgMatches) -> Result<(), CliError> {
     let assertion_payload =
         create_standard_assertion_payload(&assertion_id, create_standard_action_payload);
 
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&assertion_payload]);
+    }
+
     // Submit assertion payload as a transaction
     submit_standard_assertion_transaction(
         assertion_payload,
@@ -422,19 +603,282 @@ fn run_standard_create_command(args: &ArgMatches) -> Result<(), CliError> {
         &asserter_organization_id,
         &standard_id,
         key,
-        url,
+        &client,
+        &TransactionOptions::from_args(args)?,
+    )
+}
+
+fn run_transfer_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    // Extract system arguments
+    let key = args.value_of("key");
+    let client = submit::ApiClient::from_args(args)?;
+    let assertion_id = commands::require_arg(args, "id")?;
+    commands::validate_id("assertion", assertion_id)?;
+    let new_owner = args
+        .value_of("new_owner")
+        .map(key::normalize_public_key)
+        .transpose()?;
+    let options = TransactionOptions::from_args(args)?;
+
+    if args.is_present("payload_only") {
+        // The new owner defaults to the signer, so the key is loaded, but
+        // nothing is signed
+        let new_owner = match new_owner {
+            Some(new_owner) => new_owner,
+            None => {
+                let signing_context = key::SigningContext::new(key, &options)?;
+                let public_key = signing_context.signer().get_public_key()?.as_hex();
+                public_key
+            }
+        };
+        let payload = create_transfer_assertion_payload(assertion_id, &new_owner);
+        return commands::payload_only_outcome(&[&payload]);
+    }
+
+    submit_transfer_assertion_transaction(
+        &assertion_id,
+        new_owner.as_deref(),
+        key,
+        &client,
+        &options,
     )
 }
 
-fn run_transfer_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_batch_transfer_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-    let assertion_id = args.value_of("id").expect("Assertion ID must be provided");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+    let new_owner = args
+        .value_of("new_owner")
+        .map(key::normalize_public_key)
+        .transpose()?;
+
+    // Read assertion ids from provided file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let mut assertion_ids =
+        parse_assertion_ids(&commands::read_input_file(filepath)?).map_err(|err| {
+            CliError::InvalidInputError(format!(
+                "Unable to parse assertion ids from {}: {}",
+                filepath, err
+            ))
+        })?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
+    assertion_ids.retain(|assertion_id| entry_filter.includes(assertion_id));
+    if assertion_ids.is_empty() {
+        return Ok(commands::nothing_to_submit(filepath, "assertions"));
+    }
+    for assertion_id in &assertion_ids {
+        commands::validate_id("assertion", assertion_id)?;
+    }
 
-    let payload = create_transfer_assertion_payload(assertion_id);
+    // Create signing key
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+    let signer_public_key = signer.get_public_key()?.as_hex();
+
+    // Create a transfer transaction for each assertion, remembering which
+    // assertion each transaction transfers
+    eprintln!("Creating transactions for {}", filepath);
+    let mut txn_list: Vec<Transaction> = vec![];
+    let mut txn_assertions: Vec<(String, &str)> = vec![];
+    for assertion_id in &assertion_ids {
+        let payload = create_transfer_assertion_payload(
+            assertion_id,
+            new_owner.as_deref().unwrap_or(&signer_public_key),
+        );
+        let (header_input, header_output) =
+            create_transfer_assertion_transaction_addresses(&signer, assertion_id)?;
 
-    submit_transfer_assertion_transaction(payload, &assertion_id, key, url)
+        let txn = create_transaction(
+            &payload,
+            &signer,
+            batcher.as_ref(),
+            header_input,
+            header_output,
+            vec![],
+            &options,
+        )?;
+        txn_assertions.push((txn.header_signature.clone(), assertion_id.as_str()));
+        txn_list.push(txn);
+    }
+
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
+    let batch_list = create_batch_list(vec![batch]);
+
+    eprintln!("Submitting batch list for processing");
+    let status = client.submit_and_get_status(&batch_list)?;
+    client.push_metrics(&batch_list, &status);
+
+    if status.status == "INVALID" {
+        client.log_invalid_transactions(&status, &txn_assertions)?;
+        submit::report_invalid_transactions(&status, max_invalid_display, |invalid_transaction| {
+            let assertion_id = txn_assertions
+                .iter()
+                .find(|(txn_id, _)| *txn_id == invalid_transaction.id)
+                .map(|(_, assertion_id)| *assertion_id)
+                .unwrap_or("unknown");
+            format!(
+                "Assertion {} could not be transferred: {}",
+                assertion_id, invalid_transaction.message
+            )
+        });
+        return Err(submit::invalid_transaction_error(&status));
+    }
+
+    let message = assertion_ids
+        .iter()
+        .map(|assertion_id| format!("Assertion {} has been transferred", assertion_id))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(CommandOutcome::submitted(None, &status, message))
+}
+
+/// Parses a list of assertion ids, given either as a JSON array or one per
+/// line
+fn parse_assertion_ids(data: &str) -> Result<Vec<String>, String> {
+    if data.trim_start().starts_with('[') {
+        serde_json::from_str(data).map_err(|err| err.to_string())
+    } else {
+        Ok(data
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+fn run_list_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+
+    let assertions: Vec<AssertionRecord> = client.fetch_all_data("/assertions")?;
+
+    let summaries: Vec<AssertionView> = assertions.into_iter().map(AssertionView::from).collect();
+
+    let mut outcome = CommandOutcome::completed(
+        summaries
+            .iter()
+            .map(AssertionView::describe)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    outcome.listing = Some(Listing::new("assertion", &summaries)?);
+    Ok(outcome)
+}
+
+fn run_show_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+    let concurrency = submit::concurrency_from_args(args)?;
+    let assertion_ids: Vec<&str> = commands::require_values(args, "id")?.collect();
+
+    let paths: Vec<String> = assertion_ids
+        .iter()
+        .map(|assertion_id| format!("/assertions/{}", assertion_id))
+        .collect();
+    let summaries = client
+        .fetch_data_concurrently(&paths, concurrency)?
+        .into_iter()
+        .zip(&assertion_ids)
+        .map(|(assertion, assertion_id)| {
+            assertion
+                .ok_or_else(|| {
+                    CliError::UserError(format!("Assertion {} does not exist", assertion_id))
+                })
+                .and_then(submit::parse_data::<AssertionRecord>)
+                .map(AssertionView::from)
+        })
+        .collect::<Result<Vec<_>, CliError>>()?;
+
+    let mut outcome = CommandOutcome::completed(
+        summaries
+            .iter()
+            .map(AssertionView::describe)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    outcome.listing = Some(Listing::new("assertion", &summaries)?);
+    if let [summary] = summaries.as_slice() {
+        outcome.id = Some(summary.assertion_id.clone());
+    }
+    Ok(outcome)
+}
+
+/// An assertion as returned by the REST API
+#[derive(Deserialize)]
+struct AssertionRecord {
+    assertion_id: String,
+    /// FACTORY, CERTIFICATE or STANDARD
+    assertion_type: String,
+    assertor_org_id: String,
+    /// The id of the factory, certificate or standard asserted
+    object_id: String,
+}
+
+/// The parts of an assertion, as returned by the REST API, that are shown by
+/// the read commands
+///
+/// The field names are part of the `--format json` output, so they are kept
+/// stable whatever the REST API calls them.
+#[derive(Serialize)]
+struct AssertionView {
+    assertion_id: String,
+    assertion_type: String,
+    asserter_organization_id: String,
+    target_id: String,
+}
+
+impl AssertionView {
+    fn describe(&self) -> String {
+        format!(
+            "Assertion {}: {} {}, asserted by {}",
+            self.assertion_id, self.assertion_type, self.target_id, self.asserter_organization_id
+        )
+    }
+}
+
+impl TableRow for AssertionView {
+    const COLUMNS: &'static [&'static str] = &["ASSERTION", "TYPE", "TARGET", "ASSERTED BY"];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.assertion_id.clone(),
+            self.assertion_type.clone(),
+            self.target_id.clone(),
+            self.asserter_organization_id.clone(),
+        ]
+    }
+}
+
+impl From<AssertionRecord> for AssertionView {
+    fn from(assertion: AssertionRecord) -> Self {
+        AssertionView {
+            assertion_id: assertion.assertion_id,
+            assertion_type: assertion.assertion_type.to_lowercase(),
+            asserter_organization_id: assertion.assertor_org_id,
+            target_id: assertion.object_id,
+        }
+    }
+}
+
+/// Checks the ids of a factory assertion
+fn validate_factory_assertion_ids(
+    factory_organization_id: &str,
+    asserter_organization_id: &str,
+) -> Result<(), CliError> {
+    commands::validate_id("factory", factory_organization_id)?;
+    commands::validate_id("asserter organization", asserter_organization_id)?;
+    if factory_organization_id == asserter_organization_id {
+        return Err(CliError::InvalidInputError(format!(
+            "Factory {} cannot be asserted by itself",
+            factory_organization_id
+        )));
+    }
+    Ok(())
 }
 
 fn submit_factory_assertion_transaction(
@@ -443,12 +887,12 @@ fn submit_factory_assertion_transaction(
     asserter_organization_id: &str,
     factory_organization_id: &str,
     key: Option<&str>,
-    url: &str,
-) -> Result<(), CliError> {
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    client: &submit::ApiClient,
+    options: &TransactionOptions,
+) -> Result<CommandOutcome, CliError> {
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     let (header_input, header_output) = create_factory_assertion_transaction_addresses(
         &signer,
@@ -457,88 +901,73 @@ fn submit_factory_assertion_transaction(
         factory_organization_id,
     )?;
 
-    let txn = create_transaction(&assertion_payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &assertion_payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!(
-                    "Assertion {} has been created for factory {}",
-                    assertion_id, factory_organization_id
-                );
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            // "UNKNOWN" case where we should recheck
-            // "STATUS_UNSET" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(url, &batch_status.link)?;
-            }
-        }
-    }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(assertion_id.to_string()),
+        &batch_status,
+        format!(
+            "Assertion {} has been created for factory {}",
+            assertion_id, factory_organization_id
+        ),
+    ))
 }
 
+/// Submits a batch of assertions, reporting the id of the assertion created
+/// for each entry of the batch file
+///
+/// # Arguments
+///
+/// * `kind` - what the entries of the batch file are, e.g. "factory"
+/// * `assertion_ids` - the assertion id of each entry, by the entry's key
+/// * `txn_entries` - the key of the entry each transaction was created for, by
+///                   the transaction's id
 fn submit_assertions_batch_list(
-    assertion_id: String,
+    kind: &str,
+    assertion_ids: BTreeMap<String, String>,
     batch_list: BatchList,
-    url: &str,
-) -> Result<(), CliError> {
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Assertion {} has been created", assertion_id,);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            // "UNKNOWN" case where we should recheck
-            // "STATUS_UNSET" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(url, &batch_status.link)?;
-            }
-        }
-    }
+    txn_entries: &[(String, &str)],
+    client: &submit::ApiClient,
+    max_invalid_display: usize,
+) -> Result<CommandOutcome, CliError> {
+    let batch_status =
+        client.submit_batch_and_wait(&batch_list, txn_entries, max_invalid_display)?;
+    Ok(assertions_outcome(kind, assertion_ids, &batch_status))
+}
+
+/// Returns the outcome of a batch command which created an assertion for each
+/// entry of its batch file
+fn assertions_outcome(
+    kind: &str,
+    assertion_ids: BTreeMap<String, String>,
+    batch_status: &submit::Status,
+) -> CommandOutcome {
+    let message = assertion_ids
+        .iter()
+        .map(|(key, assertion_id)| {
+            format!(
+                "Assertion {} has been created for {} {}",
+                assertion_id, kind, key
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut outcome = CommandOutcome::submitted(None, batch_status, message);
+    outcome.details = assertion_ids;
+    outcome
 }
 
 fn submit_standard_assertion_transaction(
@@ -547,12 +976,12 @@ fn submit_standard_assertion_transaction(
     asserter_organization_id: &str,
     standard_id: &str,
     key: Option<&str>,
-    url: &str,
-) -> Result<(), CliError> {
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    client: &submit::ApiClient,
+    options: &TransactionOptions,
+) -> Result<CommandOutcome, CliError> {
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     let (header_input, header_output) = create_standard_assertion_transaction_addresses(
         &signer,
@@ -561,101 +990,115 @@ fn submit_standard_assertion_transaction(
         standard_id,
     )?;
 
-    let txn = create_transaction(&assertion_payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &assertion_payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!(
-                    "Assertion {} has been created for standard {}",
-                    assertion_id, standard_id
-                );
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            // "UNKNOWN" case where we should recheck
-            // "STATUS_UNSET" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(url, &batch_status.link)?;
-            }
-        }
-    }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(assertion_id.to_string()),
+        &batch_status,
+        format!(
+            "Assertion {} has been created for standard {}",
+            assertion_id, standard_id
+        ),
+    ))
 }
 
+/// Submits a transaction transferring an assertion to a new owner, which
+/// defaults to the signer
 fn submit_transfer_assertion_transaction(
-    transfer_payload: CertificateRegistryPayload,
     assertion_id: &str,
+    new_owner: Option<&str>,
     key: Option<&str>,
-    url: &str,
-) -> Result<(), CliError> {
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    client: &submit::ApiClient,
+    options: &TransactionOptions,
+) -> Result<CommandOutcome, CliError> {
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+
+    let signer_public_key = signer.get_public_key()?.as_hex();
+    let transfer_payload =
+        create_transfer_assertion_payload(assertion_id, new_owner.unwrap_or(&signer_public_key));
 
     let (header_input, header_output) =
         create_transfer_assertion_transaction_addresses(&signer, assertion_id)?;
 
-    let txn = create_transaction(&transfer_payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &transfer_payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Assertion {} has been transferred", assertion_id);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            // "UNKNOWN" case where we should recheck
-            // "STATUS_UNSET" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(url, &batch_status.link)?;
-            }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(assertion_id.to_string()),
+        &batch_status,
+        format!("Assertion {} has been transferred", assertion_id),
+    ))
+}
+
+/// Returns the contact of a factory being asserted, if one is given
+///
+/// The contact is optional, but one given without a name or phone number is
+/// rejected rather than stored with a blank field. The language code is only
+/// looked up if there is a contact.
+///
+/// # Errors
+///
+/// If only one of the name and phone number is given, or the phone number or
+/// language code is invalid, a `CliError::InvalidInputError` is returned.
+fn factory_contact<'a, F>(
+    name: Option<&str>,
+    phone_number: Option<&str>,
+    language_code: F,
+    strict_contact: bool,
+) -> Result<Option<Organization_Contact>, CliError>
+where
+    F: FnOnce() -> Result<&'a str, CliError>,
+{
+    let (name, phone_number) = match (name, phone_number) {
+        (None, None) => return Ok(None),
+        (Some(name), Some(phone_number)) => (name, phone_number),
+        (Some(_), None) => {
+            return Err(CliError::InvalidInputError(String::from(
+                "A contact phone number is required with a contact name",
+            )))
         }
-    }
+        (None, Some(_)) => {
+            return Err(CliError::InvalidInputError(String::from(
+                "A contact name is required with a contact phone number",
+            )))
+        }
+    };
+    let language_code = language_code()?;
+    contact::validate_language_code(language_code)?;
+
+    let mut contact = Organization_Contact::new();
+    contact.set_name(String::from(name));
+    contact.set_phone_number(contact::normalize_phone_number(
+        phone_number,
+        strict_contact,
+    )?);
+    contact.set_language_code(String::from(language_code));
+    Ok(Some(contact))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -663,9 +1106,7 @@ fn build_create_organization_action_payload(
     id: &str,
     organization_type: Organization_Type,
     name: &str,
-    contact_name: &str,
-    contact_phone_number: &str,
-    contact_language_code: &str,
+    contact: Option<Organization_Contact>,
     street: &str,
     city: &str,
     state_province: Option<&str>,
@@ -676,12 +1117,9 @@ fn build_create_organization_action_payload(
     payload.set_id(String::from(id));
     payload.set_organization_type(organization_type);
     payload.set_name(String::from(name));
-
-    let mut new_contact = Organization_Contact::new();
-    new_contact.set_name(String::from(contact_name));
-    new_contact.set_phone_number(String::from(contact_phone_number));
-    new_contact.set_language_code(String::from(contact_language_code));
-    payload.set_contacts(protobuf::RepeatedField::from_vec(vec![new_contact]));
+    payload.set_contacts(protobuf::RepeatedField::from_vec(
+        contact.into_iter().collect(),
+    ));
 
     if organization_type == Organization_Type::FACTORY {
         let mut address = Factory_Address::new();
@@ -726,17 +1164,28 @@ fn build_create_certificate_action_payload(
     cert_data: Vec<Certificate_CertificateData>,
     valid_from: &str,
     valid_to: &str,
-) -> IssueCertificateAction {
+) -> Result<IssueCertificateAction, CliError> {
+    let parse_timestamp = |timestamp: &str| {
+        commands::parse_timestamp(timestamp)
+            .map(|timestamp| timestamp as u64)
+            .map_err(|_| {
+                CliError::InvalidInputError(format!(
+                    "Certificate {} has an invalid timestamp {:?}. Expected seconds since the Unix epoch, a date formatted as YYYY-MM-DD, or now with an optional offset",
+                    certificate_id, timestamp
+                ))
+            })
+    };
+
     let mut payload = IssueCertificateAction::new();
     payload.set_id(String::from(certificate_id));
     payload.set_factory_id(String::from(factory_id));
     payload.set_source(IssueCertificateAction_Source::INDEPENDENT);
     payload.set_certificate_data(::protobuf::RepeatedField::from_vec(cert_data));
-    payload.set_valid_from(valid_from.parse().unwrap());
-    payload.set_valid_to(valid_to.parse().unwrap());
+    payload.set_valid_from(parse_timestamp(valid_from)?);
+    payload.set_valid_to(parse_timestamp(valid_to)?);
     payload.set_standard_id(String::from(standard_id));
 
-    payload
+    Ok(payload)
 }
 
 fn create_factory_assertion_payload(
@@ -784,10 +1233,13 @@ fn create_certificate_assertion_payload(
     payload
 }
 
-fn create_transfer_assertion_payload(assertion_id: &str) -> CertificateRegistryPayload {
+fn create_transfer_assertion_payload(
+    assertion_id: &str,
+    new_owner_public_key: &str,
+) -> CertificateRegistryPayload {
     let mut transfer = TransferAssertionAction::new();
     transfer.set_assertion_id(String::from(assertion_id));
-    transfer.set_new_owner_public_key(String::from("Plz dont use dis"));
+    transfer.set_new_owner_public_key(String::from(new_owner_public_key));
 
     let mut payload = CertificateRegistryPayload::new();
     payload.action = CertificateRegistryPayload_Action::TRANSFER_ASSERTION;
@@ -956,12 +1408,12 @@ fn submit_certificate_assertion_transaction(
     factory_id: &str,
     standard_id: &str,
     key: Option<&str>,
-    url: &str,
-) -> Result<(), CliError> {
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    client: &submit::ApiClient,
+    options: &TransactionOptions,
+) -> Result<CommandOutcome, CliError> {
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     let (header_input, header_output) = create_certificate_assertion_transaction_addresses(
         &signer,
@@ -972,45 +1424,105 @@ fn submit_certificate_assertion_transaction(
         standard_id,
     )?;
 
-    let txn = create_transaction(&assertion_payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &assertion_payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!(
-                    "Assertion {} has been created for certificate {}",
-                    assertion_id, certificate_id
-                );
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            // "UNKNOWN" case where we should recheck
-            // "STATUS_UNSET" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(url, &batch_status.link)?;
-            }
-        }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(assertion_id.to_string()),
+        &batch_status,
+        format!(
+            "Assertion {} has been created for certificate {}",
+            assertion_id, certificate_id
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submit_assertions_batch_list_test() {
+        let assertion_ids = || {
+            let mut assertion_ids = BTreeMap::new();
+            assertion_ids.insert(String::from("factory-1"), String::from("assertion-1"));
+            assertion_ids
+        };
+        let txn_entries = vec![(String::from("txn-id"), "factory-1")];
+
+        let client = submit::mock::client(&["PENDING", "COMMITTED"]);
+        let outcome = submit_assertions_batch_list(
+            "factory",
+            assertion_ids(),
+            submit::mock::batch_list(),
+            &txn_entries,
+            &client,
+            10,
+        )
+        .unwrap();
+        assert_eq!(outcome.status, "COMMITTED");
+        assert_eq!(outcome.details, assertion_ids());
+
+        let client = submit::mock::client(&["INVALID"]);
+        assert!(submit_assertions_batch_list(
+            "factory",
+            assertion_ids(),
+            submit::mock::batch_list(),
+            &txn_entries,
+            &client,
+            10,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn factory_contact_test() {
+        let language_code = || Ok("en");
+        assert!(factory_contact(None, None, language_code, true)
+            .unwrap()
+            .is_none());
+
+        let contact = factory_contact(Some("Ana"), Some("+1 415 555 2671"), language_code, true)
+            .unwrap()
+            .unwrap();
+        assert_eq!(contact.get_phone_number(), "+14155552671");
+        assert_eq!(contact.get_language_code(), "en");
+
+        assert!(factory_contact(Some("Ana"), None, language_code, true).is_err());
+        assert!(factory_contact(None, Some("+14155552671"), language_code, true).is_err());
+    }
+
+    #[test]
+    fn assertion_view_test() {
+        let assertion: AssertionRecord = submit::parse_data(serde_json::json!({
+            "assertion_id": "assertion-1",
+            "assertion_type": "FACTORY",
+            "assertor_org_id": "org-1",
+            "object_id": "factory-1"
+        }))
+        .expect("Failed to parse the assertion");
+        let view = AssertionView::from(assertion);
+        assert_eq!(view.assertion_type, "factory");
+        assert_eq!(view.asserter_organization_id, "org-1");
+        assert_eq!(view.target_id, "factory-1");
+
+        // A missing field is an error rather than shown as unknown
+        assert!(submit::parse_data::<AssertionRecord>(serde_json::json!({
+            "assertion_id": "assertion-1",
+            "assertion_type": "FACTORY",
+            "object_id": "factory-1"
+        }))
+        .is_err());
     }
 }
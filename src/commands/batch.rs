@@ -0,0 +1,50 @@
+use crate::batch_file::{self, BatchFormat};
+use crate::commands;
+use crate::commands::CommandOutcome;
+use crate::error::CliError;
+use crate::key;
+use crate::transaction::{sign_batch_list, TransactionOptions};
+
+use clap::ArgMatches;
+use std::fs;
+
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    match args.subcommand() {
+        ("sign", Some(args)) => run_sign_command(args),
+        _ => Err(CliError::InvalidInputError(String::from(
+            "Invalid subcommand. Pass --help for usage",
+        ))),
+    }
+}
+
+/// Signs a batch list written with `--output-batch --sign-later`, writing the
+/// signed batch list to another file in the same `--batch-format`
+fn run_sign_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let input = commands::require_arg(args, "input")?;
+    let output = commands::require_arg(args, "output")?;
+
+    batch_file::check_new_batch_file(output)?;
+    let format = BatchFormat::from_args(args)?;
+    let batch_list = batch_file::decode_batch_list(&fs::read(input)?, format)?;
+
+    let signing_context =
+        key::SigningContext::new(args.value_of("key"), &TransactionOptions::from_args(args)?)?;
+    let signed = sign_batch_list(&batch_list, &signing_context.signer())?;
+    batch_file::write_batch_file(&signed, output, format, args.is_present("batch_manifest"))?;
+
+    let batch_ids: Vec<&str> = signed
+        .get_batches()
+        .iter()
+        .map(|batch| batch.get_header_signature())
+        .collect();
+    let mut outcome = CommandOutcome::completed(format!(
+        "Signed {} batch(es) into {}: {}",
+        batch_ids.len(),
+        output,
+        batch_ids.join(", ")
+    ));
+    outcome
+        .details
+        .insert(String::from("batch_ids"), batch_ids.join(","));
+    Ok(outcome)
+}
@@ -1,11 +1,16 @@
+use crate::commands;
+use crate::commands::{CommandOutcome, Listing};
 use crate::error::CliError;
 use crate::key;
+use crate::prompt;
+use crate::render::TableRow;
 use crate::submit;
 use crate::transaction::{
     create_batch, create_batch_list, create_batch_list_from_one, create_batch_with_transactions,
-    create_transaction,
+    create_transaction, TransactionOptions,
 };
 
+use chrono::{Duration, NaiveDateTime, Utc};
 use clap::ArgMatches;
 use common::addressing;
 use common::proto::certificate::Certificate_CertificateData;
@@ -13,65 +18,43 @@ use common::proto::payload::{
     CertificateRegistryPayload, CertificateRegistryPayload_Action, IssueCertificateAction_Source,
 };
 use common::proto::payload::{IssueCertificateAction, UpdateCertificateAction};
+use sawtooth_sdk::messages::batch::BatchList;
 use sawtooth_sdk::messages::transaction::Transaction;
-use sawtooth_sdk::signing;
-use std::fs::File;
-use std::io::prelude::*;
-use std::{thread, time};
+use serde::de::{self, Deserialize, Deserializer};
+use serde_derive::{Deserialize, Serialize};
 
-const SECP_256K1: &str = "secp256k1";
-
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     match args.subcommand() {
         ("create", Some(args)) => run_create_command(args),
         ("update", Some(args)) => run_update_command(args),
         ("batch_update", Some(args)) => run_batch_update_command(args),
+        ("list", Some(args)) => run_list_command(args),
+        ("verify", Some(args)) => run_verify_command(args),
         _ => Err(CliError::InvalidInputError(String::from(
             "Invalid subcommand. Pass --help for usage",
         ))),
     }
 }
 
-fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
-    let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-    let cert_id = args.value_of("id").unwrap();
-    let certifying_body_id = args.value_of("certifying_body_id").unwrap();
-    let factory_id = args.value_of("factory_id").unwrap();
-    let source = args.value_of("source").unwrap();
+fn run_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let cert_id = commands::require_arg(args, "id")?;
+    let certifying_body_id = commands::require_arg(args, "certifying_body_id")?;
+    let key = key::signing_key_for_org(args, certifying_body_id)?;
+    let factory_id = commands::require_arg(args, "factory_id")?;
+    let source = commands::require_arg(args, "source")?;
     let request_id = args.value_of("request_id");
-    let standard_id = args.value_of("standard_id").unwrap();
-    let valid_from = args.value_of("valid_from").unwrap();
-    let valid_to = args.value_of("valid_to").unwrap();
-
-    let cert_data: Result<Vec<Certificate_CertificateData>, CliError> = args
-        .values_of("cert_data")
-        .map(|values| {
-            values
-                .map(|cert_data| {
-                    let cd: Vec<&str> = cert_data.split(':').collect();
-                    match (cd.get(0), cd.get(1)) {
-                        (Some(field), Some(data)) => {
-                            let mut ccd: Certificate_CertificateData =
-                                Certificate_CertificateData::new();
-                            ccd.set_field(field.to_string());
-                            ccd.set_data(data.to_string());
-                            Ok(ccd)
-                        }
-                        _ => Err(CliError::InvalidInputError(String::from(
-                            "Invalid format for cert_data",
-                        ))),
-                    }
-                })
-                .collect()
-        })
-        .unwrap_or_else(|| Ok(vec![]));
+    let standard_id = parse_standard_id(commands::require_arg(args, "standard_id")?)?;
+    let valid_from =
+        commands::parse_timestamp(commands::require_arg(args, "valid_from")?)?.to_string();
+    commands::validate_id("certificate", cert_id)?;
+    commands::validate_id("certifying body", certifying_body_id)?;
+    commands::validate_id("factory", factory_id)?;
+    commands::validate_id("standard", standard_id)?;
+    let valid_to = commands::valid_to_from_args(args, &valid_from)?;
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let public_key = context.get_public_key(&private_key)?.as_hex();
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let cert_data = commands::parse_cert_data(args.values_of("cert_data").into_iter().flatten())?;
 
     let payload = issue_certificate_payload(
         &cert_id,
@@ -79,176 +62,180 @@ fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
         source,
         request_id,
         standard_id,
-        cert_data?,
+        cert_data,
         &valid_from,
         &valid_to,
     )?;
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
+    }
+
+    commands::check_replace_existing(args, "Certificate", cert_id, |id| {
+        client.certificate_exists(id)
+    })?;
+
+    if args.is_present("preflight") {
+        if !client.organization_exists(factory_id)? {
+            return Err(CliError::UserError(format!(
+                "Factory {} does not exist",
+                factory_id
+            )));
+        }
+        if !client.standard_exists(standard_id)? {
+            return Err(CliError::UserError(format!(
+                "Standard {} does not exist",
+                standard_id
+            )));
+        }
+    }
 
-    let mut header_input =
-        make_create_header_input(&public_key, &certifying_body_id, &cert_id, &factory_id);
+    let signing_context = key::SigningContext::new(key.as_deref(), &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+    let public_key = signer.get_public_key()?.as_hex();
+
+    let mut header_input = make_create_header_input(
+        &public_key,
+        &certifying_body_id,
+        &cert_id,
+        &factory_id,
+        &standard_id,
+    );
     let mut header_output = vec![addressing::make_certificate_address(cert_id)];
     if payload.get_issue_certificate().get_source() == IssueCertificateAction_Source::FROM_REQUEST {
         let request_address = addressing::make_request_address(request_id.unwrap());
         header_input.push(request_address.clone());
         header_output.push(request_address);
     }
-    let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        &options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(&url, &link))?;
-
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Certificate {} has been issued", cert_id);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(&url, &batch_status.link)?;
-            }
-        }
-    }
+    certificate_status_handler(cert_id, "issued", &client, &batch_list)
 }
 
-fn run_update_command(args: &ArgMatches) -> Result<(), CliError> {
-    let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-    let cert_id = args.value_of("id").unwrap();
-    let certifying_body_id = args.value_of("certifying_body_id").unwrap();
-    let valid_from = args.value_of("valid_from").unwrap();
-    let valid_to = args.value_of("valid_to").unwrap();
-
-    let cert_data: Result<Vec<Certificate_CertificateData>, CliError> = args
-        .values_of("cert_data")
-        .map(|values| {
-            values
-                .map(|cert_data| {
-                    let cd: Vec<&str> = cert_data.split(':').collect();
-                    match (cd.get(0), cd.get(1)) {
-                        (Some(field), Some(data)) => {
-                            let mut ccd: Certificate_CertificateData =
-                                Certificate_CertificateData::new();
-                            ccd.set_field(field.to_string());
-                            ccd.set_data(data.to_string());
-                            Ok(ccd)
-                        }
-                        _ => Err(CliError::InvalidInputError(String::from(
-                            "Invalid format for cert_data",
-                        ))),
-                    }
-                })
-                .collect()
-        })
-        .unwrap_or_else(|| Ok(vec![]));
+fn run_update_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let cert_id = commands::require_arg(args, "id")?;
+    let certifying_body_id = commands::require_arg(args, "certifying_body_id")?;
+    let key = key::signing_key_for_org(args, certifying_body_id)?;
+    let valid_from =
+        commands::parse_timestamp(commands::require_arg(args, "valid_from")?)?.to_string();
+    let valid_to = commands::parse_timestamp(commands::require_arg(args, "valid_to")?)?.to_string();
+    let request_id = args.value_of("request_id");
+    commands::validate_id("certificate", cert_id)?;
+    commands::validate_id("certifying body", certifying_body_id)?;
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let public_key = context.get_public_key(&private_key)?.as_hex();
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let mut cert_data =
+        commands::parse_cert_data(args.values_of("cert_data").into_iter().flatten())?;
+    if args.is_present("merge_cert_data") {
+        cert_data = merge_cert_data(client.certificate_data(cert_id)?, cert_data);
+    }
+
+    let payload = update_certificate_payload(&cert_id, cert_data, &valid_from, &valid_to)?;
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
+    }
 
-    let payload = update_certificate_payload(&cert_id, cert_data?, &valid_from, &valid_to);
+    let signing_context = key::SigningContext::new(key.as_deref(), &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
+    let public_key = signer.get_public_key()?.as_hex();
 
-    let header_input = make_update_header_input(&public_key, &certifying_body_id, &cert_id);
-    let header_output = vec![addressing::make_certificate_address(cert_id)];
-    let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let mut header_input = make_update_header_input(&public_key, &certifying_body_id, &cert_id);
+    let mut header_output = vec![addressing::make_certificate_address(cert_id)];
+    // Certificates issued FROM_REQUEST also touch the request they were issued for
+    if let Some(request_id) = request_id {
+        let request_address = addressing::make_request_address(request_id);
+        header_input.push(request_address.clone());
+        header_output.push(request_address);
+    }
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        &options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut batch_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(&url, &link))?;
+    prompt::confirm(
+        &format!("This will modify certificate {} on chain.", cert_id),
+        args.is_present("assume_yes"),
+    )?;
 
-    loop {
-        match batch_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Certificate {} has been updated", cert_id);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    batch_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                batch_status = submit::wait_for_status(&url, &batch_status.link)?;
-            }
-        }
-    }
+    certificate_status_handler(cert_id, "updated", &client, &batch_list)
+}
+
+fn certificate_status_handler(
+    cert_id: &str,
+    action: &str,
+    client: &submit::ApiClient,
+    batch_list: &BatchList,
+) -> Result<CommandOutcome, CliError> {
+    let batch_status = client.submit_and_wait(batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(cert_id.to_string()),
+        &batch_status,
+        format!("Certificate {} has been {}", cert_id, action),
+    ))
 }
 
-fn run_batch_update_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_batch_update_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-
-    // Define uninitialized arguments
-    let mut cert_id: &str;
-    let mut certifying_body_id: &str;
-    let mut valid_from: &str;
-    let mut valid_to: &str;
-    // TODO: support this eventually
-    let mut cert_data: Result<Vec<Certificate_CertificateData>, CliError>;
-
-    // Read factories from provided JSON batch file
-    let filepath = args.value_of("filepath").unwrap();
-    let mut file = File::open(filepath)?;
-    let mut data: String = String::new();
-    file.read_to_string(&mut data)?;
-    let certificates: serde_json::Value = serde_json::from_str(&data).expect("Unable to parse");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+
+    // Read factories from provided batch file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let certificates: serde_json::Map<String, serde_json::Value> = commands::read_batch_file(args)?;
+    let entry_filter = commands::EntryFilter::from_args(args)?;
 
     // Create signing key
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     // Loop through map of certificates and populate list of transactions
-    println!("Creating transactions for {}", filepath);
+    eprintln!("Creating transactions for {}", filepath);
     let mut txn_list: Vec<Transaction> = vec![];
-    for (key, value) in certificates.as_object().unwrap() {
-        // Gather information and initialize defined variables from above
-        cert_id = key.as_str();
-        certifying_body_id = value.get("certifying_body_id").unwrap().as_str().unwrap();
-        valid_from = value.get("valid_from").unwrap().as_str().unwrap();
-        valid_to = value.get("valid_to").unwrap().as_str().unwrap();
-        cert_data = Ok(vec![]);
+    let mut txn_entries: Vec<(String, &str)> = vec![];
+    for (key, value) in &certificates {
+        if !entry_filter.includes(key) {
+            continue;
+        }
+
+        let cert_id = key.as_str();
+        let certifying_body_id = commands::batch_entry_field(key, value, "certifying_body_id")?;
+        commands::validate_id("certificate", cert_id)?;
+        commands::validate_id("certifying body", certifying_body_id)?;
+        let timestamp = |field: &str| {
+            let timestamp = value.get(field).unwrap_or(&serde_json::Value::Null);
+            commands::entry_timestamp(key, field, timestamp).map(|timestamp| timestamp.to_string())
+        };
+        let valid_from = timestamp("valid_from")?;
+        let valid_to = timestamp("valid_to")?;
+        // TODO: support certificate data in batch files eventually
+        let cert_data = vec![];
 
         // Build update certificate payload
         let update_cert_payload =
-            update_certificate_payload(cert_id, cert_data?, valid_from, valid_to);
+            update_certificate_payload(cert_id, cert_data, &valid_from, &valid_to)?;
 
         // Create a transaction address for the transaction
         let header_input = make_update_header_input(
@@ -257,46 +244,415 @@ fn run_batch_update_command(args: &ArgMatches) -> Result<(), CliError> {
             &cert_id,
         );
         let header_output = vec![addressing::make_certificate_address(cert_id)];
-        let txn = create_transaction(&update_cert_payload, &signer, header_input, header_output)?;
+        let txn = create_transaction(
+            &update_cert_payload,
+            &signer,
+            batcher.as_ref(),
+            header_input,
+            header_output,
+            vec![],
+            &options,
+        )?;
+        txn_entries.push((txn.header_signature.clone(), key.as_str()));
         txn_list.push(txn);
     }
 
-    println!("Creating batch list for transactions");
-    let batch = create_batch_with_transactions(txn_list, &signer)?;
+    if txn_list.is_empty() {
+        return Ok(commands::nothing_to_submit(filepath, "certificates"));
+    }
+
+    prompt::confirm_input(
+        &format!("This will modify {} certificates on chain.", txn_list.len()),
+        filepath,
+        args.is_present("assume_yes"),
+    )?;
+
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list(vec![batch]);
 
-    let mut update_cert_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
+    let batch_status =
+        client.submit_batch_and_wait(&batch_list, &txn_entries, max_invalid_display)?;
+
+    Ok(CommandOutcome::submitted(
+        None,
+        &batch_status,
+        format!("Certificates from file {} have been updated", filepath),
+    ))
+}
+
+fn run_list_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+    let expiring_within = args
+        .value_of("expiring_within")
+        .map(commands::parse_duration)
+        .transpose()?;
+    let since = args
+        .value_of("since")
+        .map(commands::parse_timestamp)
+        .transpose()?;
+    let until = args
+        .value_of("until")
+        .map(commands::parse_timestamp)
+        .transpose()?;
+
+    let certificates: Vec<CertificateRecord> = client.fetch_all_data("/certificates")?;
+
+    let mut summaries: Vec<CertificateView> = certificates
+        .into_iter()
+        .map(CertificateView::from)
+        .collect();
+    if since.is_some() || until.is_some() {
+        summaries = valid_from_between(summaries, since, until);
+    }
+    if let Some(expiring_within) = expiring_within {
+        summaries = expiring_between(summaries, Utc::now().timestamp(), expiring_within);
+    }
+
+    let mut outcome = CommandOutcome::completed(
+        summaries
+            .iter()
+            .map(CertificateView::describe)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    outcome.listing = Some(Listing::new("certificate", &summaries)?);
+    Ok(outcome)
+}
+
+/// A certificate as returned by the REST API
+#[derive(Deserialize)]
+struct CertificateRecord {
+    id: String,
+    certifying_body_id: String,
+    factory_id: String,
+    standard_id: String,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    valid_from: i64,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    valid_to: i64,
+}
+
+/// An organization as returned by the REST API, with only the fields read by
+/// `certificate verify`
+#[derive(Deserialize)]
+struct OrganizationRecord {
+    accreditations: Vec<AccreditationRecord>,
+}
+
+/// An accreditation of a certifying body as returned by the REST API
+#[derive(Deserialize)]
+struct AccreditationRecord {
+    standard_id: String,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    valid_from: i64,
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    valid_to: i64,
+}
+
+/// Deserializes a timestamp in seconds since the epoch
+///
+/// The REST API may give timestamps as numbers or as strings of digits.
+fn deserialize_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Timestamp {
+        Number(i64),
+        Text(String),
+    }
+
+    match Timestamp::deserialize(deserializer)? {
+        Timestamp::Number(timestamp) => Ok(timestamp),
+        Timestamp::Text(timestamp) => timestamp
+            .parse()
+            .map_err(|_| de::Error::custom(format!("invalid timestamp \"{}\"", timestamp))),
+    }
+}
+
+/// A certificate as listed by `certificate list`
+///
+/// The field names are part of the `--format json` output, so they are kept
+/// stable whatever the REST API calls them.
+#[derive(Serialize)]
+struct CertificateView {
+    certificate_id: String,
+    factory_id: String,
+    standard_id: String,
+    /// The start of the certificate's validity, in seconds since the epoch
+    valid_from: i64,
+    /// The end of the certificate's validity, in seconds since the epoch
+    valid_to: i64,
+}
+
+impl From<CertificateRecord> for CertificateView {
+    fn from(certificate: CertificateRecord) -> Self {
+        CertificateView {
+            certificate_id: certificate.id,
+            factory_id: certificate.factory_id,
+            standard_id: certificate.standard_id,
+            valid_from: certificate.valid_from,
+            valid_to: certificate.valid_to,
+        }
+    }
+}
+
+impl CertificateView {
+    fn describe(&self) -> String {
+        format!(
+            "Certificate {}: standard {} for factory {}, valid to {}",
+            self.certificate_id,
+            self.standard_id,
+            self.factory_id,
+            describe_timestamp(self.valid_to)
+        )
+    }
+}
+
+impl TableRow for CertificateView {
+    const COLUMNS: &'static [&'static str] = &[
+        "CERTIFICATE",
+        "FACTORY",
+        "STANDARD",
+        "VALID FROM",
+        "VALID TO",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.certificate_id.clone(),
+            self.factory_id.clone(),
+            self.standard_id.clone(),
+            describe_timestamp(self.valid_from),
+            describe_timestamp(self.valid_to),
+        ]
+    }
+}
+
+/// Returns the date and time of a timestamp in seconds since the epoch, or the
+/// timestamp itself if it is out of range
+fn describe_timestamp(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp_opt(timestamp, 0)
+        .map_or_else(|| timestamp.to_string(), |date| date.to_string())
+}
+
+/// Returns the certificates whose validity ends between `now` and `now` plus
+/// `within`, soonest first
+fn expiring_between(
+    certificates: Vec<CertificateView>,
+    now: i64,
+    within: Duration,
+) -> Vec<CertificateView> {
+    let until = now.saturating_add(within.num_seconds());
+    let mut expiring: Vec<CertificateView> = certificates
+        .into_iter()
+        .filter(|certificate| now <= certificate.valid_to && certificate.valid_to <= until)
+        .collect();
+    expiring.sort_by_key(|certificate| certificate.valid_to);
+    expiring
+}
+
+/// Returns the certificates whose validity starts between `since` and `until`,
+/// either of which may be left open
+fn valid_from_between(
+    certificates: Vec<CertificateView>,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Vec<CertificateView> {
+    certificates
+        .into_iter()
+        .filter(|certificate| {
+            since.map_or(true, |since| since <= certificate.valid_from)
+                && until.map_or(true, |until| certificate.valid_from <= until)
+        })
+        .collect()
+}
+
+fn run_verify_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+    let cert_id = commands::require_arg(args, "id")?;
+    commands::validate_id("certificate", cert_id)?;
+
+    let certificate: CertificateRecord = client
+        .fetch_data(&format!("/certificates/{}", cert_id))?
+        .ok_or_else(|| CliError::UserError(format!("Certificate {} does not exist", cert_id)))
+        .and_then(submit::parse_data)?;
+    let CertificateRecord {
+        certifying_body_id,
+        standard_id,
+        valid_from,
+        valid_to,
+        ..
+    } = certificate;
+
+    let accreditations = client
+        .fetch_data(&format!("/organizations/{}", certifying_body_id))?
+        .ok_or_else(|| {
+            CliError::UserError(format!(
+                "Certifying body {} of certificate {} does not exist",
+                certifying_body_id, cert_id
+            ))
+        })
+        .and_then(submit::parse_data::<OrganizationRecord>)?
+        .accreditations;
+    let accreditation = find_accreditation(&accreditations, &standard_id, valid_from);
+    let verdict = verify_certificate(
+        valid_from,
+        valid_to,
+        accreditation.is_some(),
+        Utc::now().timestamp(),
+    );
+
+    let reason = match verdict {
+        Verdict::Valid => format!(
+            "valid from {} to {}, and issued by {} while accredited for standard {}",
+            describe_timestamp(valid_from),
+            describe_timestamp(valid_to),
+            certifying_body_id,
+            standard_id
+        ),
+        Verdict::NotYetValid => format!("it is only valid from {}", describe_timestamp(valid_from)),
+        Verdict::Expired => format!("it was valid to {}", describe_timestamp(valid_to)),
+        Verdict::NotAccredited => format!(
+            "certifying body {} held no accreditation for standard {} on {}, when it was issued",
+            certifying_body_id,
+            standard_id,
+            describe_timestamp(valid_from)
+        ),
+    };
+    let message = format!("Certificate {} is {}: {}", cert_id, verdict.name(), reason);
+    // Anything short of valid fails the command, so that scripts can rely on
+    // the exit status
+    if verdict != Verdict::Valid {
+        return Err(CliError::UserError(message));
+    }
+
+    let mut outcome = CommandOutcome::completed(message);
+    outcome.id = Some(cert_id.to_string());
+    for (name, value) in &[
+        ("verdict", verdict.name().to_string()),
+        ("certifying_body_id", certifying_body_id.clone()),
+        ("standard_id", standard_id.clone()),
+        ("valid_from", valid_from.to_string()),
+        ("valid_to", valid_to.to_string()),
+    ] {
+        outcome.details.insert(name.to_string(), value.clone());
+    }
+    if let Some((accredited_from, accredited_to)) = accreditation {
+        outcome
+            .details
+            .insert(String::from("accredited_from"), accredited_from.to_string());
+        outcome
+            .details
+            .insert(String::from("accredited_to"), accredited_to.to_string());
+    }
+    Ok(outcome)
+}
+
+/// Whether a certificate can be relied on, as reported by `certificate verify`
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Verdict {
+    Valid,
+    NotYetValid,
+    Expired,
+    /// The certifying body was not accredited for the standard when the
+    /// certificate was issued
+    NotAccredited,
+}
+
+impl Verdict {
+    fn name(self) -> &'static str {
+        match self {
+            Verdict::Valid => "VALID",
+            Verdict::NotYetValid => "NOT_YET_VALID",
+            Verdict::Expired => "EXPIRED",
+            Verdict::NotAccredited => "NOT_ACCREDITED",
+        }
+    }
+}
+
+/// Returns the verdict on a certificate valid from `valid_from` to `valid_to`
+/// at the time `now`
+///
+/// A certificate issued without an accreditation is never valid, so that is
+/// checked before its validity window.
+fn verify_certificate(valid_from: i64, valid_to: i64, accredited: bool, now: i64) -> Verdict {
+    if !accredited {
+        Verdict::NotAccredited
+    } else if now < valid_from {
+        Verdict::NotYetValid
+    } else if now > valid_to {
+        Verdict::Expired
+    } else {
+        Verdict::Valid
+    }
+}
+
+/// Returns the validity of the accreditation, among a certifying body's
+/// accreditations as listed by the REST API, for the given standard at the
+/// given time
+///
+/// A certificate records no issuance time, so the start of its validity is
+/// taken as the time it was issued.
+fn find_accreditation(
+    accreditations: &[AccreditationRecord],
+    standard_id: &str,
+    issued: i64,
+) -> Option<(i64, i64)> {
+    accreditations
+        .iter()
+        .filter(|accreditation| accreditation.standard_id == standard_id)
+        .map(|accreditation| (accreditation.valid_from, accreditation.valid_to))
+        .find(|(valid_from, valid_to)| *valid_from <= issued && issued <= *valid_to)
+}
+
+/// Returns the standard a certificate is issued for
+///
+/// `IssueCertificateAction` only has room for a single `standard_id`, so a
+/// comma separated list of standards is rejected rather than silently
+/// truncated.
+fn parse_standard_id(standard_id: &str) -> Result<&str, CliError> {
+    let standard_ids: Vec<&str> = standard_id.split(',').map(str::trim).collect();
+    if standard_ids.len() > 1 {
+        return Err(CliError::InvalidInputError(format!(
+            "A certificate can only be issued for a single standard, but {} were given: {}. \
+             Issue a separate certificate for each standard",
+            standard_ids.len(),
+            standard_ids.join(", ")
+        )));
+    }
+
+    Ok(standard_id.trim())
+}
 
-    loop {
-        match update_cert_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
+/// Returns the existing certificate data with the given fields set over it
+///
+/// Existing fields keep their order, with their data replaced if the field is
+/// given again. New fields are added after them.
+fn merge_cert_data(
+    existing: Vec<submit::CertificateDataRecord>,
+    cert_data: Vec<Certificate_CertificateData>,
+) -> Vec<Certificate_CertificateData> {
+    let mut merged: Vec<Certificate_CertificateData> = existing
+        .into_iter()
+        .map(|record| {
+            let mut ccd = Certificate_CertificateData::new();
+            ccd.set_field(record.field);
+            ccd.set_data(record.data);
+            ccd
+        })
+        .collect();
+
+    for ccd in cert_data {
+        match merged
+            .iter_mut()
+            .find(|existing| existing.get_field() == ccd.get_field())
         {
-            "COMMITTED" => {
-                println!("Certificates from file {} have been updated", filepath);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    update_cert_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                update_cert_status = submit::wait_for_status(url, &update_cert_status.link)?;
-            }
+            Some(existing) => existing.set_data(ccd.get_data().to_string()),
+            None => merged.push(ccd),
         }
     }
+
+    merged
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -333,8 +689,8 @@ fn issue_certificate_payload(
     }?;
     certificate.set_source(source_enum);
     certificate.set_certificate_data(::protobuf::RepeatedField::from_vec(cert_data));
-    certificate.set_valid_from(valid_from.parse().unwrap());
-    certificate.set_valid_to(valid_to.parse().unwrap());
+    certificate.set_valid_from(commands::parse_timestamp(valid_from)? as u64);
+    certificate.set_valid_to(commands::parse_timestamp(valid_to)? as u64);
 
     let mut payload = CertificateRegistryPayload::new();
     payload.action = CertificateRegistryPayload_Action::ISSUE_CERTIFICATE;
@@ -348,17 +704,17 @@ fn update_certificate_payload(
     cert_data: Vec<Certificate_CertificateData>,
     valid_from: &str,
     valid_to: &str,
-) -> CertificateRegistryPayload {
+) -> Result<CertificateRegistryPayload, CliError> {
     let mut certificate = UpdateCertificateAction::new();
     certificate.set_id(id.to_string());
     certificate.set_certificate_data(::protobuf::RepeatedField::from_vec(cert_data));
-    certificate.set_valid_from(valid_from.parse().unwrap());
-    certificate.set_valid_to(valid_to.parse().unwrap());
+    certificate.set_valid_from(commands::parse_timestamp(valid_from)? as u64);
+    certificate.set_valid_to(commands::parse_timestamp(valid_to)? as u64);
 
     let mut payload = CertificateRegistryPayload::new();
     payload.action = CertificateRegistryPayload_Action::UPDATE_CERTIFICATE;
     payload.set_update_certificate(certificate);
-    payload
+    Ok(payload)
 }
 
 fn make_create_header_input(
@@ -366,12 +722,20 @@ fn make_create_header_input(
     certifying_body_id: &str,
     certificate_id: &str,
     factory_id: &str,
+    standard_id: &str,
 ) -> Vec<String> {
     let agent_address = addressing::make_agent_address(public_key);
     let org_address = addressing::make_organization_address(certifying_body_id);
     let cert_address = addressing::make_certificate_address(certificate_id);
     let factory_address = addressing::make_organization_address(factory_id);
-    vec![agent_address, org_address, cert_address, factory_address]
+    let standard_address = addressing::make_standard_address(standard_id);
+    vec![
+        agent_address,
+        org_address,
+        cert_address,
+        factory_address,
+        standard_address,
+    ]
 }
 
 fn make_update_header_input(
@@ -384,3 +748,216 @@ fn make_update_header_input(
     let cert_address = addressing::make_certificate_address(certificate_id);
     vec![agent_address, org_address, cert_address]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_status_handler_test() {
+        let batch_list = submit::mock::batch_list();
+
+        let client = submit::mock::client(&["PENDING", "COMMITTED"]);
+        let outcome = certificate_status_handler("cert-1", "issued", &client, &batch_list).unwrap();
+        assert_eq!(outcome.id, Some(String::from("cert-1")));
+        assert_eq!(outcome.status, "COMMITTED");
+        assert_eq!(outcome.message, "Certificate cert-1 has been issued");
+
+        let client = submit::mock::client(&["INVALID"]);
+        assert!(certificate_status_handler("cert-1", "issued", &client, &batch_list).is_err());
+    }
+
+    #[test]
+    fn certificate_listing_test() {
+        let certificate: CertificateRecord = submit::parse_data(serde_json::json!({
+            "id": "cert-1",
+            "certifying_body_id": "body-1",
+            "factory_id": "factory-1",
+            "standard_id": "standard-1",
+            "valid_from": "1000",
+            "valid_to": 2000
+        }))
+        .expect("Failed to parse the certificate");
+
+        let listing = Listing::new("certificate", &[CertificateView::from(certificate)])
+            .expect("Failed to list certificates");
+        assert_eq!(listing.schema_version, commands::VIEW_SCHEMA_VERSION);
+        assert_eq!(
+            listing.items,
+            vec![serde_json::json!({
+                "certificate_id": "cert-1",
+                "factory_id": "factory-1",
+                "standard_id": "standard-1",
+                "valid_from": 1000,
+                "valid_to": 2000
+            })]
+        );
+    }
+
+    #[test]
+    fn certificate_record_missing_field_test() {
+        let certificate = serde_json::json!({
+            "id": "cert-1",
+            "certifying_body_id": "body-1",
+            "standard_id": "standard-1",
+            "valid_from": 1000,
+            "valid_to": 2000
+        });
+        match submit::parse_data::<CertificateRecord>(certificate) {
+            Err(CliError::UserError(message)) => assert!(message.contains("factory_id")),
+            Err(other) => panic!("Expected a UserError, got {:?}", other),
+            Ok(_) => panic!("Expected the missing factory_id to be an error"),
+        }
+
+        let certificate = serde_json::json!({
+            "id": "cert-1",
+            "certifying_body_id": "body-1",
+            "factory_id": "factory-1",
+            "standard_id": "standard-1",
+            "valid_from": "soon",
+            "valid_to": 2000
+        });
+        assert!(submit::parse_data::<CertificateRecord>(certificate).is_err());
+    }
+
+    #[test]
+    fn verify_certificate_test() {
+        let organization: OrganizationRecord = submit::parse_data(serde_json::json!({
+            "accreditations": [
+                {"standard_id": "standard-1", "valid_from": 1_000, "valid_to": 2_000},
+                {"standard_id": "standard-2", "valid_from": "0", "valid_to": "9000"}
+            ]
+        }))
+        .expect("Failed to parse the organization");
+        let accreditations = organization.accreditations;
+        assert_eq!(
+            find_accreditation(&accreditations, "standard-1", 1_500),
+            Some((1_000, 2_000))
+        );
+        // Accredited for the standard, but not when the certificate was issued
+        assert_eq!(
+            find_accreditation(&accreditations, "standard-1", 2_500),
+            None
+        );
+        assert_eq!(
+            find_accreditation(&accreditations, "standard-3", 1_500),
+            None
+        );
+
+        assert_eq!(
+            verify_certificate(1_500, 3_000, true, 2_000),
+            Verdict::Valid
+        );
+        assert_eq!(
+            verify_certificate(1_500, 3_000, true, 1_000),
+            Verdict::NotYetValid
+        );
+        assert_eq!(
+            verify_certificate(1_500, 3_000, true, 3_001),
+            Verdict::Expired
+        );
+        assert_eq!(
+            verify_certificate(1_500, 3_000, false, 2_000),
+            Verdict::NotAccredited
+        );
+    }
+
+    #[test]
+    fn expiring_between_test() {
+        let certificate = |certificate_id: &str, valid_to: i64| CertificateView {
+            certificate_id: certificate_id.to_string(),
+            factory_id: String::from("factory-1"),
+            standard_id: String::from("standard-1"),
+            valid_from: 0,
+            valid_to,
+        };
+        let certificates = vec![
+            certificate("expired", 900),
+            certificate("later", 1_000 + 20 * 86_400),
+            certificate("sooner", 1_000 + 86_400),
+            certificate("too-late", 1_000 + 40 * 86_400),
+        ];
+
+        let expiring = expiring_between(certificates, 1_000, Duration::days(30));
+        let ids: Vec<&str> = expiring
+            .iter()
+            .map(|certificate| certificate.certificate_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["sooner", "later"]);
+    }
+
+    #[test]
+    fn valid_from_between_test() {
+        let certificate = |certificate_id: &str, valid_from: i64| CertificateView {
+            certificate_id: certificate_id.to_string(),
+            factory_id: String::from("factory-1"),
+            standard_id: String::from("standard-1"),
+            valid_from,
+            valid_to: valid_from + 86_400,
+        };
+        let certificates = || {
+            vec![
+                certificate("before", 500),
+                certificate("start", 1_000),
+                certificate("inside", 1_500),
+                certificate("after", 2_500),
+            ]
+        };
+        let ids = |certificates: Vec<CertificateView>| -> Vec<String> {
+            certificates
+                .into_iter()
+                .map(|certificate| certificate.certificate_id)
+                .collect()
+        };
+
+        assert_eq!(
+            ids(valid_from_between(certificates(), Some(1_000), Some(2_000))),
+            vec!["start", "inside"]
+        );
+        assert_eq!(
+            ids(valid_from_between(certificates(), Some(1_000), None)),
+            vec!["start", "inside", "after"]
+        );
+        assert_eq!(
+            ids(valid_from_between(certificates(), None, Some(1_000))),
+            vec!["before", "start"]
+        );
+    }
+
+    #[test]
+    fn merge_cert_data_test() {
+        let existing = vec![
+            submit::CertificateDataRecord {
+                field: String::from("scope"),
+                data: String::from("organic"),
+            },
+            submit::CertificateDataRecord {
+                field: String::from("grade"),
+                data: String::from("A"),
+            },
+        ];
+        let cert_data = commands::parse_cert_data(vec!["grade:B", "auditor:Jane"])
+            .expect("Failed to parse cert_data");
+
+        let merged = merge_cert_data(existing, cert_data);
+        let fields: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|ccd| (ccd.get_field(), ccd.get_data()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![("scope", "organic"), ("grade", "B"), ("auditor", "Jane")]
+        );
+    }
+
+    #[test]
+    fn update_certificate_payload_test() {
+        let payload = update_certificate_payload("cert-1", vec![], "2020-01-02", "1600000000")
+            .expect("Failed to build payload");
+        let certificate = payload.get_update_certificate();
+        assert_eq!(certificate.get_valid_from(), 1_577_923_200);
+        assert_eq!(certificate.get_valid_to(), 1_600_000_000);
+
+        assert!(update_certificate_payload("cert-1", vec![], "tomorrow", "1600000000").is_err());
+    }
+}
@@ -1,30 +1,90 @@
-use crate::commands::agent::{create_agent_payload, create_agent_transaction_addresses};
+use crate::batch_file::{self, BatchFormat};
+use crate::commands::accreditation::{
+    create_accreditation_payload, create_accreditation_transaction_addresses,
+};
+use crate::commands::agent::{
+    authorize_agent_payload, authorize_agent_transaction_addresses_input, create_agent_payload,
+    create_agent_transaction_addresses,
+};
 use crate::commands::organization::{
     create_organization_payload, create_organization_transaction_addresses,
+    organization_type_matches,
+};
+use crate::commands::standard::{
+    create_standard_payload, create_standard_transaction_addresses, make_standard_id,
 };
-use crate::commands::standard::{create_standard_payload, create_standard_transaction_addresses};
+use crate::commands::CommandOutcome;
 use crate::error::CliError;
-use crate::transaction::{create_batch, create_transaction};
+use crate::key;
+use crate::output;
+use crate::prompt;
+use crate::settings::{
+    create_setting_proposal_payload, create_setting_transaction_addresses,
+    settings_transaction_options,
+};
+use crate::submit;
+use crate::transaction::{
+    chain_dependencies, create_batch, create_batch_list, create_batch_list_from_one,
+    create_batch_with_transactions, create_transaction, create_transaction_from_bytes,
+    TransactionOptions,
+};
 
 use chrono::NaiveDate;
 use clap::ArgMatches;
+use common::addressing;
 use common::proto::organization::Organization_Type;
-use protobuf::Message;
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
 use sawtooth_sdk::messages::batch::Batch;
 use sawtooth_sdk::messages::batch::BatchList;
+use sawtooth_sdk::messages::transaction::Transaction;
 use sawtooth_sdk::signing;
+use sawtooth_sdk::signing::secp256k1::Secp256k1PrivateKey;
 use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
 use std::fmt;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
+/// The contents of a genesis descriptor
+///
+/// A descriptor is either a plain list of agents, or a map with the agents and
+/// the network settings to propose ahead of them.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum GenesisDescriptor {
+    Agents(Vec<GenesisAgent>),
+    Network {
+        #[serde(default)]
+        settings: BTreeMap<String, String>,
+        #[serde(default)]
+        agents: Vec<GenesisAgent>,
+    },
+}
+
+impl GenesisDescriptor {
+    fn into_parts(self) -> (BTreeMap<String, String>, Vec<GenesisAgent>) {
+        match self {
+            GenesisDescriptor::Agents(agents) => (BTreeMap::new(), agents),
+            GenesisDescriptor::Network { settings, agents } => (settings, agents),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct GenesisAgent {
     email: String,
     organization: Option<GenesisOrganization>,
+    /// Whether the agent is also explicitly authorized as an ADMIN of the
+    /// organization it creates, in the same batch. The transaction processor
+    /// already makes the creator of an organization its ADMIN and TRANSACTOR,
+    /// and may reject the repeated authorization, so this is off by default
+    #[serde(default)]
+    authorize_as_admin: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -38,6 +98,8 @@ enum GenesisOrganization {
     CertifyingBody {
         name: String,
         contact: GenesisContact,
+        #[serde(default)]
+        accreditations: Vec<GenesisAccreditation>,
     },
     Factory {
         name: String,
@@ -46,6 +108,24 @@ enum GenesisOrganization {
     },
 }
 
+impl GenesisOrganization {
+    fn name(&self) -> &str {
+        match self {
+            GenesisOrganization::StandardsBody { name, .. } => name,
+            GenesisOrganization::CertifyingBody { name, .. } => name,
+            GenesisOrganization::Factory { name, .. } => name,
+        }
+    }
+
+    fn organization_type(&self) -> Organization_Type {
+        match self {
+            GenesisOrganization::StandardsBody { .. } => Organization_Type::STANDARDS_BODY,
+            GenesisOrganization::CertifyingBody { .. } => Organization_Type::CERTIFYING_BODY,
+            GenesisOrganization::Factory { .. } => Organization_Type::FACTORY,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct GenesisStandard {
     name: String,
@@ -56,6 +136,29 @@ struct GenesisStandard {
     approval_date: u64,
 }
 
+/// An accreditation of a certifying body for one of the standards created by
+/// the standards bodies of the descriptor
+#[derive(Serialize, Deserialize, Debug)]
+struct GenesisAccreditation {
+    /// The name of the standard
+    standard: String,
+    #[serde(deserialize_with = "date_to_epoch_time")]
+    valid_from: u64,
+    #[serde(deserialize_with = "date_to_epoch_time")]
+    valid_to: u64,
+}
+
+/// A standard created by genesis, which certifying bodies may be accredited for
+struct CreatedStandard {
+    standard_id: String,
+    standards_body_id: String,
+    /// The index of the standards body's agent, whose key signs accreditations
+    /// for the standard
+    agent_index: usize,
+    /// The transaction creating the standard
+    dependencies: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct GenesisContact {
     name: String,
@@ -73,136 +176,867 @@ struct GenesisAddress {
     country: String,
 }
 
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
-    let context = signing::create_context("secp256k1")?;
+/// The network genesis tops up with `--check-existing`, as reported by the
+/// REST API given by `--url`
+struct ExistingNetwork {
+    client: submit::ApiClient,
+    /// Every organization on the network
+    organizations: Vec<serde_json::Value>,
+}
+
+impl ExistingNetwork {
+    fn from_args(args: &ArgMatches) -> Result<Self, CliError> {
+        let client = submit::ApiClient::from_args(args)?;
+        let organizations = client.fetch_all_data("/organizations")?;
+        Ok(ExistingNetwork {
+            client,
+            organizations,
+        })
+    }
+
+    /// Returns the id of the organization on the network with the name and
+    /// type of the given one
+    ///
+    /// Genesis gives organizations random ids, so they are matched by name.
+    fn organization_id(&self, org: &GenesisOrganization) -> Option<String> {
+        self.organizations
+            .iter()
+            .find(|organization| {
+                organization.get("name").and_then(serde_json::Value::as_str) == Some(org.name())
+                    && organization_type_matches(organization, org.organization_type())
+            })
+            .and_then(|organization| organization.get("id"))
+            .and_then(serde_json::Value::as_str)
+            .map(String::from)
+    }
+
+    /// Returns whether the certifying body on the network is accredited for
+    /// the given standard
+    fn accredited(&self, certifying_body_id: &str, standard_id: &str) -> bool {
+        self.organizations
+            .iter()
+            .filter(|organization| {
+                organization.get("id").and_then(serde_json::Value::as_str)
+                    == Some(certifying_body_id)
+            })
+            .filter_map(|organization| organization.get("accreditations"))
+            .filter_map(serde_json::Value::as_array)
+            .flatten()
+            .any(|accreditation| {
+                accreditation
+                    .get("standard_id")
+                    .and_then(serde_json::Value::as_str)
+                    == Some(standard_id)
+            })
+    }
+}
+
+/// What genesis created, and what it left out because it is already on the
+/// network
+#[derive(Default)]
+struct GenesisReport {
+    created: Vec<String>,
+    skipped: Vec<String>,
+}
+
+impl GenesisReport {
+    fn describe(&self) -> String {
+        self.created
+            .iter()
+            .map(|created| format!("Created {}", created))
+            .chain(
+                self.skipped
+                    .iter()
+                    .map(|skipped| format!("Skipped {}, which already exists", skipped)),
+            )
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// An entry of the genesis manifest, identifying an agent created by genesis
+#[derive(Serialize, Debug)]
+struct GenesisManifestEntry {
+    email: String,
+    public_key: String,
+    organization_id: Option<String>,
+    organization_name: Option<String>,
+}
+
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let context = signing::create_context(key::SECP_256K1)?;
     let factory = signing::CryptoFactory::new(&*context);
+    let options = TransactionOptions::from_args(args)?;
+    let batcher_key = key::load_optional_signing_key(options.batcher_key.as_deref())?;
+    let batcher = batcher_key.as_ref().map(|k| factory.new_signer(k));
+    let seed = args.value_of("seed").map(parse_seed).transpose()?;
 
     let output_file = args
         .value_of("output")
         .unwrap_or("consensource-genesis.batch");
+    let batch_format = BatchFormat::from_args(args)?;
+    let max_batches_per_file = args
+        .value_of("max_batches_per_file")
+        .map(|_| match value_t!(args, "max_batches_per_file", usize) {
+            Ok(max_batches) if max_batches > 0 => Ok(max_batches),
+            _ => Err(CliError::InvalidInputError(String::from(
+                "Invalid value for --max-batches-per-file. Please provide a positive number",
+            ))),
+        })
+        .transpose()?;
     let generated_keys_dir = args.value_of("keys_directory");
+    if let Some(key_dir) = generated_keys_dir {
+        prepare_keys_directory(key_dir)?;
+    }
+    let manifest_file = args.value_of("manifest");
     let genesis_descriptor = args.value_of("descriptor").unwrap_or("genesis.yaml");
 
-    let descriptor_file = File::open(&Path::new(genesis_descriptor))?;
+    let mut descriptor_text = String::new();
+    File::open(&Path::new(genesis_descriptor))?.read_to_string(&mut descriptor_text)?;
+    let descriptor_text = expand_env_vars(&descriptor_text, |name| env::var(name).ok())?;
 
-    let agents: Vec<GenesisAgent> = serde_yaml::from_reader(descriptor_file).map_err(|err| {
+    let descriptor: GenesisDescriptor = serde_yaml::from_str(&descriptor_text).map_err(|err| {
         CliError::InvalidInputError(format!("Unable to parse genesis descriptor: {:?}", err))
     })?;
+    let (settings, agents) = descriptor.into_parts();
 
     let mut batches = vec![];
+    let mut manifest = vec![];
+
+    // The settings have to be in place before anything else is bootstrapped,
+    // so their batch goes first
+    if !settings.is_empty() {
+        let private_key: Box<dyn signing::PrivateKey> = match args.value_of("settings_key") {
+            Some(settings_key) => Box::new(key::load_signing_key(Some(settings_key))?),
+            None => generate_private_key(&*context, seed, "settings")?,
+        };
+        let signer = factory.new_signer(&*private_key);
 
-    for agent in agents {
-        let private_key = context.new_random_private_key()?;
+        batches.push(create_settings_batch(
+            &signer,
+            batcher.as_ref(),
+            &settings,
+            &options,
+        )?);
+
+        if let (Some(key_dir), None) = (generated_keys_dir, args.value_of("settings_key")) {
+            store_key(&signer, &*private_key, "settings", key_dir)?;
+        }
+    }
+
+    // With --check-existing, what is already on the network is left out
+    let existing = if args.is_present("check_existing") {
+        Some(ExistingNetwork::from_args(args)?)
+    } else {
+        None
+    };
+    let mut report = GenesisReport::default();
+
+    // The standards created so far, by name, and the accreditations which
+    // refer to them
+    let mut created_standards: BTreeMap<&str, CreatedStandard> = BTreeMap::new();
+    let mut pending_accreditations = vec![];
+    let mut agent_keys = vec![];
+
+    for (index, agent) in agents.iter().enumerate() {
+        let private_key = generate_private_key(&*context, seed, &format!("agent-{}", index))?;
         let signer = factory.new_signer(&*private_key);
+        let public_key = signer.get_public_key()?.as_hex();
+
+        let agent_exists = match &existing {
+            Some(existing) => existing.client.agent_exists(&public_key)?,
+            None => false,
+        };
+        let agent_dependencies = if agent_exists {
+            report.skipped.push(format!("agent {}", agent.email));
+            vec![]
+        } else {
+            let create_time = current_epoch_time();
+            let payload = create_agent_payload(&agent.email, create_time);
+
+            let header_input = create_agent_transaction_addresses(&public_key);
+            let header_output = header_input.clone();
+            let txn = create_transaction(
+                &payload,
+                &signer,
+                batcher.as_ref(),
+                header_input,
+                header_output,
+                vec![],
+                &options,
+            )?;
+            // The agent's organization can only be created once the agent exists
+            let agent_dependencies = chain_dependencies(Some(&txn));
+            let batch = create_batch(txn, &signer, batcher.as_ref())?;
+            batches.push(batch);
+            report.created.push(format!("agent {}", agent.email));
+            agent_dependencies
+        };
+
+        let mut manifest_entry = GenesisManifestEntry {
+            email: agent.email.clone(),
+            public_key,
+            organization_id: None,
+            organization_name: None,
+        };
 
-        let create_time = current_epoch_time();
-        let payload = create_agent_payload(&agent.email, create_time);
+        if let Some(org) = &agent.organization {
+            let existing_org_id = existing
+                .as_ref()
+                .and_then(|existing| existing.organization_id(org));
+            let (org_id, org_dependencies) = match existing_org_id {
+                // The organization could only have been created by the agent,
+                // so the agent's key was not generated with the --seed which
+                // created the network
+                Some(org_id) if !agent_exists => {
+                    return Err(CliError::UserError(format!(
+                        "Organization {} ({}) already exists, but its agent {} does not. Run with the --seed the network was created with",
+                        org.name(),
+                        org_id,
+                        agent.email
+                    )));
+                }
+                Some(org_id) => {
+                    report
+                        .skipped
+                        .push(format!("organization {} ({})", org.name(), org_id));
+                    (org_id, vec![])
+                }
+                None => {
+                    let org_id = Uuid::new_v4().to_string();
+                    let batch = create_org_batch(
+                        &signer,
+                        batcher.as_ref(),
+                        &org_id,
+                        org,
+                        agent.authorize_as_admin,
+                        agent_dependencies,
+                        &options,
+                    )?;
+                    // The organization's standards and accreditations can only
+                    // be created once it exists
+                    let org_dependencies = chain_dependencies(batch.get_transactions().first());
+                    batches.push(batch);
+                    report
+                        .created
+                        .push(format!("organization {} ({})", org.name(), org_id));
+                    (org_id, org_dependencies)
+                }
+            };
 
-        let header_input = create_agent_transaction_addresses(&signer.get_public_key()?.as_hex());
-        let header_output = header_input.clone();
-        let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-        let batch = create_batch(txn, &signer)?;
-        batches.push(batch);
+            match org {
+                GenesisOrganization::StandardsBody { standards, .. } => {
+                    for standard in standards {
+                        let standard_id = make_standard_id(&standard.name);
+                        let standard_exists = match &existing {
+                            Some(existing) => existing.client.standard_exists(&standard_id)?,
+                            None => false,
+                        };
+                        let dependencies = if standard_exists {
+                            report.skipped.push(format!("standard {}", standard.name));
+                            vec![]
+                        } else {
+                            let batch = create_standard_batch(
+                                &signer,
+                                batcher.as_ref(),
+                                &org_id,
+                                standard,
+                                org_dependencies.clone(),
+                                &options,
+                            )?;
+                            let dependencies = chain_dependencies(batch.get_transactions().first());
+                            batches.push(batch);
+                            report.created.push(format!("standard {}", standard.name));
+                            dependencies
+                        };
+                        created_standards.insert(
+                            &standard.name,
+                            CreatedStandard {
+                                standard_id,
+                                standards_body_id: org_id.clone(),
+                                agent_index: index,
+                                dependencies,
+                            },
+                        );
+                    }
+                }
+                GenesisOrganization::CertifyingBody { accreditations, .. } => {
+                    for accreditation in accreditations {
+                        pending_accreditations.push((
+                            org_id.clone(),
+                            org_dependencies.clone(),
+                            accreditation,
+                        ));
+                    }
+                }
+                GenesisOrganization::Factory { .. } => (),
+            }
 
-        if let Some(org) = agent.organization {
-            let mut org_batches = create_org_batches(&signer, &org)?;
-            batches.append(&mut org_batches);
+            manifest_entry.organization_id = Some(org_id);
+            manifest_entry.organization_name = Some(org.name().to_string());
         }
 
         if let Some(key_dir) = generated_keys_dir {
             store_key(&signer, &*private_key, &agent.email, key_dir)?;
         }
+
+        manifest.push(manifest_entry);
+        agent_keys.push(private_key);
+    }
+
+    // Accreditations go after every agent's batches, so that each one follows
+    // the standard it refers to, whatever order the agents are described in
+    for (certifying_body_id, mut dependencies, accreditation) in pending_accreditations {
+        let standard = created_standards
+            .get(accreditation.standard.as_str())
+            .ok_or_else(|| {
+                CliError::InvalidInputError(format!(
+                    "Certifying body {} is accredited for standard {}, which no standards \
+                     body in the genesis descriptor creates",
+                    certifying_body_id, accreditation.standard
+                ))
+            })?;
+        let description = format!(
+            "accreditation of {} for standard {}",
+            certifying_body_id, accreditation.standard
+        );
+        if existing.as_ref().map_or(false, |existing| {
+            existing.accredited(&certifying_body_id, &standard.standard_id)
+        }) {
+            report.skipped.push(description);
+            continue;
+        }
+        dependencies.extend(standard.dependencies.iter().cloned());
+
+        let signer = factory.new_signer(&*agent_keys[standard.agent_index]);
+        batches.push(create_accreditation_batch(
+            &signer,
+            batcher.as_ref(),
+            &certifying_body_id,
+            standard,
+            accreditation,
+            dependencies,
+            &options,
+        )?);
+        report.created.push(description);
+    }
+
+    // An empty or mis-structured descriptor would otherwise produce a useless
+    // output file, unless everything in it is already on the network
+    if batches.is_empty() {
+        if existing.is_some() {
+            return Ok(CommandOutcome::completed(report.describe()));
+        }
+        return Err(CliError::UserError(String::from(
+            "genesis descriptor produced no transactions",
+        )));
+    }
+
+    if let Some(manifest_file) = manifest_file {
+        write_manifest(&manifest, manifest_file, args.is_present("pretty"))?;
+    }
+
+    // Only the batches generated now are submitted, not any appended to
+    let new_batch_count = batches.len();
+
+    if args.is_present("append") && Path::new(output_file).exists() {
+        let mut existing_batches = read_batch_list(output_file, batch_format)?
+            .take_batches()
+            .into_vec();
+        existing_batches.append(&mut batches);
+        batches = existing_batches;
     }
 
     let mut batch_list = BatchList::new();
     batch_list.set_batches(protobuf::RepeatedField::from_vec(batches));
 
-    if !args.is_present("dry_run") {
-        let mut out = File::create(&Path::new(output_file))?;
-        batch_list.write_to_writer(&mut out)?;
+    let output_files = if args.is_present("dry_run") {
+        vec![]
+    } else {
+        write_batch_files(
+            &batch_list,
+            output_file,
+            batch_format,
+            max_batches_per_file,
+            args.is_present("batch_manifest"),
+        )?
+    };
+
+    let mut message = if existing.is_some() {
+        report.describe()
+    } else {
+        String::new()
+    };
+    if output_files.len() > 1 {
+        message = format!(
+            "Split the batches across {} files: {}\n{}",
+            output_files.len(),
+            output_files.join(", "),
+            message
+        )
+        .trim_end()
+        .to_string();
     }
 
-    Ok(())
+    if args.is_present("submit") && !args.is_present("dry_run") {
+        let batches = batch_list.get_batches();
+        let new_batches = &batches[batches.len() - new_batch_count..];
+        prompt::confirm(
+            &format!(
+                "This will submit {} genesis batches to the network.",
+                new_batches.len()
+            ),
+            args.is_present("assume_yes"),
+        )?;
+
+        let client = submit::ApiClient::from_args(args)?;
+        let batch_status = submit_batches(&client, new_batches)?;
+        let mut outcome = CommandOutcome::submitted(
+            None,
+            &batch_status,
+            format!(
+                "Submitted {} genesis batches\n{}",
+                new_batches.len(),
+                message
+            )
+            .trim_end()
+            .to_string(),
+        );
+        outcome
+            .details
+            .insert(String::from("batches"), new_batches.len().to_string());
+        return Ok(outcome);
+    }
+
+    Ok(CommandOutcome::completed(message))
+}
+
+/// Writes the batch list to the output file, returning the files written
+///
+/// If `max_batches_per_file` is given and the batch list holds more batches,
+/// they are split, in order, across numbered files next to the output file
+/// instead, so that each file can be injected on its own.
+///
+/// # Errors
+///
+/// If a file cannot be written, a `CliError::IoError` is returned.
+fn write_batch_files(
+    batch_list: &BatchList,
+    output_file: &str,
+    batch_format: BatchFormat,
+    max_batches_per_file: Option<usize>,
+    manifest: bool,
+) -> Result<Vec<String>, CliError> {
+    let batches = batch_list.get_batches();
+    let max_batches = match max_batches_per_file {
+        Some(max_batches) if batches.len() > max_batches => max_batches,
+        _ => {
+            batch_file::write_batch_file(batch_list, output_file, batch_format, manifest)?;
+            return Ok(vec![output_file.to_string()]);
+        }
+    };
+
+    batches
+        .chunks(max_batches)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let path = numbered_file_name(output_file, index + 1);
+            batch_file::write_batch_file(
+                &create_batch_list(chunk.to_vec()),
+                &path,
+                batch_format,
+                manifest,
+            )?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Returns the name of the given numbered part of the output file, with the
+/// number before the extension: `consensource-genesis.001.batch`
+fn numbered_file_name(output_file: &str, number: usize) -> String {
+    let path = Path::new(output_file);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{:03}.{}", stem, number, extension.to_string_lossy()),
+        None => format!("{}.{:03}", stem, number),
+    };
+    path.with_file_name(file_name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Submits the batches one at a time, in order, and returns the status of the
+/// last one
+///
+/// The client only waits on the first batch of a batch list, so each batch is
+/// submitted in a list of its own. That way every batch is committed before
+/// the next one, which may depend on it, is submitted, and a rejected batch
+/// stops the submission.
+///
+/// # Errors
+///
+/// If a batch is rejected, a `CliError::InvalidTransactionError` is returned.
+fn submit_batches(
+    client: &submit::ApiClient,
+    batches: &[Batch],
+) -> Result<submit::Status, CliError> {
+    let mut batch_status = None;
+    for (index, batch) in batches.iter().enumerate() {
+        eprintln!(
+            "Submitting genesis batch {} of {}",
+            index + 1,
+            batches.len()
+        );
+        batch_status = Some(client.submit_and_wait(&create_batch_list_from_one(batch.clone()))?);
+    }
+    batch_status.ok_or_else(|| CliError::UserError(String::from("No genesis batches to submit")))
+}
+
+/// Replaces each `${VAR}` placeholder in a genesis descriptor with the value
+/// of the variable, as returned by `lookup`
+///
+/// Comment lines are left as they are, and `$${VAR}` is kept as a literal
+/// `${VAR}`.
+///
+/// # Errors
+///
+/// If a placeholder is not closed, or refers to a variable which is not set, a
+/// `CliError::InvalidInputError` is returned.
+fn expand_env_vars<F>(text: &str, lookup: F) -> Result<String, CliError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut lines = vec![];
+    for line in text.split('\n') {
+        if line.trim_start().starts_with('#') {
+            lines.push(line.to_string());
+        } else {
+            lines.push(expand_line_env_vars(line, &lookup)?);
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+fn expand_line_env_vars<F>(line: &str, lookup: &F) -> Result<String, CliError>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expanded = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            // An escaped placeholder, kept without its extra '$'
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| {
+            CliError::InvalidInputError(format!(
+                "Unclosed placeholder in genesis descriptor: {}",
+                &rest[start..]
+            ))
+        })?;
+        let name = &rest[start + 2..start + end];
+        let value = lookup(name).ok_or_else(|| {
+            CliError::InvalidInputError(format!(
+                "Genesis descriptor refers to environment variable {}, which is not set",
+                name
+            ))
+        })?;
+        expanded.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
 }
 
-fn create_org_batches<'s>(
-    signer: &'s signing::Signer,
+/// Reads a previously written genesis batch file
+fn read_batch_list(batch_file: &str, format: BatchFormat) -> Result<BatchList, CliError> {
+    let mut file = File::open(&Path::new(batch_file))?;
+    let mut bytes = vec![];
+    file.read_to_end(&mut bytes)?;
+
+    batch_file::decode_batch_list(&bytes, format).map_err(|err| {
+        CliError::InvalidInputError(format!(
+            "Unable to read existing batches from {}: {}",
+            batch_file, err
+        ))
+    })
+}
+
+/// Returns the batch creating the organization, which also authorizes the
+/// signer as its ADMIN if `authorize_as_admin` is set
+fn create_org_batch(
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    org_id: &str,
     org: &GenesisOrganization,
-) -> Result<Vec<Batch>, CliError> {
-    let mut batches = vec![];
-    let org_id = Uuid::new_v4().to_string();
-    let (name, organization_type, contact, address, standards) = match org {
-        GenesisOrganization::StandardsBody {
-            name,
-            contact,
-            standards,
-            ..
-        } => (
-            name,
-            Organization_Type::STANDARDS_BODY,
-            contact,
-            None,
-            Some(standards),
-        ),
-        GenesisOrganization::CertifyingBody { name, contact, .. } => (
-            name,
-            Organization_Type::CERTIFYING_BODY,
-            contact,
-            None,
-            None,
-        ),
+    authorize_as_admin: bool,
+    dependencies: Vec<String>,
+    options: &TransactionOptions,
+) -> Result<Batch, CliError> {
+    let (name, contact, address) = match org {
+        GenesisOrganization::StandardsBody { name, contact, .. }
+        | GenesisOrganization::CertifyingBody { name, contact, .. } => (name, contact, None),
         GenesisOrganization::Factory {
             name,
             contact,
             address,
-        } => (
-            name,
-            Organization_Type::FACTORY,
-            contact,
-            Some(address),
-            None,
-        ),
+        } => (name, contact, Some(address)),
     };
 
     let payload = create_organization_payload(
         &org_id,
         &name,
-        organization_type,
+        org.organization_type(),
         &contact.name,
         &contact.phone_number,
         &contact.language,
-        address.as_ref().map(|a| &*a.street_1),
-        address.as_ref().map(|a| &*a.city.as_str()),
-        address.as_ref().map(|a| &*a.country.as_str()),
-    );
+        address.as_ref().map(|a| a.street_1.as_str()),
+        address.as_ref().and_then(|a| a.street_2.as_deref()),
+        address.as_ref().map(|a| a.city.as_str()),
+        address.as_ref().and_then(|a| a.state_province.as_deref()),
+        address.as_ref().and_then(|a| a.postal_code.as_deref()),
+        address.as_ref().map(|a| a.country.as_str()),
+    )?;
 
     let header_input =
         create_organization_transaction_addresses(&signer.get_public_key()?.as_hex(), &org_id);
     let header_output = header_input.clone();
 
-    let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-    batches.push(create_batch(txn, &signer)?);
-
-    if let Some(standards) = standards {
-        for standard in standards {
-            let payload = create_standard_payload(
-                &standard.name,
-                &standard.version,
-                &standard.description,
-                &standard.link,
-                standard.approval_date,
-            );
-            let (inputs, outputs) = create_standard_transaction_addresses(
-                &signer,
-                payload.get_create_standard().get_standard_id(),
-                &org_id,
-            )?;
-            let txn = create_transaction(&payload, &signer, inputs, outputs)?;
-            batches.push(create_batch(txn, &signer)?);
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher,
+        header_input,
+        header_output,
+        dependencies,
+        options,
+    )?;
+    if authorize_as_admin {
+        let authorization = create_admin_authorization(signer, batcher, &org_id, &txn, options)?;
+        create_batch_with_transactions(vec![txn, authorization], &signer, batcher)
+    } else {
+        create_batch(txn, &signer, batcher)
+    }
+}
+
+/// Returns the batch creating one of a standards body's standards
+fn create_standard_batch(
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    org_id: &str,
+    standard: &GenesisStandard,
+    dependencies: Vec<String>,
+    options: &TransactionOptions,
+) -> Result<Batch, CliError> {
+    let payload = create_standard_payload(
+        &standard.name,
+        &standard.version,
+        &standard.description,
+        &standard.link,
+        standard.approval_date,
+    );
+    let (inputs, outputs) = create_standard_transaction_addresses(
+        &signer,
+        payload.get_create_standard().get_standard_id(),
+        &org_id,
+    )?;
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher,
+        inputs,
+        outputs,
+        dependencies,
+        options,
+    )?;
+    create_batch(txn, &signer, batcher)
+}
+
+/// Returns the transaction authorizing the signer as an ADMIN of the
+/// organization created by `create_org_txn`
+fn create_admin_authorization(
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    org_id: &str,
+    create_org_txn: &Transaction,
+    options: &TransactionOptions,
+) -> Result<Transaction, CliError> {
+    let public_key = signer.get_public_key()?.as_hex();
+    // Role 1 is ADMIN
+    let payload = authorize_agent_payload(&public_key, "1")?;
+
+    // The signer authorizes itself, so its agent address only needs to be
+    // given once
+    let mut inputs = authorize_agent_transaction_addresses_input(&public_key, org_id, &public_key);
+    inputs.sort();
+    inputs.dedup();
+    let outputs = vec![
+        addressing::make_organization_address(org_id),
+        addressing::make_agent_address(&public_key),
+    ];
+
+    create_transaction(
+        &payload,
+        signer,
+        batcher,
+        inputs,
+        outputs,
+        chain_dependencies(Some(create_org_txn)),
+        options,
+    )
+}
+
+/// Returns a batch accrediting a certifying body for a standard, signed by the
+/// agent of the standards body which created the standard
+fn create_accreditation_batch(
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    certifying_body_id: &str,
+    standard: &CreatedStandard,
+    accreditation: &GenesisAccreditation,
+    dependencies: Vec<String>,
+    options: &TransactionOptions,
+) -> Result<Batch, CliError> {
+    let payload = create_accreditation_payload(
+        &standard.standard_id,
+        certifying_body_id,
+        accreditation.valid_from,
+        accreditation.valid_to,
+    );
+    let (inputs, outputs) = create_accreditation_transaction_addresses(
+        signer,
+        &standard.standard_id,
+        certifying_body_id,
+        &standard.standards_body_id,
+    )?;
+
+    let txn = create_transaction(
+        &payload,
+        signer,
+        batcher,
+        inputs,
+        outputs,
+        dependencies,
+        options,
+    )?;
+    create_batch(txn, signer, batcher)
+}
+
+/// Returns a batch proposing each of the given settings
+fn create_settings_batch(
+    signer: &signing::Signer,
+    batcher: Option<&signing::Signer>,
+    settings: &BTreeMap<String, String>,
+    options: &TransactionOptions,
+) -> Result<Batch, CliError> {
+    let settings_options = settings_transaction_options(options);
+
+    let txns = settings
+        .iter()
+        .map(|(setting, value)| {
+            let payload =
+                create_setting_proposal_payload(setting, value, &Uuid::new_v4().to_string())?;
+            let (inputs, outputs) = create_setting_transaction_addresses(setting);
+            create_transaction_from_bytes(
+                payload,
+                signer,
+                batcher,
+                inputs,
+                outputs,
+                vec![],
+                &settings_options,
+            )
+        })
+        .collect::<Result<Vec<_>, CliError>>()?;
+
+    create_batch_with_transactions(txns, signer, batcher)
+}
+
+/// Writes the manifest of generated agents as JSON to the given file, or to
+/// stdout if the file is "-"
+fn write_manifest(
+    manifest: &[GenesisManifestEntry],
+    manifest_file: &str,
+    pretty: bool,
+) -> Result<(), CliError> {
+    let manifest_json = output::to_json(manifest, pretty)?;
+
+    if manifest_file == "-" {
+        println!("{}", manifest_json);
+    } else {
+        File::create(&Path::new(manifest_file))?.write_all(manifest_json.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Checks that a key seed is a non-empty hex string
+fn parse_seed(seed: &str) -> Result<&str, CliError> {
+    if seed.is_empty() || !seed.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(CliError::InvalidInputError(format!(
+            "Invalid seed: {}. Please provide a hex string",
+            seed
+        )));
+    }
+    Ok(seed)
+}
+
+/// Returns a new private key
+///
+/// Without a seed the key is random. With a seed, the key is the sha256 hash
+/// of the seed and the key's name, so the same seed always yields the same
+/// keys. Seeded keys are trivially recoverable by anyone who knows the seed
+/// and must only be used on test networks.
+fn generate_private_key(
+    context: &dyn signing::Context,
+    seed: Option<&str>,
+    name: &str,
+) -> Result<Box<dyn signing::PrivateKey>, CliError> {
+    match seed {
+        Some(seed) => {
+            let mut sha = Sha256::new();
+            sha.input_str(&format!("{}:{}", seed.to_lowercase(), name));
+            Ok(Box::new(Secp256k1PrivateKey::from_hex(&sha.result_str())?))
         }
+        None => Ok(context.new_random_private_key()?),
     }
+}
+
+/// Creates the directory the generated keys are written to, if it is missing,
+/// and checks that it is writable
+///
+/// This is done before any keys are generated, so that a bad directory doesn't
+/// lose the keys of a whole genesis run.
+///
+/// # Errors
+///
+/// If the directory cannot be created or written to, a `CliError::UserError`
+/// is returned.
+fn prepare_keys_directory(key_dir: &str) -> Result<(), CliError> {
+    fs::create_dir_all(key_dir).map_err(|err| {
+        CliError::UserError(format!(
+            "Unable to create keys directory {}: {}",
+            key_dir, err
+        ))
+    })?;
 
-    Ok(batches)
+    let probe_file = Path::new(key_dir).join(".csrc-write-check");
+    File::create(&probe_file)
+        .and_then(|_| fs::remove_file(&probe_file))
+        .map_err(|err| {
+            CliError::UserError(format!(
+                "Keys directory {} is not writable: {}",
+                key_dir, err
+            ))
+        })
 }
 
 fn store_key(
@@ -267,3 +1101,144 @@ impl<'de> serde::de::Visitor<'de> for DateVisitor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::proto::organization::Organization_Authorization_Role;
+    use common::proto::payload::CertificateRegistryPayload;
+    use sawtooth_sdk::messages::transaction::TransactionHeader;
+    use std::sync::Arc;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "PHONE" => Some(String::from("+15555555555")),
+            "HOST" => Some(String::from("example.com")),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_env_vars_test() {
+        let expanded = expand_env_vars(
+            "phone_number: ${PHONE}\nlink: https://${HOST}/standard?cost=$5",
+            lookup,
+        )
+        .expect("Failed to expand variables");
+        assert_eq!(
+            expanded,
+            "phone_number: +15555555555\nlink: https://example.com/standard?cost=$5"
+        );
+
+        assert!(expand_env_vars("phone_number: ${MISSING}", lookup).is_err());
+        assert!(expand_env_vars("phone_number: ${PHONE", lookup).is_err());
+
+        // Comments and escaped placeholders are left alone
+        assert_eq!(
+            expand_env_vars("  # Set ${MISSING}\nname: $${PHONE}", lookup).unwrap(),
+            "  # Set ${MISSING}\nname: ${PHONE}"
+        );
+    }
+
+    #[test]
+    fn example_genesis_descriptor_test() {
+        let text = expand_env_vars(include_str!("../../example-genesis.yaml"), |_| None)
+            .expect("Failed to expand the example descriptor");
+        serde_yaml::from_str::<GenesisDescriptor>(&text)
+            .expect("Failed to parse the example descriptor");
+    }
+
+    #[test]
+    fn numbered_file_name_test() {
+        assert_eq!(
+            numbered_file_name("consensource-genesis.batch", 1),
+            "consensource-genesis.001.batch"
+        );
+        assert_eq!(numbered_file_name("out/genesis", 12), "out/genesis.012");
+    }
+
+    #[test]
+    fn submit_batches_test() {
+        let batches = submit::mock::batch_list().take_batches().into_vec();
+        let batches = [batches[0].clone(), batches[0].clone()];
+
+        let submitter = Arc::new(submit::mock::MockSubmitter::new(&["COMMITTED"]));
+        let client = submit::mock::client_with(submitter.clone());
+        let batch_status = submit_batches(&client, &batches).expect("Failed to submit batches");
+        assert_eq!(batch_status.status, "COMMITTED");
+        assert_eq!(submitter.status_checks(), 2);
+
+        // A rejected batch stops the submission
+        let submitter = Arc::new(submit::mock::MockSubmitter::new(&["INVALID"]));
+        let client = submit::mock::client_with(submitter.clone());
+        assert!(submit_batches(&client, &batches).is_err());
+        assert_eq!(submitter.status_checks(), 1);
+    }
+
+    #[test]
+    fn existing_network_test() {
+        let existing = ExistingNetwork {
+            client: submit::mock::client(&["COMMITTED"]),
+            organizations: vec![
+                serde_json::json!({"id": "sb-1", "name": "Certifier", "organization_type": 2}),
+                serde_json::json!({
+                    "id": "cb-1",
+                    "name": "Certifier",
+                    "organization_type": 1,
+                    "accreditations": [{"standard_id": "standard-1"}]
+                }),
+            ],
+        };
+        let org: GenesisOrganization = serde_yaml::from_str(
+            "type: CertifyingBody\n\
+             name: Certifier\n\
+             contact: {name: Ann, phone_number: '+15555555555', language: en}\n",
+        )
+        .unwrap();
+
+        assert_eq!(existing.organization_id(&org), Some(String::from("cb-1")));
+        assert!(existing.accredited("cb-1", "standard-1"));
+        assert!(!existing.accredited("cb-1", "standard-2"));
+        assert!(!existing.accredited("sb-1", "standard-1"));
+    }
+
+    #[test]
+    fn create_org_batch_admin_test() {
+        let context = signing::create_context(key::SECP_256K1).unwrap();
+        let private_key = context.new_random_private_key().unwrap();
+        let signer = signing::CryptoFactory::new(&*context).new_signer(&*private_key);
+        let org: GenesisOrganization = serde_yaml::from_str(
+            "type: CertifyingBody\n\
+             name: Certifier\n\
+             contact: {name: Ann, phone_number: '+15555555555', language: en}\n",
+        )
+        .unwrap();
+        let options = TransactionOptions::default();
+
+        let batch = create_org_batch(&signer, None, "cb-1", &org, true, vec![], &options)
+            .expect("Failed to create batch");
+        let txns = batch.get_transactions();
+        assert_eq!(txns.len(), 2);
+
+        let header: TransactionHeader = protobuf::parse_from_bytes(txns[1].get_header()).unwrap();
+        assert_eq!(
+            header.get_dependencies(),
+            &[txns[0].get_header_signature().to_string()]
+        );
+        let payload: CertificateRegistryPayload =
+            protobuf::parse_from_bytes(txns[1].get_payload()).unwrap();
+        let authorization = payload.get_authorize_agent();
+        assert_eq!(
+            authorization.get_public_key(),
+            signer.get_public_key().unwrap().as_hex()
+        );
+        assert_eq!(
+            authorization.get_role(),
+            Organization_Authorization_Role::ADMIN
+        );
+
+        let batch = create_org_batch(&signer, None, "cb-1", &org, false, vec![], &options)
+            .expect("Failed to create batch");
+        assert_eq!(batch.get_transactions().len(), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::commands::CommandOutcome;
+use crate::error::CliError;
+use crate::submit;
+use crate::{APP_NAME, VERSION};
+
+use clap::ArgMatches;
+use common::addressing;
+
+pub fn run(_args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let info = vec![
+        ("name", APP_NAME.to_string()),
+        ("version", VERSION.to_string()),
+        ("family_name", addressing::FAMILY_NAMESPACE.to_string()),
+        ("family_version", addressing::FAMILY_VERSION.to_string()),
+        (
+            "namespace_prefix",
+            addressing::get_family_namespace_prefix(),
+        ),
+        ("default_url", submit::DEFAULT_URL.to_string()),
+    ];
+
+    let message = info
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut outcome = CommandOutcome::completed(message);
+    outcome.details = info
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    Ok(outcome)
+}
@@ -0,0 +1,39 @@
+use crate::commands::CommandOutcome;
+use crate::error::CliError;
+use crate::key;
+use crate::transaction::TransactionOptions;
+
+use clap::ArgMatches;
+use common::addressing;
+
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    match args.subcommand() {
+        ("show", Some(args)) => run_show_command(args),
+        _ => Err(CliError::InvalidInputError(String::from(
+            "Invalid subcommand. Pass --help for usage",
+        ))),
+    }
+}
+
+/// Shows the public key of a signing key in the compressed form which
+/// identifies agents, with the address of the matching agent
+fn run_show_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let signing_context =
+        key::SigningContext::new(args.value_of("key"), &TransactionOptions::from_args(args)?)?;
+    let public_key =
+        key::normalize_public_key(&signing_context.signer().get_public_key()?.as_hex())?;
+    let agent_address = addressing::make_agent_address(&public_key);
+
+    let mut outcome = CommandOutcome::completed(format!(
+        "public_key: {}\nagent_address: {}",
+        public_key, agent_address
+    ));
+    outcome.id = Some(public_key.clone());
+    outcome
+        .details
+        .insert(String::from("public_key"), public_key);
+    outcome
+        .details
+        .insert(String::from("agent_address"), agent_address);
+    Ok(outcome)
+}
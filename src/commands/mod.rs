@@ -1,7 +1,977 @@
 pub mod accreditation;
+pub mod address;
 pub mod agent;
 pub mod assertion;
+pub mod batch;
 pub mod certificate;
 pub mod genesis;
+pub mod info;
+pub mod key;
 pub mod organization;
+pub mod ping;
 pub mod standard;
+pub mod status;
+
+use crate::error::CliError;
+use crate::render::{Table, TableRow};
+use crate::submit::{BlockInclusion, Status, SUBMITTED_STATUS, WRITTEN_STATUS};
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use clap::{ArgMatches, Values};
+use common::proto::certificate::Certificate_CertificateData;
+use common::proto::payload::CertificateRegistryPayload;
+use protobuf::Message;
+use serde::de::DeserializeOwned;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// The result of running a command, reported by `main` in the output format
+/// selected by `--format`
+#[derive(Debug, Serialize)]
+pub struct CommandOutcome {
+    /// The id of the object the command created or modified, if any
+    pub id: Option<String>,
+    /// The id of the batch the command submitted, if any
+    pub batch_id: Option<String>,
+    /// The status of the submitted batch, or "OK" for commands which do not
+    /// submit a batch
+    pub status: String,
+    /// The ids of the transactions the command submitted, for finding them in
+    /// the transaction processor's logs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub transaction_ids: Vec<String>,
+    /// The block the batch was included in, if `--wait-for-block` was given
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block: Option<BlockInclusion>,
+    /// How many seconds the batch took to be committed after it was
+    /// submitted, if the command waited for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_seconds: Option<f64>,
+    /// Any further results of the command, by name
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub details: BTreeMap<String, String>,
+    /// The objects a read command listed, in their views' stable schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listing: Option<Listing>,
+    /// The summary printed for the human output format
+    #[serde(skip)]
+    pub message: String,
+}
+
+impl CommandOutcome {
+    /// Returns the outcome of a command which submitted a batch
+    ///
+    /// If the batch was not waited on, the message is replaced with one
+    /// pointing at the `status` command, since the command's effect is not
+    /// known yet. Likewise if the batch was only written to a file.
+    pub fn submitted(id: Option<String>, batch_status: &Status, message: String) -> Self {
+        let message = match batch_status.status.as_str() {
+            SUBMITTED_STATUS => format!(
+                "Batch {} has been submitted. Run `csrc status {}` to check whether it was committed",
+                batch_status.id, batch_status.id
+            ),
+            WRITTEN_STATUS => format!(
+                "Batch {} has been written to the --output-batch file without being submitted",
+                batch_status.id
+            ),
+            _ => match &batch_status.block {
+                Some(block) => format!(
+                    "{}\nBatch {} is in block {} ({})",
+                    message, batch_status.id, block.block_num, block.block_id
+                ),
+                None => message,
+            },
+        };
+
+        CommandOutcome {
+            id,
+            batch_id: Some(batch_status.id.clone()),
+            status: batch_status.status.clone(),
+            transaction_ids: batch_status.transaction_ids.clone(),
+            block: batch_status.block.clone(),
+            commit_seconds: batch_status.elapsed.map(|elapsed| elapsed.as_secs_f64()),
+            details: BTreeMap::new(),
+            listing: None,
+            message,
+        }
+    }
+
+    /// Returns the outcome of a command which completed without submitting a
+    /// batch
+    pub fn completed(message: String) -> Self {
+        CommandOutcome {
+            id: None,
+            batch_id: None,
+            status: String::from("OK"),
+            transaction_ids: vec![],
+            block: None,
+            commit_seconds: None,
+            details: BTreeMap::new(),
+            listing: None,
+            message,
+        }
+    }
+}
+
+/// The version of the schema of the views in a `Listing`
+///
+/// It is bumped whenever a field of a view is renamed or removed, or changes
+/// meaning. Adding a field does not change it.
+pub const VIEW_SCHEMA_VERSION: u32 = 1;
+
+/// The objects listed by a read command, reported by `--format json`
+///
+/// Each object is given as a view, whose fields are documented on its type
+/// and kept stable across changes to the REST API's responses.
+#[derive(Debug, Serialize)]
+pub struct Listing {
+    /// See `VIEW_SCHEMA_VERSION`
+    pub schema_version: u32,
+    /// The kind of object listed, e.g. "certificate"
+    pub kind: &'static str,
+    pub items: Vec<serde_json::Value>,
+    /// The views as rows of a table, for `--format table`
+    #[serde(skip)]
+    pub table: Table,
+}
+
+impl Listing {
+    /// Returns a listing of the given views
+    ///
+    /// # Errors
+    ///
+    /// If a view cannot be serialized, a `CliError::UserError` is returned.
+    pub fn new<T: serde::Serialize + TableRow>(
+        kind: &'static str,
+        views: &[T],
+    ) -> Result<Self, CliError> {
+        let items = views
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()
+            .map_err(|err| {
+                CliError::UserError(format!("Unable to serialize {} list: {}", kind, err))
+            })?;
+        Ok(Listing {
+            schema_version: VIEW_SCHEMA_VERSION,
+            kind,
+            items,
+            table: Table::from_rows(views),
+        })
+    }
+}
+
+/// Returns the contents of a batch command's input file, read from stdin if
+/// the path is "-"
+pub fn read_input_file(filepath: &str) -> Result<String, CliError> {
+    let mut data = String::new();
+    if filepath == "-" {
+        io::stdin().read_to_string(&mut data)?;
+    } else {
+        File::open(filepath)?.read_to_string(&mut data)?;
+    }
+    Ok(data)
+}
+
+/// Opens a batch command's input file for reading line by line, or stdin if
+/// the path is "-"
+pub fn open_input_file(filepath: &str) -> Result<Box<dyn BufRead>, CliError> {
+    if filepath == "-" {
+        Ok(Box::new(BufReader::new(io::stdin())))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(filepath)?)))
+    }
+}
+
+/// The number of entries submitted per batch when no `--batch-size` is given
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Returns the number of batch file entries to submit per batch, as given by
+/// `--batch-size`
+///
+/// # Errors
+///
+/// If the batch size is not a positive number, a
+/// `CliError::InvalidInputError` is returned.
+pub fn batch_size_from_args(args: &ArgMatches) -> Result<usize, CliError> {
+    match args.value_of("batch_size") {
+        None => Ok(DEFAULT_BATCH_SIZE),
+        Some(batch_size) => match batch_size.parse::<usize>() {
+            Ok(batch_size) if batch_size > 0 => Ok(batch_size),
+            _ => Err(CliError::InvalidInputError(format!(
+                "Invalid value for --batch-size: {}. Please provide a positive number",
+                batch_size
+            ))),
+        },
+    }
+}
+
+/// Reads and parses the file given by a batch command's `filepath` argument
+///
+/// The file is parsed as YAML or JSON as selected by `--input`. Otherwise, files
+/// ending in .yaml or .yml are parsed as YAML, files ending in .json as JSON,
+/// and any other file (or stdin) as JSON unless its contents don't look like
+/// it.
+///
+/// A file holding nothing but whitespace has no entries, and is returned as
+/// the empty default, so that the command reports it has nothing to submit as
+/// it does when every entry is filtered out.
+///
+/// # Errors
+///
+/// If the file cannot be read, a `CliError::IoError` is returned. If it cannot
+/// be parsed, a `CliError::InvalidInputError` is returned.
+pub fn read_batch_file<T: DeserializeOwned + Default>(args: &ArgMatches) -> Result<T, CliError> {
+    let filepath = require_arg(args, "filepath")?;
+    let data = read_input_file(filepath)?;
+    if data.trim().is_empty() {
+        return Ok(T::default());
+    }
+
+    let is_yaml = match args.value_of("input") {
+        Some(input) => input == "yaml",
+        None => is_yaml_file(filepath, &data),
+    };
+
+    let parsed = if is_yaml {
+        serde_yaml::from_str(&data).map_err(|err| err.to_string())
+    } else {
+        serde_json::from_str(&data).map_err(|err| err.to_string())
+    };
+    parsed.map_err(|err| {
+        CliError::InvalidInputError(format!("Unable to parse {}: {}", filepath, err))
+    })
+}
+
+/// Returns whether a batch file should be parsed as YAML rather than JSON
+fn is_yaml_file(filepath: &str, data: &str) -> bool {
+    match Path::new(filepath).extension().and_then(|ext| ext.to_str()) {
+        Some("yaml") | Some("yml") => true,
+        Some("json") => false,
+        // JSON batch files are always objects or arrays
+        _ => !matches!(
+            data.trim_start().chars().next(),
+            Some('{') | Some('[') | None
+        ),
+    }
+}
+
+/// Parses `field:value` pairs into certificate data
+///
+/// # Errors
+///
+/// If an entry is not of the form `field:value`, or a field is given more than
+/// once, a `CliError::InvalidInputError` is returned.
+pub fn parse_cert_data<'a, I>(values: I) -> Result<Vec<Certificate_CertificateData>, CliError>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let mut fields = BTreeSet::new();
+    let mut duplicates = BTreeSet::new();
+    let mut cert_data = vec![];
+    for value in values {
+        let cd: Vec<&str> = value.split(':').collect();
+        match (cd.get(0), cd.get(1)) {
+            (Some(field), Some(data)) => {
+                if !fields.insert(field.to_string()) {
+                    duplicates.insert(field.to_string());
+                }
+                let mut ccd = Certificate_CertificateData::new();
+                ccd.set_field(field.to_string());
+                ccd.set_data(data.to_string());
+                cert_data.push(ccd);
+            }
+            _ => {
+                return Err(CliError::InvalidInputError(String::from(
+                    "Invalid format for cert_data",
+                )))
+            }
+        }
+    }
+
+    if !duplicates.is_empty() {
+        return Err(CliError::InvalidInputError(format!(
+            "Duplicate cert_data fields: {}",
+            duplicates.into_iter().collect::<Vec<_>>().join(", ")
+        )));
+    }
+
+    Ok(cert_data)
+}
+
+/// How many batch file entries are processed between progress reports when no
+/// `--chunk-report` is given
+const DEFAULT_CHUNK_REPORT: usize = 500;
+
+/// Counts the entries of a batch file as their transactions are created,
+/// reporting progress on stderr every `--chunk-report` entries unless
+/// `--quiet` is given
+pub struct ProgressCounter {
+    processed: usize,
+    total: Option<usize>,
+    report_every: Option<usize>,
+}
+
+impl ProgressCounter {
+    /// Returns a counter for a batch file with `total` entries, if the number
+    /// of entries is known up front
+    ///
+    /// # Errors
+    ///
+    /// If `--chunk-report` is not a positive number, a
+    /// `CliError::InvalidInputError` is returned.
+    pub fn from_args(args: &ArgMatches, total: Option<usize>) -> Result<ProgressCounter, CliError> {
+        let report_every = if args.is_present("quiet") {
+            None
+        } else {
+            match args.value_of("chunk_report") {
+                None => Some(DEFAULT_CHUNK_REPORT),
+                Some(chunk_report) => match chunk_report.parse::<usize>() {
+                    Ok(every) if every > 0 => Some(every),
+                    _ => {
+                        return Err(CliError::InvalidInputError(format!(
+                        "Invalid value for --chunk-report: {}. Please provide a positive number",
+                        chunk_report
+                    )))
+                    }
+                },
+            }
+        };
+
+        Ok(ProgressCounter {
+            processed: 0,
+            total,
+            report_every,
+        })
+    }
+
+    /// Counts one more entry as processed
+    pub fn increment(&mut self) {
+        if let Some(report) = self.advance() {
+            eprintln!("{}", report);
+        }
+    }
+
+    fn advance(&mut self) -> Option<String> {
+        self.processed += 1;
+        match (self.report_every, self.total) {
+            (Some(every), Some(total)) if self.processed % every == 0 => {
+                Some(format!("Processed {}/{} entries", self.processed, total))
+            }
+            (Some(every), None) if self.processed % every == 0 => {
+                Some(format!("Processed {} entries", self.processed))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the outcome of a batch command which found nothing to submit,
+/// because its file has no entries or `--retry-failed` filtered them all out
+///
+/// No batch is submitted, since an empty one would only be rejected.
+pub fn nothing_to_submit(filepath: &str, kind: &str) -> CommandOutcome {
+    CommandOutcome::completed(format!(
+        "{} contains no {} to submit, so no batch was submitted",
+        filepath, kind
+    ))
+}
+
+/// Selects the entries of a batch file to submit
+///
+/// Every entry is selected, unless `--retry-failed` gives the `--error-log`
+/// of a previous run, in which case only the entries which failed in that run
+/// are.
+pub struct EntryFilter {
+    failed: Option<BTreeSet<String>>,
+}
+
+impl EntryFilter {
+    /// Returns the filter selected by `--retry-failed`
+    ///
+    /// # Errors
+    ///
+    /// If the error log cannot be read, a `CliError::IoError` is returned. If
+    /// it is malformed, or records no batch file entries, a
+    /// `CliError::InvalidInputError` is returned.
+    pub fn from_args(args: &ArgMatches) -> Result<EntryFilter, CliError> {
+        let error_log = match args.value_of("retry_failed") {
+            Some(error_log) => error_log,
+            None => return Ok(EntryFilter { failed: None }),
+        };
+
+        let failed = parse_failed_entries(&read_input_file(error_log)?).map_err(|err| {
+            CliError::InvalidInputError(format!(
+                "Unable to read failed entries from {}: {}",
+                error_log, err
+            ))
+        })?;
+        if failed.is_empty() {
+            return Err(CliError::InvalidInputError(format!(
+                "{} does not record any failed entries",
+                error_log
+            )));
+        }
+
+        Ok(EntryFilter {
+            failed: Some(failed),
+        })
+    }
+
+    /// Returns whether the entry with the given key is to be submitted
+    pub fn includes(&self, key: &str) -> bool {
+        self.failed
+            .as_ref()
+            .map_or(true, |failed| failed.contains(key))
+    }
+}
+
+/// Returns the keys of the batch file entries recorded in an error log
+///
+/// Records of invalid transactions which were not created for a batch file
+/// entry have a null `entry`, and are skipped with a warning, since there is
+/// nothing in the batch file to retry for them.
+fn parse_failed_entries(error_log: &str) -> Result<BTreeSet<String>, String> {
+    let mut failed = BTreeSet::new();
+    for line in error_log.lines().filter(|line| !line.trim().is_empty()) {
+        let record: serde_json::Value =
+            serde_json::from_str(line).map_err(|err| err.to_string())?;
+        match record.get("entry") {
+            Some(serde_json::Value::String(entry)) => {
+                failed.insert(entry.clone());
+            }
+            Some(serde_json::Value::Null) => eprintln!(
+                "Skipping transaction {} of the error log, which was not created for a batch file entry",
+                record["transaction_id"].as_str().unwrap_or_default()
+            ),
+            _ => return Err(format!("Record has no entry: {}", line)),
+        }
+    }
+    Ok(failed)
+}
+
+/// Returns the value of an argument the command requires
+///
+/// clap rejects a command line missing an argument declared `+required`, but
+/// not one which is only required by some of the command's modes, or whose
+/// declaration has drifted from the command, so required arguments are read
+/// through this rather than unwrapped.
+///
+/// # Errors
+///
+/// If the argument is not given, a `CliError::InvalidInputError` is returned.
+pub fn require_arg<'a>(args: &'a ArgMatches, name: &str) -> Result<&'a str, CliError> {
+    args.value_of(name).ok_or_else(|| missing_arg_error(name))
+}
+
+/// Returns the values of an argument the command requires, like `require_arg`
+///
+/// # Errors
+///
+/// If the argument is not given, a `CliError::InvalidInputError` is returned.
+pub fn require_values<'a>(args: &'a ArgMatches, name: &str) -> Result<Values<'a>, CliError> {
+    args.values_of(name).ok_or_else(|| missing_arg_error(name))
+}
+
+fn missing_arg_error(name: &str) -> CliError {
+    CliError::InvalidInputError(format!("missing required argument: {}", name))
+}
+
+/// Returns a string field of the batch file entry with the given key
+///
+/// # Errors
+///
+/// If the entry has no such field, or it is not a string, a
+/// `CliError::InvalidInputError` is returned.
+pub fn batch_entry_field<'a>(
+    key: &str,
+    entry: &'a serde_json::Value,
+    field: &str,
+) -> Result<&'a str, CliError> {
+    entry
+        .get(field)
+        .and_then(serde_json::Value::as_str)
+        .ok_or_else(|| {
+            CliError::InvalidInputError(format!("Batch file entry {} has no {} string", key, field))
+        })
+}
+
+/// Returns an optional string field of the batch file entry with the given
+/// key, which is `None` if the field is missing or null
+///
+/// # Errors
+///
+/// If the field is given but is not a string, such as a phone number which
+/// YAML read as a number, a `CliError::InvalidInputError` is returned rather
+/// than the field being ignored.
+pub fn optional_batch_entry_field<'a>(
+    key: &str,
+    entry: &'a serde_json::Value,
+    field: &str,
+) -> Result<Option<&'a str>, CliError> {
+    match entry.get(field) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(serde_json::Value::String(value)) => Ok(Some(value)),
+        Some(value) => Err(CliError::InvalidInputError(format!(
+            "Batch file entry {} has a {} which is not a string: {}. Quote it in YAML files",
+            key, field, value
+        ))),
+    }
+}
+
+/// Returns a timestamp of a batch file entry, given either as a number of
+/// seconds since the Unix epoch or in any form `parse_timestamp` accepts
+///
+/// YAML reads an unquoted timestamp such as `1546300800` as a number, so both
+/// are accepted whatever the format of the file.
+///
+/// # Errors
+///
+/// If the timestamp is missing, negative or malformed, a
+/// `CliError::InvalidInputError` naming the entry is returned.
+pub fn entry_timestamp(
+    entry_key: &str,
+    field: &str,
+    value: &serde_json::Value,
+) -> Result<u64, CliError> {
+    let invalid = || {
+        CliError::InvalidInputError(format!(
+            "Batch file entry {} has an invalid {}: {}",
+            entry_key, field, value
+        ))
+    };
+    match value {
+        serde_json::Value::Number(number) => number.as_u64().ok_or_else(invalid),
+        serde_json::Value::String(timestamp) => parse_timestamp(timestamp)
+            .map(|timestamp| timestamp as u64)
+            .map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Checks that the id of an object is fit to derive its state address from
+///
+/// The `make_*_address` helpers of `common::addressing` hash any string, so a
+/// mistyped id (empty, padded with whitespace, or holding control characters
+/// picked up from a file) would yield an address for an object which cannot
+/// exist, which the validator only rejects opaquely.
+///
+/// # Errors
+///
+/// If the id is malformed, a `CliError::InvalidInputError` naming the kind of
+/// object is returned.
+pub fn validate_id(kind: &str, id: &str) -> Result<(), CliError> {
+    let problem = if id.trim().is_empty() {
+        "it is empty"
+    } else if id.trim() != id {
+        "it has leading or trailing whitespace"
+    } else if id.chars().any(char::is_control) {
+        "it contains control characters"
+    } else {
+        return Ok(());
+    };
+
+    Err(CliError::InvalidInputError(format!(
+        "Invalid {} id {:?}: {}",
+        kind, id, problem
+    )))
+}
+
+/// Refuses to create an object which already exists on chain, unless
+/// `--replace-existing` was passed
+///
+/// `exists` is only called, to look the object up in the REST API, when the
+/// flag is absent.
+///
+/// # Errors
+///
+/// If the object exists, a `CliError::UserError` is returned. Errors from
+/// `exists` are passed through.
+pub fn check_replace_existing<F>(
+    args: &ArgMatches,
+    kind: &str,
+    id: &str,
+    exists: F,
+) -> Result<(), CliError>
+where
+    F: FnOnce(&str) -> Result<bool, CliError>,
+{
+    if args.is_present("replace_existing") || !exists(id)? {
+        return Ok(());
+    }
+
+    Err(CliError::UserError(format!(
+        "{} {} already exists. Pass --replace-existing to overwrite it",
+        kind, id
+    )))
+}
+
+/// Returns the outcome of a write command run with `--payload-only`, which
+/// dumps the payloads the command would submit instead of signing them
+///
+/// Each payload is serialized and decoded again, so the dump shows what the
+/// transaction processor would receive.
+///
+/// # Errors
+///
+/// If a payload cannot be encoded or decoded, a `CliError::ProtobufError` is
+/// returned.
+pub fn payload_only_outcome(
+    payloads: &[&CertificateRegistryPayload],
+) -> Result<CommandOutcome, CliError> {
+    let mut dumps = vec![];
+    let mut details = BTreeMap::new();
+    for (i, payload) in payloads.iter().enumerate() {
+        let bytes = payload.write_to_bytes()?;
+        let decoded: CertificateRegistryPayload = protobuf::parse_from_bytes(&bytes)?;
+        dumps.push(format!(
+            "{:?} ({} bytes)\n{}",
+            decoded.get_action(),
+            bytes.len(),
+            protobuf::text_format::print_to_string(&decoded).trim_end()
+        ));
+        details.insert(format!("payload_{}", i), base64::encode(&bytes));
+    }
+
+    let mut outcome = CommandOutcome::completed(dumps.join("\n\n"));
+    outcome.details = details;
+    Ok(outcome)
+}
+
+/// Returns the end timestamp of a certificate, either given directly as
+/// `valid_to` or computed from `valid_from` and `--valid-for`
+///
+/// # Errors
+///
+/// If both or neither of `valid_to` and `--valid-for` are given, or either
+/// cannot be parsed, a `CliError::InvalidInputError` is returned.
+pub fn valid_to_from_args(args: &ArgMatches, valid_from: &str) -> Result<String, CliError> {
+    match (args.value_of("valid_to"), args.value_of("valid_for")) {
+        (Some(valid_to), None) => Ok(parse_timestamp(valid_to)?.to_string()),
+        (None, Some(valid_for)) => {
+            let valid_from = parse_timestamp(valid_from)?;
+            let valid_for = parse_duration(valid_for)?;
+            NaiveDateTime::from_timestamp_opt(valid_from, 0)
+                .and_then(|valid_from| valid_from.checked_add_signed(valid_for))
+                .map(|valid_to| valid_to.timestamp().to_string())
+                .ok_or_else(|| {
+                    CliError::InvalidInputError(String::from(
+                        "valid_from and --valid-for give a timestamp which is out of range",
+                    ))
+                })
+        }
+        (Some(_), Some(_)) => Err(CliError::InvalidInputError(String::from(
+            "Only one of valid_to and --valid-for can be given",
+        ))),
+        (None, None) => Err(CliError::InvalidInputError(String::from(
+            "Either valid_to or --valid-for must be given",
+        ))),
+    }
+}
+
+/// Parses a point in time given in seconds since the Unix epoch, as a date
+/// formatted as YYYY-MM-DD or YYYY/MM/DD, which is taken as midnight UTC, or as
+/// `now`, optionally followed by an offset such as `now+30d` or `now-1h`
+///
+/// # Errors
+///
+/// If the timestamp is in none of these forms, or is before the Unix epoch, a
+/// `CliError::InvalidInputError` is returned.
+pub fn parse_timestamp(timestamp: &str) -> Result<i64, CliError> {
+    parse_timestamp_at(timestamp, Utc::now().timestamp())
+}
+
+/// Parses a timestamp as `parse_timestamp` does, resolving `now` to the given
+/// number of seconds since the Unix epoch
+fn parse_timestamp_at(timestamp: &str, now: i64) -> Result<i64, CliError> {
+    let invalid_timestamp = || {
+        CliError::InvalidInputError(format!(
+            "Invalid timestamp: {}. Please provide seconds since Unix epoch, a date formatted as YYYY-MM-DD, or now with an optional offset such as now+30d",
+            timestamp
+        ))
+    };
+
+    let seconds = if timestamp.starts_with("now") {
+        let offset = &timestamp["now".len()..];
+        match offset.chars().next() {
+            None => Some(now),
+            Some('+') => now.checked_add(parse_duration(&offset[1..])?.num_seconds()),
+            Some('-') => now.checked_sub(parse_duration(&offset[1..])?.num_seconds()),
+            Some(_) => None,
+        }
+    } else if let Ok(seconds) = timestamp.parse::<i64>() {
+        Some(seconds)
+    } else {
+        ["%Y-%m-%d", "%Y/%m/%d"]
+            .iter()
+            .find_map(|format| NaiveDate::parse_from_str(timestamp, format).ok())
+            .map(|date| date.and_hms(0, 0, 0).timestamp())
+    };
+
+    match seconds {
+        Some(seconds) if seconds >= 0 => Ok(seconds),
+        _ => Err(invalid_timestamp()),
+    }
+}
+
+/// Parses a duration such as "90s", "12h", "365d" or "52w"
+///
+/// # Errors
+///
+/// If the duration is not a positive number followed by one of the units s, m,
+/// h, d or w, or is too long to represent, a `CliError::InvalidInputError` is
+/// returned.
+pub fn parse_duration(duration: &str) -> Result<Duration, CliError> {
+    let invalid_duration = || {
+        CliError::InvalidInputError(format!(
+            "Invalid duration: {}. Please provide a number followed by s, m, h, d or w",
+            duration
+        ))
+    };
+
+    // The unit may be any character, so split before it rather than before
+    // the last byte
+    let (unit_start, unit) = duration
+        .char_indices()
+        .last()
+        .ok_or_else(invalid_duration)?;
+    let amount = duration[..unit_start]
+        .parse::<i64>()
+        .map_err(|_| invalid_duration())?;
+    if amount <= 0 {
+        return Err(invalid_duration());
+    }
+
+    let unit_seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        _ => return Err(invalid_duration()),
+    };
+    // chrono panics on durations of more than i64::MAX milliseconds
+    match amount.checked_mul(unit_seconds) {
+        Some(seconds) if seconds <= i64::MAX / 1000 => Ok(Duration::seconds(seconds)),
+        _ => Err(CliError::InvalidInputError(format!(
+            "Invalid duration: {}. It is too long",
+            duration
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::proto::payload::{CertificateRegistryPayload_Action, CreateAgentAction};
+
+    #[test]
+    fn payload_only_outcome_test() {
+        let mut action = CreateAgentAction::new();
+        action.set_name(String::from("agent-name"));
+        let mut payload = CertificateRegistryPayload::new();
+        payload.set_action(CertificateRegistryPayload_Action::CREATE_AGENT);
+        payload.set_create_agent(action);
+
+        let outcome = payload_only_outcome(&[&payload]).expect("Failed to dump payload");
+        assert_eq!(outcome.status, "OK");
+        assert!(outcome.batch_id.is_none());
+        assert!(outcome.message.starts_with("CREATE_AGENT ("));
+        assert!(outcome.message.contains("agent-name"));
+
+        let bytes = base64::decode(&outcome.details["payload_0"]).expect("Invalid base64");
+        let decoded: CertificateRegistryPayload =
+            protobuf::parse_from_bytes(&bytes).expect("Invalid payload");
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn require_arg_test() {
+        let app = clap::App::new("csrc")
+            .arg(clap::Arg::with_name("name").long("name").takes_value(true))
+            .arg(
+                clap::Arg::with_name("filepath")
+                    .long("filepath")
+                    .takes_value(true),
+            );
+        let args = app.get_matches_from(vec!["csrc", "--name", "acme"]);
+
+        assert_eq!(require_arg(&args, "name").unwrap(), "acme");
+        match require_arg(&args, "filepath") {
+            Err(CliError::InvalidInputError(message)) => {
+                assert_eq!(message, "missing required argument: filepath")
+            }
+            _ => panic!("Expected a missing filepath to be an InvalidInputError"),
+        }
+    }
+
+    #[test]
+    fn parse_cert_data_test() {
+        let cert_data =
+            parse_cert_data(vec!["scope:organic", "grade:A"]).expect("Failed to parse cert_data");
+        assert_eq!(cert_data.len(), 2);
+        assert_eq!(cert_data[0].get_field(), "scope");
+        assert_eq!(cert_data[0].get_data(), "organic");
+
+        match parse_cert_data(vec![
+            "scope:organic",
+            "grade:A",
+            "scope:fair trade",
+            "grade:B",
+        ]) {
+            Err(CliError::InvalidInputError(message)) => {
+                assert_eq!(message, "Duplicate cert_data fields: grade, scope")
+            }
+            other => panic!("Expected an InvalidInputError, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_duration_test() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::seconds(90));
+        assert_eq!(parse_duration("365d").unwrap(), Duration::days(365));
+        assert_eq!(parse_duration("52w").unwrap(), Duration::weeks(52));
+
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("0d").is_err());
+        assert!(parse_duration("12y").is_err());
+        assert!(parse_duration("5µ").is_err());
+        assert!(parse_duration("99999999999999999s").is_err());
+        assert!(parse_duration("9999999999999999w").is_err());
+    }
+
+    #[test]
+    fn parse_timestamp_test() {
+        assert_eq!(parse_timestamp("1500000000").unwrap(), 1_500_000_000);
+        assert_eq!(parse_timestamp("2020-01-02").unwrap(), 1_577_923_200);
+        assert_eq!(parse_timestamp("2020/01/02").unwrap(), 1_577_923_200);
+
+        assert!(parse_timestamp("").is_err());
+        assert!(parse_timestamp("02/01/2020").is_err());
+        assert!(parse_timestamp("2020-13-01").is_err());
+        assert!(parse_timestamp("-1").is_err());
+
+        assert_eq!(parse_timestamp_at("now", 1_000_000).unwrap(), 1_000_000);
+        assert_eq!(
+            parse_timestamp_at("now+30d", 1_000_000).unwrap(),
+            1_000_000 + 30 * 86_400
+        );
+        assert_eq!(
+            parse_timestamp_at("now-1h", 1_000_000).unwrap(),
+            1_000_000 - 3_600
+        );
+        assert!(parse_timestamp_at("now-1h", 60).is_err());
+        assert!(parse_timestamp_at("now+", 1_000_000).is_err());
+        assert!(parse_timestamp_at("nowish", 1_000_000).is_err());
+        assert!(parse_timestamp_at("now+9999999999999999w", 1_000_000).is_err());
+    }
+
+    #[test]
+    fn optional_batch_entry_field_test() {
+        let entry = serde_json::json!({
+            "name": "Paper Factory",
+            "city": null,
+            "contact_phone_number": 15555555555u64,
+        });
+        let field = |field| optional_batch_entry_field("factory-1", &entry, field);
+        assert_eq!(field("name").unwrap(), Some("Paper Factory"));
+        assert_eq!(field("city").unwrap(), None);
+        assert_eq!(field("country").unwrap(), None);
+        assert!(field("contact_phone_number").is_err());
+    }
+
+    #[test]
+    fn entry_timestamp_test() {
+        let timestamp = |value| entry_timestamp("cb:std", "valid_from", &value);
+        assert_eq!(
+            timestamp(serde_json::json!(1546300800)).unwrap(),
+            1546300800
+        );
+        assert_eq!(
+            timestamp(serde_json::json!("2019-01-01")).unwrap(),
+            1546300800
+        );
+        assert!(timestamp(serde_json::json!(-1)).is_err());
+        assert!(timestamp(serde_json::json!("tomorrow")).is_err());
+        assert!(timestamp(serde_json::json!(null)).is_err());
+    }
+
+    #[test]
+    fn progress_counter_test() {
+        let mut counter = ProgressCounter {
+            processed: 0,
+            total: Some(5),
+            report_every: Some(2),
+        };
+        let reports: Vec<_> = (0..5).filter_map(|_| counter.advance()).collect();
+        assert_eq!(
+            reports,
+            vec!["Processed 2/5 entries", "Processed 4/5 entries"]
+        );
+
+        let mut streaming = ProgressCounter {
+            processed: 0,
+            total: None,
+            report_every: Some(3),
+        };
+        let reports: Vec<_> = (0..5).filter_map(|_| streaming.advance()).collect();
+        assert_eq!(reports, vec!["Processed 3 entries"]);
+
+        let mut quiet = ProgressCounter {
+            processed: 0,
+            total: Some(5),
+            report_every: None,
+        };
+        assert!((0..5).all(|_| quiet.advance().is_none()));
+    }
+
+    #[test]
+    fn parse_failed_entries_test() {
+        let error_log = concat!(
+            "{\"transaction_id\":\"a1\",\"entry\":\"factory-1\",\"message\":\"x\"}\n",
+            "\n",
+            "{\"transaction_id\":\"b2\",\"entry\":\"factory-2\",\"message\":\"y\"}\n",
+        );
+        let failed = parse_failed_entries(error_log).unwrap();
+        assert_eq!(
+            failed.into_iter().collect::<Vec<_>>(),
+            vec!["factory-1", "factory-2"]
+        );
+
+        // A transaction with no entry has nothing to retry
+        let failed =
+            parse_failed_entries("{\"transaction_id\":\"a1\",\"entry\":null,\"message\":\"x\"}")
+                .unwrap();
+        assert!(failed.is_empty());
+
+        assert!(parse_failed_entries("{\"transaction_id\":\"a1\"}").is_err());
+        assert!(parse_failed_entries("not json").is_err());
+    }
+
+    #[test]
+    fn validate_id_test() {
+        assert!(validate_id("organization", "factory-1").is_ok());
+        assert!(validate_id("organization", "Paper Factory").is_ok());
+
+        assert!(validate_id("organization", "").is_err());
+        assert!(validate_id("organization", "  ").is_err());
+        assert!(validate_id("organization", "factory-1\n").is_err());
+        assert!(validate_id("organization", "factory\u{0}1").is_err());
+    }
+
+    #[test]
+    fn is_yaml_file_test() {
+        assert!(is_yaml_file("factories.yaml", "{}"));
+        assert!(is_yaml_file("factories.yml", ""));
+        assert!(!is_yaml_file("factories.json", "factory: {}"));
+
+        // Without a known extension the contents decide, defaulting to JSON
+        assert!(is_yaml_file("-", "factory-id:\n  name: Factory"));
+        assert!(!is_yaml_file("-", "  {\"factory-id\": {}}"));
+        assert!(!is_yaml_file("factories", ""));
+    }
+}
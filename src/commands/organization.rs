@@ -1,29 +1,28 @@
+use crate::commands;
+use crate::commands::CommandOutcome;
+use crate::contact;
 use crate::error::CliError;
 use crate::key;
+use crate::prompt;
 use crate::submit;
 use crate::transaction::{
     create_batch, create_batch_list, create_batch_list_from_one, create_batch_with_transactions,
-    create_transaction,
+    create_transaction, TransactionOptions,
 };
 
 use clap::ArgMatches;
 use common::addressing;
 use common::proto::payload::{CertificateRegistryPayload, CertificateRegistryPayload_Action};
 use common::proto::payload::{CreateOrganizationAction, UpdateOrganizationAction};
+use protobuf::ProtobufEnum;
 use sawtooth_sdk::messages::transaction::Transaction;
-use sawtooth_sdk::signing;
-use std::fs::File;
-use std::io::prelude::*;
-use std::{thread, time};
 use uuid::Uuid;
 
 use common::proto::organization::Factory_Address;
 use common::proto::organization::Organization_Contact;
 use common::proto::organization::Organization_Type;
 
-const SECP_256K1: &str = "secp256k1";
-
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     match args.subcommand() {
         ("create", Some(args)) => run_create_command(args),
         ("update", Some(args)) => run_update_command(args),
@@ -34,235 +33,430 @@ pub fn run(args: &ArgMatches) -> Result<(), CliError> {
     }
 }
 
-fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
-    let name = args.value_of("name").unwrap();
-    let contact_name = args.value_of("contact_name").unwrap();
-    let contact_phone_number = args.value_of("contact_phone_number").unwrap();
-    let contact_language_code = args.value_of("contact_language_code").unwrap();
+fn run_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let name = commands::require_arg(args, "name")?;
+    let contact_name = commands::require_arg(args, "contact_name")?;
+    let contact_phone_number = contact::normalize_phone_number(
+        commands::require_arg(args, "contact_phone_number")?,
+        args.is_present("strict_contact"),
+    )?;
+    let contact_language_code = contact::language_code_from_args(args)?;
     let street = args.value_of("street_address");
+    let street_line_2 = args.value_of("street_line_2");
     let city = args.value_of("city");
+    let state_province = args.value_of("state_province");
+    let postal_code = args.value_of("postal_code");
     let country = args.value_of("country");
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
 
-    let valid_org_types =
-        "1 - CERTIFYING_BODY \n 2 - STANDARDS_BODY \n 3 - FACTORY \n 4 - INGESTION";
+    let organization_type = parse_organization_type(commands::require_arg(args, "org_type")?)?;
 
-    let organization_type = match args.value_of("org_type").unwrap() {
-        "1" => Ok(Organization_Type::CERTIFYING_BODY),
-        "2" => Ok(Organization_Type::STANDARDS_BODY),
-        "3" => Ok(Organization_Type::FACTORY),
-        "4" => Ok(Organization_Type::INGESTION),
-        other => Err(CliError::UserError(format!(
-            "Invalid organization type: {:?}. Valid types are: \n {org_types}",
-            other,
-            org_types = valid_org_types
-        ))),
-    }?;
-
-    if organization_type == Organization_Type::FACTORY {
-        match street {
-            None => Err(CliError::InvalidInputError(
-                "A street address is required for a factory".to_string(),
-            )),
-            other => Ok(other),
-        }?;
-        match city {
-            None => Err(CliError::InvalidInputError(
-                "A city is required for a factory".to_string(),
-            )),
-            other => Ok(other),
-        }?;
-        match country {
-            None => Err(CliError::InvalidInputError(
-                "A country is required for a factory".to_string(),
-            )),
-            other => Ok(other),
-        }?;
+    if args.is_present("verify") && args.is_present("no_wait") {
+        return Err(CliError::InvalidInputError(String::from(
+            "--verify needs to wait for the organization to be committed, so it cannot be used with --no-wait",
+        )));
     }
 
     let org_id = Uuid::new_v4().to_string();
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context("secp256k1")?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
-
     let payload = create_organization_payload(
         &org_id,
         &name,
         organization_type,
         contact_name,
-        contact_phone_number,
+        &contact_phone_number,
         contact_language_code,
         street,
+        street_line_2,
         city,
+        state_province,
+        postal_code,
         country,
-    );
+    )?;
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
+    }
+
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     let header_input =
         create_organization_transaction_addresses(&signer.get_public_key()?.as_hex(), &org_id);
     let header_output = header_input.clone();
 
-    let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        &options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut org_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
+    let batch_status = client.submit_and_wait(&batch_list)?;
 
-    loop {
-        match org_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Organization {} has been created", org_id);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    org_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                org_status = submit::wait_for_status(url, &org_status.link)?;
-            }
+    if args.is_present("verify") {
+        verify_organization(&client, &org_id, name, organization_type)?;
+    }
+
+    let message = format!("Organization {} has been created", org_id);
+    Ok(CommandOutcome::submitted(
+        Some(org_id),
+        &batch_status,
+        message,
+    ))
+}
+
+/// Checks that the REST API reports a newly created organization with the
+/// name and type it was created with
+///
+/// # Errors
+///
+/// If the organization is missing or differs, a `CliError::UserError` is
+/// returned.
+fn verify_organization(
+    client: &submit::ApiClient,
+    org_id: &str,
+    name: &str,
+    organization_type: Organization_Type,
+) -> Result<(), CliError> {
+    let organization = client
+        .fetch_data(&format!("/organizations/{}", org_id))?
+        .ok_or_else(|| {
+            CliError::UserError(format!(
+                "Organization {} was committed, but the REST API does not know of it",
+                org_id
+            ))
+        })?;
+
+    let stored_name = organization.get("name").and_then(|name| name.as_str());
+    if stored_name != Some(name) {
+        return Err(CliError::UserError(format!(
+            "Organization {} was created with name {:?}, but the REST API reports {:?}",
+            org_id,
+            name,
+            stored_name.unwrap_or("no name")
+        )));
+    }
+
+    if !organization_type_matches(&organization, organization_type) {
+        return Err(CliError::UserError(format!(
+            "Organization {} was created as a {:?}, but the REST API reports {}",
+            org_id,
+            organization_type,
+            organization
+                .get("organization_type")
+                .map(|organization_type| organization_type.to_string())
+                .unwrap_or_else(|| String::from("no type"))
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns whether an organization, as reported by the REST API, has the
+/// given type
+///
+/// The type may be reported either by its number or by its name.
+pub fn organization_type_matches(
+    organization: &serde_json::Value,
+    organization_type: Organization_Type,
+) -> bool {
+    match organization.get("organization_type") {
+        Some(serde_json::Value::Number(number)) => {
+            number.as_i64() == Some(i64::from(organization_type.value()))
+        }
+        Some(serde_json::Value::String(type_name)) => {
+            type_name.eq_ignore_ascii_case(&format!("{:?}", organization_type))
         }
+        _ => false,
     }
 }
 
-fn run_update_command(args: &ArgMatches) -> Result<(), CliError> {
-    let org_id = args.value_of("id").unwrap();
+fn run_update_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let org_id = commands::require_arg(args, "id")?;
+    commands::validate_id("organization", org_id)?;
     let name = args.value_of("name");
     let contact_name = args.value_of("contact_name");
-    let contact_phone_number = args.value_of("contact_phone_number");
+    let contact_phone_number = args
+        .value_of("contact_phone_number")
+        .map(|number| contact::normalize_phone_number(number, args.is_present("strict_contact")))
+        .transpose()?;
     let contact_language_code = args.value_of("contact_language_code");
     let street = args.value_of("street_address");
     let city = args.value_of("city");
     let country = args.value_of("country");
-    let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context("secp256k1")?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let key = key::signing_key_for_org(args, org_id)?;
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+
+    // UpdateOrganizationAction has no type field, so the transaction family
+    // offers no way to change the type of an existing organization.
+    if let Some(org_type) = args.value_of("org_type") {
+        let organization_type = parse_organization_type(org_type)?;
+        return Err(CliError::UserError(format!(
+            "Unable to change the type of organization {} to {:?}: the ConsenSource \
+             transaction family does not support changing an organization's type. \
+             Create a new organization with the correct type instead",
+            org_id, organization_type
+        )));
+    }
 
     let payload = update_organization_payload(
         &org_id,
         name,
         contact_name,
-        contact_phone_number,
+        contact_phone_number.as_deref(),
         contact_language_code,
         street,
         city,
         country,
-    );
+    )?;
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
+    }
+
+    if args.is_present("dry_run") {
+        let current = client
+            .fetch_data(&format!("/organizations/{}", org_id))?
+            .ok_or_else(|| {
+                CliError::UserError(format!("Organization {} does not exist", org_id))
+            })?;
+        return Ok(dry_run_outcome(
+            org_id,
+            diff_organization_update(&current, payload.get_update_organization()),
+        ));
+    }
+
+    let signing_context = key::SigningContext::new(key.as_deref(), &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     let header_input =
         create_organization_transaction_addresses(&signer.get_public_key()?.as_hex(), &org_id);
     let header_output = header_input.clone();
 
-    let txn = create_transaction(&payload, &signer, header_input, header_output)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher.as_ref(),
+        header_input,
+        header_output,
+        vec![],
+        &options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut org_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
+    prompt::confirm(
+        &format!("This will modify organization {} on chain.", org_id),
+        args.is_present("assume_yes"),
+    )?;
 
-    loop {
-        match org_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Organization {} has been updated", org_id);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    org_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                org_status = submit::wait_for_status(url, &org_status.link)?;
-            }
-        }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(org_id.to_string()),
+        &batch_status,
+        format!("Organization {} has been updated", org_id),
+    ))
+}
+
+/// A field of an organization which an update would change
+#[derive(Debug, PartialEq)]
+struct FieldChange {
+    field: &'static str,
+    before: String,
+    after: String,
+}
+
+/// Returns the fields of the organization, as returned by the REST API, which
+/// the update would change
+///
+/// The transaction family replaces the whole contact list and address when
+/// an update sets them, so they are compared as a whole.
+fn diff_organization_update(
+    current: &serde_json::Value,
+    update: &UpdateOrganizationAction,
+) -> Vec<FieldChange> {
+    let text = |value: &serde_json::Value, name: &str| match value.get(name) {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    };
+    let describe_contact = |name: &str, phone_number: &str, language_code: &str| {
+        format!("{} ({}, {})", name, phone_number, language_code)
+    };
+    let describe_address =
+        |street: &str, city: &str, country: &str| format!("{}, {}, {}", street, city, country);
+
+    let mut changes = vec![];
+
+    if !update.get_name().is_empty() {
+        changes.push(FieldChange {
+            field: "name",
+            before: text(current, "name"),
+            after: update.get_name().to_string(),
+        });
+    }
+
+    if !update.get_contacts().is_empty() {
+        let before = current
+            .get("contacts")
+            .and_then(|contacts| contacts.as_array())
+            .map(|contacts| {
+                contacts
+                    .iter()
+                    .map(|contact| {
+                        describe_contact(
+                            &text(contact, "name"),
+                            &text(contact, "phone_number"),
+                            &text(contact, "language_code"),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            })
+            .unwrap_or_default();
+        let after = update
+            .get_contacts()
+            .iter()
+            .map(|contact| {
+                describe_contact(
+                    contact.get_name(),
+                    contact.get_phone_number(),
+                    contact.get_language_code(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        changes.push(FieldChange {
+            field: "contacts",
+            before,
+            after,
+        });
+    }
+
+    if update.has_address() {
+        let before = current
+            .get("address")
+            .filter(|address| address.is_object())
+            .map(|address| {
+                describe_address(
+                    &text(address, "street_line_1"),
+                    &text(address, "city"),
+                    &text(address, "country"),
+                )
+            })
+            .unwrap_or_default();
+        let address = update.get_address();
+        changes.push(FieldChange {
+            field: "address",
+            before,
+            after: describe_address(
+                address.get_street_line_1(),
+                address.get_city(),
+                address.get_country(),
+            ),
+        });
     }
+
+    changes.retain(|change| change.before != change.after);
+    changes
+}
+
+/// Returns the outcome of a dry run of `organization update`, listing the
+/// fields which would change
+fn dry_run_outcome(org_id: &str, changes: Vec<FieldChange>) -> CommandOutcome {
+    let message = if changes.is_empty() {
+        format!("The update would not change organization {}", org_id)
+    } else {
+        let mut lines = vec![format!(
+            "The update would change organization {} as follows:",
+            org_id
+        )];
+        lines.extend(changes.iter().map(|change| {
+            format!(
+                "  {}: {:?} -> {:?}",
+                change.field, change.before, change.after
+            )
+        }));
+        lines.join("\n")
+    };
+
+    let mut outcome = CommandOutcome::completed(message);
+    outcome.id = Some(org_id.to_string());
+    outcome.details = changes
+        .into_iter()
+        .map(|change| {
+            (
+                change.field.to_string(),
+                format!("{:?} -> {:?}", change.before, change.after),
+            )
+        })
+        .collect();
+    outcome
 }
 
-fn run_batch_update_command(args: &ArgMatches) -> Result<(), CliError> {
+fn run_batch_update_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     // Extract system arguments
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
-
-    // Define uninitialized arguments
-    let mut org_id: &str;
-    let mut name: Option<&str>;
-    let mut contact_name: Option<&str>;
-    let mut contact_phone_number: Option<&str>;
-    let mut contact_language_code: Option<&str>;
-    let mut street: Option<&str>;
-    let mut city: Option<&str>;
-    let mut country: Option<&str>;
-
-    // Read factories from provided JSON batch file
-    let filepath = args.value_of("filepath").unwrap();
-    let mut file = File::open(filepath)?;
-    let mut data: String = String::new();
-    file.read_to_string(&mut data)?;
-    let org_updates: serde_json::Value = serde_json::from_str(&data).expect("Unable to parse");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
+    let max_invalid_display = submit::max_invalid_display_from_args(args)?;
+    let strict_contact = args.is_present("strict_contact");
+
+    // Read factories from provided batch file
+    let filepath = commands::require_arg(args, "filepath")?;
+    let org_updates: serde_json::Map<String, serde_json::Value> = if args.is_present("from_csv") {
+        parse_org_updates_csv(&commands::read_input_file(filepath)?).map_err(|err| {
+            CliError::InvalidInputError(format!(
+                "Unable to parse organization updates from {}: {}",
+                filepath, err
+            ))
+        })?
+    } else {
+        commands::read_batch_file(args)?
+    };
+    let entry_filter = commands::EntryFilter::from_args(args)?;
 
     // Create signing key
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context(SECP_256K1)?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
     // Loop through map of factories and populate list of transactions
-    println!("Creating transactions for {}", filepath);
+    eprintln!("Creating transactions for {}", filepath);
     let mut txn_list: Vec<Transaction> = vec![];
-    for (key, value) in org_updates.as_object().unwrap() {
-        org_id = key.as_str();
-        name = value.get("name").unwrap().as_str();
-        contact_name = value.get("contact_name").unwrap().as_str();
-        contact_phone_number = value.get("contact_phone_number").unwrap().as_str();
-        contact_language_code = value.get("contact_language_code").unwrap().as_str();
-        street = value.get("street_address").unwrap().as_str();
-        city = value.get("city").unwrap().as_str();
-        country = value.get("country").unwrap().as_str();
+    let mut txn_entries: Vec<(String, &str)> = vec![];
+    for (key, value) in &org_updates {
+        if !entry_filter.includes(key) {
+            continue;
+        }
+
+        let org_id = key.as_str();
+        commands::validate_id("organization", org_id)?;
+        let field = |field| commands::optional_batch_entry_field(key, value, field);
+        let contact_phone_number = field("contact_phone_number")?
+            .map(|number| contact::normalize_phone_number(number, strict_contact))
+            .transpose()?;
 
         let update_org_action_payload = update_organization_payload(
             org_id,
-            name,
-            contact_name,
-            contact_phone_number,
-            contact_language_code,
-            street,
-            city,
-            country,
-        );
+            field("name")?,
+            field("contact_name")?,
+            contact_phone_number.as_deref(),
+            field("contact_language_code")?,
+            field("street_address")?,
+            field("city")?,
+            field("country")?,
+        )?;
 
         let header_input =
             create_organization_transaction_addresses(&signer.get_public_key()?.as_hex(), &org_id);
@@ -270,50 +464,120 @@ fn run_batch_update_command(args: &ArgMatches) -> Result<(), CliError> {
         let txn = create_transaction(
             &update_org_action_payload,
             &signer,
+            batcher.as_ref(),
             header_input,
             header_output,
+            vec![],
+            &options,
         )?;
+        txn_entries.push((txn.header_signature.clone(), key.as_str()));
         txn_list.push(txn);
     }
 
-    println!("Creating batch list for transactions");
-    let batch = create_batch_with_transactions(txn_list, &signer)?;
+    if txn_list.is_empty() {
+        return Ok(commands::nothing_to_submit(
+            filepath,
+            "organization updates",
+        ));
+    }
+
+    prompt::confirm_input(
+        &format!(
+            "This will modify {} organizations on chain.",
+            txn_list.len()
+        ),
+        filepath,
+        args.is_present("assume_yes"),
+    )?;
+
+    eprintln!("Creating batch list for transactions");
+    let batch = create_batch_with_transactions(txn_list, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list(vec![batch]);
 
-    let mut update_org_status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
+    let batch_status =
+        client.submit_batch_and_wait(&batch_list, &txn_entries, max_invalid_display)?;
 
-    loop {
-        match update_org_status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Organizations from file {} have been updated", filepath);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    update_org_status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                update_org_status = submit::wait_for_status(url, &update_org_status.link)?;
+    Ok(CommandOutcome::submitted(
+        None,
+        &batch_status,
+        format!("Organizations from file {} have been updated", filepath),
+    ))
+}
+
+/// The columns of a CSV file of organization updates
+const ORG_UPDATE_CSV_COLUMNS: [&str; 8] = [
+    "id",
+    "name",
+    "contact_name",
+    "contact_phone_number",
+    "contact_language_code",
+    "street_address",
+    "city",
+    "country",
+];
+
+/// Parses a CSV file of organization updates into the shape of a JSON batch
+/// file: an object of updates keyed by organization id
+///
+/// Blank cells become null, leaving the field unchanged. Columns other than
+/// `ORG_UPDATE_CSV_COLUMNS` are ignored.
+fn parse_org_updates_csv(data: &str) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+    let headers = reader.headers().map_err(|err| err.to_string())?.clone();
+
+    if let Some(missing) = ORG_UPDATE_CSV_COLUMNS
+        .iter()
+        .find(|column| !headers.iter().any(|header| header == **column))
+    {
+        return Err(format!("Missing column {}", missing));
+    }
+
+    let mut org_updates = serde_json::Map::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| err.to_string())?;
+        let line = record.position().map_or(0, |position| position.line());
+
+        let mut org_id = None;
+        let mut org_update = serde_json::Map::new();
+        for (column, cell) in headers.iter().zip(record.iter()) {
+            if column == "id" {
+                org_id = Some(cell);
+            } else if ORG_UPDATE_CSV_COLUMNS.contains(&column) {
+                let value = if cell.is_empty() {
+                    serde_json::Value::Null
+                } else {
+                    serde_json::Value::from(cell)
+                };
+                org_update.insert(column.to_string(), value);
             }
         }
+
+        let org_id = org_id
+            .filter(|org_id| !org_id.is_empty())
+            .ok_or_else(|| format!("Row on line {} has no id", line))?;
+        if org_updates
+            .insert(org_id.to_string(), serde_json::Value::Object(org_update))
+            .is_some()
+        {
+            return Err(format!("Duplicate id {} on line {}", org_id, line));
+        }
     }
+
+    Ok(org_updates)
 }
 
+/// Returns a payload for creating an organization
+///
+/// Every organization needs a name and a contact. A factory also needs a
+/// street address, city and country; the other types, including ingestion
+/// organizations, have no address, so any address fields are ignored.
+///
+/// # Errors
+///
+/// If a factory is missing part of its address, or the contact's language
+/// code is invalid, a `CliError::InvalidInputError` is returned.
 #[allow(clippy::too_many_arguments)]
 pub fn create_organization_payload(
     id: &str,
@@ -323,9 +587,14 @@ pub fn create_organization_payload(
     contact_phone_number: &str,
     contact_language_code: &str,
     street: Option<&str>,
+    street_line_2: Option<&str>,
     city: Option<&str>,
+    state_province: Option<&str>,
+    postal_code: Option<&str>,
     country: Option<&str>,
-) -> CertificateRegistryPayload {
+) -> Result<CertificateRegistryPayload, CliError> {
+    contact::validate_language_code(contact_language_code)?;
+
     let mut organization = CreateOrganizationAction::new();
     organization.set_name(String::from(name));
     organization.set_id(String::from(id));
@@ -333,9 +602,18 @@ pub fn create_organization_payload(
 
     if org_type == Organization_Type::FACTORY {
         let mut factory_address = Factory_Address::new();
-        factory_address.set_street_line_1(street.unwrap().to_string());
-        factory_address.set_city(city.unwrap().to_string());
-        factory_address.set_country(country.unwrap().to_string());
+        factory_address.set_street_line_1(factory_address_field(street, "A street address")?);
+        factory_address.set_city(factory_address_field(city, "A city")?);
+        factory_address.set_country(factory_address_field(country, "A country")?);
+        if let Some(street_line_2) = street_line_2 {
+            factory_address.set_street_line_2(street_line_2.to_string());
+        }
+        if let Some(state_province) = state_province {
+            factory_address.set_state_province(state_province.to_string());
+        }
+        if let Some(postal_code) = postal_code {
+            factory_address.set_postal_code(postal_code.to_string());
+        }
         organization.set_address(factory_address);
     }
 
@@ -348,7 +626,32 @@ pub fn create_organization_payload(
     let mut payload = CertificateRegistryPayload::new();
     payload.action = CertificateRegistryPayload_Action::CREATE_ORGANIZATION;
     payload.set_create_organization(organization);
-    payload
+    Ok(payload)
+}
+
+/// Returns a part of a factory's address, which must be given
+fn factory_address_field(value: Option<&str>, field: &str) -> Result<String, CliError> {
+    value
+        .map(String::from)
+        .ok_or_else(|| CliError::InvalidInputError(format!("{} is required for a factory", field)))
+}
+
+/// Returns the organization type selected by its number on the command line
+fn parse_organization_type(org_type: &str) -> Result<Organization_Type, CliError> {
+    let valid_org_types =
+        "1 - CERTIFYING_BODY \n 2 - STANDARDS_BODY \n 3 - FACTORY \n 4 - INGESTION";
+
+    match org_type {
+        "1" => Ok(Organization_Type::CERTIFYING_BODY),
+        "2" => Ok(Organization_Type::STANDARDS_BODY),
+        "3" => Ok(Organization_Type::FACTORY),
+        "4" => Ok(Organization_Type::INGESTION),
+        other => Err(CliError::UserError(format!(
+            "Invalid organization type: {:?}. Valid types are: \n {org_types}",
+            other,
+            org_types = valid_org_types
+        ))),
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -361,7 +664,7 @@ pub fn update_organization_payload(
     street: Option<&str>,
     city: Option<&str>,
     country: Option<&str>,
-) -> CertificateRegistryPayload {
+) -> Result<CertificateRegistryPayload, CliError> {
     let mut organization = UpdateOrganizationAction::new();
     organization.set_id(String::from(id));
     if let Some(name) = name {
@@ -371,6 +674,8 @@ pub fn update_organization_payload(
     if let (Some(contact_name), Some(contact_phone_number), Some(contact_language_code)) =
         (contact_name, contact_phone_number, contact_language_code)
     {
+        contact::validate_language_code(contact_language_code)?;
+
         let mut contact = Organization_Contact::new();
         contact.set_name(String::from(contact_name));
         contact.set_phone_number(String::from(contact_phone_number));
@@ -389,7 +694,7 @@ pub fn update_organization_payload(
     let mut payload = CertificateRegistryPayload::new();
     payload.action = CertificateRegistryPayload_Action::UPDATE_ORGANIZATION;
     payload.set_update_organization(organization);
-    payload
+    Ok(payload)
 }
 
 pub fn create_organization_transaction_addresses(
@@ -400,3 +705,133 @@ pub fn create_organization_transaction_addresses(
     let org_address = addressing::make_organization_address(organization_id);
     vec![agent_address, org_address]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_organization_payload_address_test() {
+        let payload = create_organization_payload(
+            "factory-1",
+            "Paper Factory",
+            Organization_Type::FACTORY,
+            "Ann",
+            "+15551234567",
+            "en",
+            Some("1 Main St"),
+            Some("Suite 200"),
+            Some("Minneapolis"),
+            Some("Minnesota"),
+            Some("55403"),
+            Some("USA"),
+        )
+        .expect("Failed to create payload");
+
+        let address = payload.get_create_organization().get_address();
+        assert_eq!(address.get_street_line_1(), "1 Main St");
+        assert_eq!(address.get_street_line_2(), "Suite 200");
+        assert_eq!(address.get_city(), "Minneapolis");
+        assert_eq!(address.get_state_province(), "Minnesota");
+        assert_eq!(address.get_postal_code(), "55403");
+        assert_eq!(address.get_country(), "USA");
+    }
+
+    #[test]
+    fn create_ingestion_organization_payload_test() {
+        let payload = create_organization_payload(
+            "ingestion-1",
+            "Data Feed",
+            Organization_Type::INGESTION,
+            "Ann",
+            "+15551234567",
+            "en",
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("Failed to create payload");
+
+        let organization = payload.get_create_organization();
+        assert_eq!(
+            organization.get_organization_type(),
+            Organization_Type::INGESTION
+        );
+        assert!(!organization.has_address());
+        assert_eq!(organization.get_contacts()[0].get_name(), "Ann");
+
+        // The same organization is rejected as a factory, which needs an address
+        assert!(create_organization_payload(
+            "factory-1",
+            "Data Feed",
+            Organization_Type::FACTORY,
+            "Ann",
+            "+15551234567",
+            "en",
+            Some("1 Main St"),
+            None,
+            None,
+            None,
+            None,
+            Some("USA"),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn diff_organization_update_test() {
+        let current = serde_json::json!({
+            "id": "factory-1",
+            "name": "Paper Factory",
+            "contacts": [
+                {"name": "Ann", "phone_number": "+15551234567", "language_code": "en"}
+            ],
+            "address": {"street_line_1": "1 Main St", "city": "Minneapolis", "country": "US"}
+        });
+
+        let payload = update_organization_payload(
+            "factory-1",
+            Some("Paper Factory"),
+            Some("Bob"),
+            Some("+15557654321"),
+            Some("en"),
+            Some("1 Main St"),
+            Some("Minneapolis"),
+            Some("US"),
+        )
+        .expect("Failed to create payload");
+        let changes = diff_organization_update(&current, payload.get_update_organization());
+
+        assert_eq!(
+            changes,
+            vec![FieldChange {
+                field: "contacts",
+                before: String::from("Ann (+15551234567, en)"),
+                after: String::from("Bob (+15557654321, en)"),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_org_updates_csv_test() {
+        let data = "id,name,contact_name,contact_phone_number,contact_language_code,street_address,city,country\n\
+                    factory-1, Paper Factory ,,,,,Minneapolis,\n";
+        let org_updates = parse_org_updates_csv(data).expect("Failed to parse CSV");
+
+        let org_update = &org_updates["factory-1"];
+        assert_eq!(org_update["name"], "Paper Factory");
+        assert_eq!(org_update["city"], "Minneapolis");
+        assert!(org_update["contact_name"].is_null());
+        assert!(org_update["country"].is_null());
+
+        assert!(parse_org_updates_csv("id,name\nfactory-1,Paper Factory\n").is_err());
+        assert!(parse_org_updates_csv(&format!(
+            "{}\n,Paper Factory,,,,,,\n",
+            ORG_UPDATE_CSV_COLUMNS.join(",")
+        ))
+        .is_err());
+    }
+}
@@ -0,0 +1,27 @@
+use crate::commands::CommandOutcome;
+use crate::error::CliError;
+use crate::submit;
+
+use clap::ArgMatches;
+
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let client = submit::ApiClient::from_args(args)?;
+
+    let (status, elapsed) = client.ping()?;
+
+    if !status.is_success() {
+        return Err(CliError::UserError(format!(
+            "REST API at {} is reachable, but responded with {} ({} ms)",
+            client.base_url(),
+            status,
+            elapsed.as_millis()
+        )));
+    }
+
+    Ok(CommandOutcome::completed(format!(
+        "REST API at {} is reachable: {} ({} ms)",
+        client.base_url(),
+        status,
+        elapsed.as_millis()
+    )))
+}
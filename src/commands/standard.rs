@@ -1,9 +1,12 @@
+use crate::commands;
+use crate::commands::CommandOutcome;
 use crate::error::CliError;
 use crate::key;
 use crate::submit;
-use crate::transaction::{create_batch, create_batch_list_from_one, create_transaction};
+use crate::transaction::{
+    create_batch, create_batch_list_from_one, create_transaction, TransactionOptions,
+};
 
-use chrono::NaiveDateTime;
 use clap::ArgMatches;
 use common::addressing;
 use common::proto::payload::CreateStandardAction;
@@ -11,9 +14,8 @@ use common::proto::payload::{CertificateRegistryPayload, CertificateRegistryPayl
 use crypto::digest::Digest;
 use crypto::sha2::Sha256;
 use sawtooth_sdk::signing;
-use std::{thread, time};
 
-pub fn run(args: &ArgMatches) -> Result<(), CliError> {
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
     match args.subcommand() {
         ("create", Some(args)) => run_create_command(args),
         _ => Err(CliError::InvalidInputError(String::from(
@@ -22,35 +24,43 @@ pub fn run(args: &ArgMatches) -> Result<(), CliError> {
     }
 }
 
-fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
-    let name = args.value_of("name").unwrap();
-    let version = args.value_of("version").unwrap();
-    let description = args.value_of("description").unwrap();
-    let link = args.value_of("link").unwrap();
-    let organization_id = args.value_of("organization_id").unwrap();
-    let approval_date = args.value_of("approval_date").unwrap();
+fn run_create_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let name = commands::require_arg(args, "name")?;
+    let version = commands::require_arg(args, "version")?;
+    let description = commands::require_arg(args, "description")?;
+    let link = commands::require_arg(args, "link")?;
+    let organization_id = commands::require_arg(args, "organization_id")?;
+    commands::validate_id("organization", organization_id)?;
+    let approval_date =
+        commands::parse_timestamp(commands::require_arg(args, "approval_date")?)? as u64;
     let key = args.value_of("key");
-    let url = args.value_of("url").unwrap_or("http://localhost:9009");
+    let client = submit::ApiClient::from_args(args)?;
+    let options = TransactionOptions::from_args(args)?;
 
-    //check approval_date is valid timestamp
-    if NaiveDateTime::parse_from_str(approval_date, "%s").is_err() {
-        return Err(CliError::UserError(
-            "Approval date is invalid. Please provide time in seconds since Unix epoch".to_string(),
-        ));
+    let payload = create_standard_payload(&name, &version, &description, &link, approval_date);
+    if args.is_present("payload_only") {
+        return commands::payload_only_outcome(&[&payload]);
     }
 
-    let private_key = key::load_signing_key(key)?;
-    let context = signing::create_context("secp256k1")?;
-    let factory = signing::CryptoFactory::new(&*context);
-    let signer = factory.new_signer(&private_key);
+    // The standard id is derived from its name, so it is known before anything
+    // is signed or submitted
+    if args.is_present("print_id_only") {
+        let standard_id = payload.get_create_standard().get_standard_id().to_string();
+        let mut outcome = CommandOutcome::completed(standard_id.clone());
+        outcome.id = Some(standard_id);
+        return Ok(outcome);
+    }
+
+    let signing_context = key::SigningContext::new(key, &options)?;
+    let signer = signing_context.signer();
+    let batcher = signing_context.batcher();
 
-    let payload = create_standard_payload(
-        &name,
-        &version,
-        &description,
-        &link,
-        approval_date.parse::<u64>().unwrap(),
-    );
+    commands::check_replace_existing(
+        args,
+        "Standard",
+        payload.get_create_standard().get_standard_id(),
+        |id| client.standard_exists(id),
+    )?;
 
     let (inputs, outputs) = create_standard_transaction_addresses(
         &signer,
@@ -58,42 +68,25 @@ fn run_create_command(args: &ArgMatches) -> Result<(), CliError> {
         &organization_id,
     )?;
 
-    let txn = create_transaction(&payload, &signer, inputs, outputs)?;
-    let batch = create_batch(txn, &signer)?;
+    let txn = create_transaction(
+        &payload,
+        &signer,
+        batcher.as_ref(),
+        inputs,
+        outputs,
+        vec![],
+        &options,
+    )?;
+    let batch = create_batch(txn, &signer, batcher.as_ref())?;
     let batch_list = create_batch_list_from_one(batch);
 
-    let mut status = submit::submit_batch_list(url, &batch_list)
-        .and_then(|link| submit::wait_for_status(url, &link))?;
-
-    loop {
-        match status
-            .data
-            .get(0)
-            .expect("Expected a batch status, but was not found")
-            .status
-            .as_ref()
-        {
-            "COMMITTED" => {
-                println!("Standard {} {} has been created", name, version);
-                break Ok(());
-            }
-            "INVALID" => {
-                break Err(CliError::InvalidTransactionError(
-                    status.data[0]
-                        .invalid_transactions
-                        .get(0)
-                        .expect("Expected a transaction status, but was not found")
-                        .message
-                        .clone(),
-                ));
-            }
-            // "PENDING" case where we should recheck
-            _ => {
-                thread::sleep(time::Duration::from_millis(3000));
-                status = submit::wait_for_status(url, &status.link)?;
-            }
-        }
-    }
+    let batch_status = client.submit_and_wait(&batch_list)?;
+
+    Ok(CommandOutcome::submitted(
+        Some(payload.get_create_standard().get_standard_id().to_string()),
+        &batch_status,
+        format!("Standard {} {} has been created", name, version),
+    ))
 }
 
 pub fn create_standard_payload(
@@ -105,9 +98,7 @@ pub fn create_standard_payload(
 ) -> CertificateRegistryPayload {
     let mut standard = CreateStandardAction::new();
 
-    let mut standard_id_sha = Sha256::new();
-    standard_id_sha.input_str(name);
-    standard.set_standard_id(standard_id_sha.result_str());
+    standard.set_standard_id(make_standard_id(name));
     standard.set_name(String::from(name));
     standard.set_version(String::from(version));
     standard.set_description(String::from(description));
@@ -120,6 +111,14 @@ pub fn create_standard_payload(
     payload
 }
 
+/// Returns the id of the standard with the given name, which is the sha256
+/// hash of the name
+pub fn make_standard_id(name: &str) -> String {
+    let mut standard_id_sha = Sha256::new();
+    standard_id_sha.input_str(name);
+    standard_id_sha.result_str()
+}
+
 pub fn create_standard_transaction_addresses(
     signer: &signing::Signer,
     standard_id: &str,
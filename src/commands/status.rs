@@ -0,0 +1,77 @@
+use crate::commands;
+use crate::commands::CommandOutcome;
+use crate::error::CliError;
+use crate::submit::{self, Status, Submitter};
+
+use clap::ArgMatches;
+use std::time::{Duration, Instant};
+
+pub fn run(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let batch = commands::require_arg(args, "batch")?;
+    let client = submit::ApiClient::from_args(args)?;
+    let timeout = args
+        .value_of("timeout")
+        .map(|timeout| {
+            timeout.parse::<u64>().map_err(|_| {
+                CliError::InvalidInputError(format!(
+                    "Invalid timeout: {}. Please provide a number of seconds",
+                    timeout
+                ))
+            })
+        })
+        .transpose()?;
+
+    // Accept either a bare batch id or the status link returned on submission
+    let link = if batch.starts_with('/') {
+        batch.to_string()
+    } else {
+        format!("/batch_statuses?id={}", batch)
+    };
+
+    let mut batch_status = client.status(&link)?;
+
+    if let Some(timeout) = timeout {
+        let deadline = Instant::now() + Duration::from_secs(timeout);
+        let mut backoff = client.backoff();
+        while batch_status
+            .data
+            .iter()
+            .any(|status| status.status == "PENDING")
+            && Instant::now() < deadline
+            && backoff.wait()
+        {
+            batch_status = client.status(&batch_status.link)?;
+        }
+    }
+
+    if batch_status.data.is_empty() {
+        return Err(CliError::UserError(format!(
+            "No batch status was found for {}",
+            batch
+        )));
+    }
+
+    let message = batch_status
+        .data
+        .iter()
+        .map(describe_status)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(CommandOutcome::submitted(
+        None,
+        &batch_status.data[0],
+        message,
+    ))
+}
+
+fn describe_status(status: &Status) -> String {
+    let mut description = format!("Batch {} is {}", status.id, status.status);
+    for invalid_transaction in &status.invalid_transactions {
+        description.push_str(&format!(
+            "\n  Transaction {} is invalid: {}",
+            invalid_transaction.id, invalid_transaction.message
+        ));
+    }
+    description
+}
@@ -0,0 +1,117 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the CLI's configuration file, which holds defaults that are
+//! awkward to pass on every command
+
+use crate::error::CliError;
+
+use clap::ArgMatches;
+use serde_derive::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+/// The contents of the configuration file
+///
+/// ```yaml
+/// org_keys:
+///   <organization id>: <signing key name>
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub struct Config {
+    /// The name of the signing key to use for each organization, by
+    /// organization id
+    #[serde(default)]
+    org_keys: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Loads the configuration file given by `--config`, or else
+    /// `$HOME/.sawtooth/csrc.yaml` if it exists
+    ///
+    /// Without either, the configuration is empty.
+    ///
+    /// # Errors
+    ///
+    /// If the `--config` file does not exist, or the file cannot be read or
+    /// parsed, a `CliError::UserError` is returned.
+    pub fn from_args(args: &ArgMatches) -> Result<Config, CliError> {
+        let path = match args.value_of("config") {
+            Some(path) => PathBuf::from(path),
+            None => match default_path() {
+                Some(path) if path.exists() => path,
+                _ => return Ok(Config::default()),
+            },
+        };
+
+        let mut text = String::new();
+        File::open(&path)
+            .and_then(|mut file| file.read_to_string(&mut text))
+            .map_err(|err| {
+                CliError::UserError(format!(
+                    "Unable to read config file {}: {}",
+                    path.display(),
+                    err
+                ))
+            })?;
+        Config::parse(&text).map_err(|err| {
+            CliError::UserError(format!(
+                "Unable to parse config file {}: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    fn parse(text: &str) -> Result<Config, serde_yaml::Error> {
+        if text.trim().is_empty() {
+            return Ok(Config::default());
+        }
+        serde_yaml::from_str(text)
+    }
+
+    /// Returns the name of the signing key configured for the organization
+    pub fn org_key(&self, org_id: &str) -> Option<&str> {
+        self.org_keys.get(org_id).map(String::as_str)
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    // Nightly has deprecated env::home_dir() and suggests using https://crates.io/crates/dirs instead
+    #[allow(deprecated)]
+    env::home_dir().map(|mut path| {
+        path.push(".sawtooth");
+        path.push("csrc.yaml");
+        path
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn org_key_test() {
+        let config = Config::parse("org_keys:\n  org-1: alice\n  org-2: bob\n").unwrap();
+        assert_eq!(config.org_key("org-1"), Some("alice"));
+        assert_eq!(config.org_key("org-2"), Some("bob"));
+        assert_eq!(config.org_key("org-3"), None);
+
+        assert_eq!(Config::parse("").unwrap().org_key("org-1"), None);
+        assert!(Config::parse("org_keys: [alice]").is_err());
+    }
+}
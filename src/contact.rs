@@ -0,0 +1,137 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which validate the contact details of organizations
+
+use crate::error::CliError;
+
+use clap::ArgMatches;
+
+/// The ISO 639-1 two-letter language codes
+const LANGUAGE_CODES: &[&str] = &[
+    "aa", "ab", "ae", "af", "ak", "am", "an", "ar", "as", "av", "ay", "az", "ba", "be", "bg", "bh",
+    "bi", "bm", "bn", "bo", "br", "bs", "ca", "ce", "ch", "co", "cr", "cs", "cu", "cv", "cy", "da",
+    "de", "dv", "dz", "ee", "el", "en", "eo", "es", "et", "eu", "fa", "ff", "fi", "fj", "fo", "fr",
+    "fy", "ga", "gd", "gl", "gn", "gu", "gv", "ha", "he", "hi", "ho", "hr", "ht", "hu", "hy", "hz",
+    "ia", "id", "ie", "ig", "ii", "ik", "io", "is", "it", "iu", "ja", "jv", "ka", "kg", "ki", "kj",
+    "kk", "kl", "km", "kn", "ko", "kr", "ks", "ku", "kv", "kw", "ky", "la", "lb", "lg", "li", "ln",
+    "lo", "lt", "lu", "lv", "mg", "mh", "mi", "mk", "ml", "mn", "mr", "ms", "mt", "my", "na", "nb",
+    "nd", "ne", "ng", "nl", "nn", "no", "nr", "nv", "ny", "oc", "oj", "om", "or", "os", "pa", "pi",
+    "pl", "ps", "pt", "qu", "rm", "rn", "ro", "ru", "rw", "sa", "sc", "sd", "se", "sg", "si", "sk",
+    "sl", "sm", "sn", "so", "sq", "sr", "ss", "st", "su", "sv", "sw", "ta", "te", "tg", "th", "ti",
+    "tk", "tl", "tn", "to", "tr", "ts", "tt", "tw", "ty", "ug", "uk", "ur", "uz", "ve", "vi", "vo",
+    "wa", "wo", "xh", "yi", "yo", "za", "zh", "zu",
+];
+
+/// Returns the contact language code given on the command line
+///
+/// If the `contact_language_code` argument is omitted, the `--language`
+/// default (which may also be set through `CSRC_LANGUAGE`) is used instead.
+///
+/// The code itself is validated when the contact is built.
+///
+/// # Errors
+///
+/// If neither is given, a `CliError::InvalidInputError` is returned.
+pub fn language_code_from_args<'a>(args: &'a ArgMatches) -> Result<&'a str, CliError> {
+    args.value_of("contact_language_code")
+        .or_else(|| args.value_of("language"))
+        .ok_or_else(|| {
+            CliError::InvalidInputError(String::from(
+                "A contact language code is required. Pass it as an argument, or set a \
+                 default with --language or CSRC_LANGUAGE",
+            ))
+        })
+}
+
+/// Checks that the given language code is a lowercase ISO 639-1 code
+///
+/// # Errors
+///
+/// If the code is invalid, a `CliError::InvalidInputError` is returned.
+pub fn validate_language_code(language_code: &str) -> Result<(), CliError> {
+    if LANGUAGE_CODES.contains(&language_code) {
+        Ok(())
+    } else {
+        Err(CliError::InvalidInputError(format!(
+            "Invalid contact language code: {:?}. Expected a lowercase ISO 639-1 code, such as \"en\"",
+            language_code
+        )))
+    }
+}
+
+/// Returns the phone number to store for a contact
+///
+/// Unless `strict` is set (by `--strict-contact`), the number is stored as
+/// given. Otherwise it is normalized to E.164: spaces, dashes, dots and
+/// parentheses are removed, and what remains must be a '+' followed by 7 to
+/// 15 digits.
+///
+/// # Errors
+///
+/// If `strict` is set and the number is not a valid E.164 number, a
+/// `CliError::InvalidInputError` is returned.
+pub fn normalize_phone_number(phone_number: &str, strict: bool) -> Result<String, CliError> {
+    if !strict {
+        return Ok(phone_number.to_string());
+    }
+
+    let normalized: String = phone_number
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+        .collect();
+
+    let digits = normalized.trim_start_matches('+');
+    if normalized.starts_with('+')
+        && digits.len() == normalized.len() - 1
+        && (7..=15).contains(&digits.len())
+        && digits.chars().all(|c| c.is_ascii_digit())
+    {
+        Ok(normalized)
+    } else {
+        Err(CliError::InvalidInputError(format!(
+            "Invalid contact phone number: {:?}. Expected an E.164 number, such as +14155552671",
+            phone_number
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_language_code_test() {
+        assert!(validate_language_code("en").is_ok());
+        assert!(validate_language_code("zh").is_ok());
+        assert!(validate_language_code("EN").is_err());
+        assert!(validate_language_code("eng").is_err());
+        assert!(validate_language_code("").is_err());
+    }
+
+    #[test]
+    fn normalize_phone_number_test() {
+        assert_eq!(
+            normalize_phone_number("+1 (415) 555-2671", true).unwrap(),
+            "+14155552671"
+        );
+        assert_eq!(
+            normalize_phone_number("123-456-7890", false).unwrap(),
+            "123-456-7890"
+        );
+        assert!(normalize_phone_number("123-456-7890", true).is_err());
+        assert!(normalize_phone_number("+1 415 CALL NOW", true).is_err());
+        assert!(normalize_phone_number("++14155552671", true).is_err());
+    }
+}
@@ -26,9 +26,12 @@ pub enum CliError {
     IoError(std::io::Error),
     SigningError(signing::Error),
     ProtobufError(protobuf::ProtobufError),
-    HyperError(hyper::Error),
+    HttpError(reqwest::Error),
     InvalidTransactionError(String),
     InvalidInputError(String),
+    /// The user interrupted the CLI while it waited on a submitted batch; the
+    /// string says how to check on the batch
+    InterruptedError(String),
 }
 
 impl StdError for CliError {
@@ -38,9 +41,10 @@ impl StdError for CliError {
             CliError::IoError(ref err) => Some(err.borrow()),
             CliError::SigningError(ref err) => Some(err.borrow()),
             CliError::ProtobufError(ref err) => Some(err.borrow()),
-            CliError::HyperError(ref err) => Some(err.borrow()),
+            CliError::HttpError(ref err) => Some(err.borrow()),
             CliError::InvalidTransactionError(ref _s) => None,
             CliError::InvalidInputError(ref _s) => None,
+            CliError::InterruptedError(ref _s) => None,
         }
     }
 }
@@ -52,9 +56,10 @@ impl std::fmt::Display for CliError {
             CliError::IoError(ref err) => write!(f, "IoError: {}", err),
             CliError::SigningError(ref err) => write!(f, "SigningError: {}", err.to_string()),
             CliError::ProtobufError(ref err) => write!(f, "ProtobufError: {}", err.to_string()),
-            CliError::HyperError(ref err) => write!(f, "HyperError: {}", err.to_string()),
+            CliError::HttpError(ref err) => write!(f, "HttpError: {}", err.to_string()),
             CliError::InvalidTransactionError(ref s) => write!(f, "InvalidTransactionError: {}", s),
             CliError::InvalidInputError(ref s) => write!(f, "InvalidInput: {}", s),
+            CliError::InterruptedError(ref s) => write!(f, "Interrupted: {}", s),
         }
     }
 }
@@ -77,14 +82,8 @@ impl From<signing::Error> for CliError {
     }
 }
 
-impl From<hyper::Error> for CliError {
-    fn from(e: hyper::Error) -> Self {
-        CliError::HyperError(e)
-    }
-}
-
-impl From<hyper::error::UriError> for CliError {
-    fn from(err: hyper::error::UriError) -> Self {
-        CliError::UserError(format!("Invalid URL: {}", err))
+impl From<reqwest::Error> for CliError {
+    fn from(e: reqwest::Error) -> Self {
+        CliError::HttpError(e)
     }
 }
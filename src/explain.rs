@@ -0,0 +1,136 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which describe the state addresses transactions touch,
+//! to help diagnose rejected transactions
+
+use crate::error::CliError;
+use crate::settings;
+
+use common::addressing;
+use sawtooth_sdk::messages::batch::BatchList;
+use sawtooth_sdk::messages::transaction::TransactionHeader;
+use std::iter;
+
+/// The byte which follows the ConsenSource namespace in every address, ahead
+/// of the type prefix
+const RESERVED_SPACE: &str = "00";
+
+/// The type prefix of each kind of object in the ConsenSource namespace
+const TYPE_PREFIXES: [(&str, &str); 6] = [
+    ("agent", "00"),
+    ("certificate", "01"),
+    ("organization", "02"),
+    ("standard", "03"),
+    ("request", "04"),
+    ("assertion", "05"),
+];
+
+/// Returns a line for every input and output address of every transaction in
+/// the batch list, labeled with the kind of object stored at the address
+///
+/// # Errors
+///
+/// If a transaction header cannot be read, a `CliError::ProtobufError` is
+/// returned.
+pub fn describe_addresses(batch_list: &BatchList) -> Result<Vec<String>, CliError> {
+    let prefixes = address_prefixes();
+
+    let mut lines = vec![];
+    for batch in batch_list.get_batches() {
+        for txn in batch.get_transactions() {
+            let header: TransactionHeader = protobuf::parse_from_bytes(txn.get_header())?;
+            lines.push(format!("Transaction {}:", txn.get_header_signature()));
+            for (direction, addresses) in &[
+                ("input", header.get_inputs()),
+                ("output", header.get_outputs()),
+            ] {
+                for address in addresses.iter() {
+                    lines.push(format!(
+                        "  {:<6} {:<12} {}",
+                        direction,
+                        address_label(address, &prefixes),
+                        address
+                    ));
+                }
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Returns the address prefix of each kind of object the CLI writes
+///
+/// ConsenSource addresses are the family namespace, the reserved byte and the
+/// type prefix of the object, followed by a hash of its id. Settings live in
+/// the settings namespace.
+fn address_prefixes() -> Vec<(&'static str, String)> {
+    let namespace = addressing::get_family_namespace_prefix();
+
+    TYPE_PREFIXES
+        .iter()
+        .map(|(label, type_prefix)| {
+            (
+                *label,
+                format!("{}{}{}", namespace, RESERVED_SPACE, type_prefix),
+            )
+        })
+        .chain(iter::once((
+            "setting",
+            settings::SETTINGS_NAMESPACE.to_string(),
+        )))
+        .collect()
+}
+
+/// Returns the label of the kind of object stored at the address, or
+/// "unknown" if it has none of the given prefixes
+fn address_label(address: &str, prefixes: &[(&'static str, String)]) -> &'static str {
+    prefixes
+        .iter()
+        .filter(|(_, prefix)| address.starts_with(prefix.as_str()))
+        .max_by_key(|(_, prefix)| prefix.len())
+        .map_or("unknown", |(label, _)| *label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn address_label_test() {
+        let prefixes = address_prefixes();
+
+        let make_addresses: [(&str, fn(&str) -> String); 6] = [
+            ("agent", addressing::make_agent_address),
+            ("organization", addressing::make_organization_address),
+            ("certificate", addressing::make_certificate_address),
+            ("request", addressing::make_request_address),
+            ("standard", addressing::make_standard_address),
+            ("assertion", addressing::make_assertion_address),
+        ];
+        for (label, make_address) in make_addresses.iter() {
+            for id in &["02abc", "cert-1", "a"] {
+                assert_eq!(address_label(&make_address(id), &prefixes), *label);
+            }
+        }
+        assert_eq!(
+            address_label(
+                &settings::make_setting_address("sawtooth.settings.vote.proposals"),
+                &prefixes
+            ),
+            "setting"
+        );
+        assert_eq!(address_label("ffffff", &prefixes), "unknown");
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the handling of Ctrl-C
+//!
+//! An interrupt while the CLI waits for a submitted batch to be committed
+//! doesn't kill the process outright. Instead, the wait is abandoned with a
+//! `CliError::InterruptedError` saying which batch was submitted, so that the
+//! user can check on it later with the status command.
+
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The exit code used when the CLI is interrupted
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Whether the CLI is waiting on a submitted batch
+static WAITING: AtomicBool = AtomicBool::new(false);
+
+/// Whether Ctrl-C was pressed while waiting on a submitted batch
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Installs the Ctrl-C handler
+///
+/// Outside of `wait`, an interrupt exits immediately with
+/// `INTERRUPTED_EXIT_CODE`, as it would without the handler.
+pub fn install() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if WAITING.load(Ordering::SeqCst) {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        } else {
+            process::exit(INTERRUPTED_EXIT_CODE);
+        }
+    })
+}
+
+/// Runs `f`, during which an interrupt is recorded rather than exiting, to be
+/// picked up with `is_interrupted`
+pub fn wait<T, F: FnOnce() -> T>(f: F) -> T {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    WAITING.store(true, Ordering::SeqCst);
+    let result = f();
+    WAITING.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Returns whether Ctrl-C was pressed during the current `wait`
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
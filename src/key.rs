@@ -14,14 +14,29 @@
 
 //! Contains functions which assist with signing key management
 
+use crate::config::Config;
 use crate::error::CliError;
+use crate::transaction::{placeholder_signature, TransactionOptions};
 
-use sawtooth_sdk::signing::secp256k1::Secp256k1PrivateKey;
+use clap::ArgMatches;
+use sawtooth_sdk::signing::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
+use sawtooth_sdk::signing::{self, CryptoFactory, PrivateKey, PublicKey, Signer};
 use std::env;
 use std::fs::File;
-use std::io::prelude::*;
+use std::io::{self, prelude::*};
+use std::path::PathBuf;
 use users::get_current_username;
 
+/// The signing algorithm of the keys the CLI signs transactions with, and the
+/// only one `--signing-algorithm` accepts so far
+pub const SECP_256K1: &str = "secp256k1";
+
+/// The length of a compressed secp256k1 public key in hex
+const COMPRESSED_KEY_LEN: usize = 66;
+
+/// The length of an uncompressed secp256k1 public key in hex
+const UNCOMPRESSED_KEY_LEN: usize = 130;
+
 /// Return a signing key loaded from the user's environment
 ///
 /// This method attempts to load the user's key from a file.  The filename
@@ -42,9 +57,43 @@ use users::get_current_username;
 ///
 /// If a signing error occurs, a CliError::SigningError is returned.
 ///
+/// If the key file is missing or cannot be read, the error from
+/// `check_signing_key` is returned.
+///
 /// If a HOME or USER environment variable is required but cannot be
 /// retrieved from the environment, a CliError::VarError is returned.
 pub fn load_signing_key(name: Option<&str>) -> Result<Secp256k1PrivateKey, CliError> {
+    let private_key_filename = check_signing_key(name)?;
+
+    let mut f = File::open(&private_key_filename)?;
+
+    let mut contents = String::new();
+    f.read_to_string(&mut contents)?;
+
+    let key_str = match contents.lines().next() {
+        Some(k) => k,
+        None => {
+            return Err(CliError::UserError(format!(
+                "Empty key file: {}",
+                private_key_filename.display()
+            )));
+        }
+    };
+
+    Ok(Secp256k1PrivateKey::from_hex(&key_str)?)
+}
+
+/// Checks that the named signing key's file exists and can be read, returning
+/// its path
+///
+/// The key is located as by `load_signing_key`, which runs this check, and
+/// `check_key_args` runs it before a command does any work.
+///
+/// # Errors
+///
+/// If the key file is missing or cannot be read, or its location cannot be
+/// determined, a `CliError::UserError` is returned.
+pub fn check_signing_key(name: Option<&str>) -> Result<PathBuf, CliError> {
     let username: String = name
         .map(String::from)
         .ok_or_else(|| env::var("USER"))
@@ -70,27 +119,292 @@ pub fn load_signing_key(name: Option<&str>) -> Result<Secp256k1PrivateKey, CliEr
             p
         })?;
 
-    if !private_key_filename.as_path().exists() {
-        return Err(CliError::UserError(format!(
-            "No such key file: {}",
+    match File::open(&private_key_filename) {
+        Ok(_) => Ok(private_key_filename),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Err(CliError::UserError(format!(
+            "signing key '{}' not found at {}",
+            username,
             private_key_filename.display()
-        )));
+        ))),
+        Err(err) => Err(CliError::UserError(format!(
+            "signing key '{}' at {} cannot be read: {}",
+            username,
+            private_key_filename.display(),
+            err
+        ))),
+    }
+}
+
+/// Checks the signing keys named by `-k` and `--batcher-key`, if given, as
+/// `check_signing_key` does
+///
+/// This runs before any command, so that a mistyped key name fails before
+/// any payload is built or the REST API is contacted.
+///
+/// # Errors
+///
+/// If a named key cannot be read, the error from `check_signing_key` is
+/// returned.
+pub fn check_key_args(args: &ArgMatches) -> Result<(), CliError> {
+    for key in [args.value_of("key"), args.value_of("batcher_key")]
+        .iter()
+        .flatten()
+    {
+        check_signing_key(Some(*key))?;
     }
+    Ok(())
+}
 
-    let mut f = File::open(&private_key_filename)?;
+/// Return a signing key loaded from the user's environment, if a name is given
+///
+/// This is used for keys which are optional, such as a separate batcher key.
+/// The key is located in the same way as by `load_signing_key`.
+///
+/// # Errors
+///
+/// If the named key cannot be loaded, the error from `load_signing_key` is
+/// returned.
+pub fn load_optional_signing_key(
+    name: Option<&str>,
+) -> Result<Option<Secp256k1PrivateKey>, CliError> {
+    name.map(|name| load_signing_key(Some(name))).transpose()
+}
 
-    let mut contents = String::new();
-    f.read_to_string(&mut contents)?;
+/// Returns the name of the signing key for a command acting on behalf of the
+/// given organization
+///
+/// A key given with `-k` is used as is. Otherwise the key configured for the
+/// organization in the configuration file is used, if there is one, so that
+/// operators of several organizations sign with the right key. If neither is
+/// given, `None` is returned and `load_signing_key` falls back to the user's
+/// key.
+///
+/// # Errors
+///
+/// If the configuration file cannot be loaded, the error from
+/// `Config::from_args` is returned.
+pub fn signing_key_for_org(args: &ArgMatches, org_id: &str) -> Result<Option<String>, CliError> {
+    if let Some(key) = args.value_of("key") {
+        return Ok(Some(key.to_string()));
+    }
+    Ok(Config::from_args(args)?.org_key(org_id).map(String::from))
+}
 
-    let key_str = match contents.lines().next() {
-        Some(k) => k,
-        None => {
-            return Err(CliError::UserError(format!(
-                "Empty key file: {}",
-                private_key_filename.display()
+/// The keys a command signs its transactions and batches with
+///
+/// `Signer`s borrow their context and key, so the context and keys are held
+/// here for the duration of the command and signers are lent out of it.
+pub struct SigningContext {
+    context: Box<dyn signing::Context>,
+    private_key: Box<dyn PrivateKey>,
+    batcher_key: Option<Box<dyn PrivateKey>>,
+}
+
+impl SigningContext {
+    /// Loads the named signing key, and the batcher key named by the
+    /// options if there is one, as `load_signing_key` does
+    ///
+    /// If the options give a public key to leave the transactions unsigned
+    /// for, no key is loaded, and the signers lent out only name that public
+    /// key and sign with placeholders.
+    ///
+    /// # Errors
+    ///
+    /// If either key cannot be loaded, the error from `load_signing_key` is
+    /// returned. If the options select a signing algorithm other than
+    /// secp256k1, a `CliError::InvalidInputError` is returned, and if the
+    /// signing context cannot be created, a `CliError::SigningError` is.
+    pub fn new(
+        key: Option<&str>,
+        options: &TransactionOptions,
+    ) -> Result<SigningContext, CliError> {
+        // Keys are only ever loaded as secp256k1 keys
+        if options.signing_algorithm != SECP_256K1 {
+            return Err(CliError::InvalidInputError(format!(
+                "Unsupported signing algorithm: {}. Only {} is supported",
+                options.signing_algorithm, SECP_256K1
             )));
         }
-    };
 
-    Ok(Secp256k1PrivateKey::from_hex(&key_str)?)
+        if let Some(public_key) = &options.unsigned_public_key {
+            return Ok(SigningContext {
+                context: Box::new(UnsignedContext {
+                    public_key: public_key.clone(),
+                }),
+                private_key: Box::new(NoPrivateKey),
+                batcher_key: None,
+            });
+        }
+
+        Ok(SigningContext {
+            context: signing::create_context(&options.signing_algorithm)?,
+            private_key: Box::new(load_signing_key(key)?),
+            batcher_key: load_optional_signing_key(options.batcher_key.as_deref())?
+                .map(|batcher_key| Box::new(batcher_key) as Box<dyn PrivateKey>),
+        })
+    }
+
+    /// Returns a signer for the transaction signing key
+    pub fn signer(&self) -> Signer<'_> {
+        CryptoFactory::new(&*self.context).new_signer(&*self.private_key)
+    }
+
+    /// Returns a signer for the batcher key, if one was given
+    pub fn batcher(&self) -> Option<Signer<'_>> {
+        self.batcher_key
+            .as_ref()
+            .map(|batcher_key| CryptoFactory::new(&*self.context).new_signer(&**batcher_key))
+    }
+}
+
+/// The signing context of transactions and batches left unsigned with
+/// `--sign-later`
+///
+/// Headers name the public key given with `--public-key`, and every signature
+/// is a placeholder, as `unsign_batch_list` writes, so the private key never
+/// has to be on the machine building the batches.
+struct UnsignedContext {
+    public_key: String,
+}
+
+impl signing::Context for UnsignedContext {
+    fn get_algorithm_name(&self) -> &str {
+        SECP_256K1
+    }
+
+    fn sign(&self, message: &[u8], _key: &dyn PrivateKey) -> Result<String, signing::Error> {
+        Ok(placeholder_signature(message))
+    }
+
+    fn verify(
+        &self,
+        signature: &str,
+        message: &[u8],
+        _key: &dyn PublicKey,
+    ) -> Result<bool, signing::Error> {
+        Ok(signature == placeholder_signature(message))
+    }
+
+    fn get_public_key(
+        &self,
+        _private_key: &dyn PrivateKey,
+    ) -> Result<Box<dyn PublicKey>, signing::Error> {
+        Ok(Box::new(Secp256k1PublicKey::from_hex(&self.public_key)?))
+    }
+
+    fn new_random_private_key(&self) -> Result<Box<dyn PrivateKey>, signing::Error> {
+        Err(signing::Error::KeyGenError(String::from(
+            "No private key is generated for batches signed later",
+        )))
+    }
+}
+
+/// The private key lent to signers of an `UnsignedContext`, which never reads
+/// it
+struct NoPrivateKey;
+
+impl PrivateKey for NoPrivateKey {
+    fn get_algorithm_name(&self) -> &str {
+        SECP_256K1
+    }
+
+    fn as_hex(&self) -> String {
+        String::new()
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &[]
+    }
+}
+
+/// Returns the given secp256k1 public key in the compressed hex form used to
+/// identify agents and derive their addresses
+///
+/// Keys are accepted either compressed (66 hex characters starting with 02 or
+/// 03) or uncompressed (130 hex characters starting with 04).
+///
+/// # Errors
+///
+/// If the key is in neither form, a `CliError::InvalidInputError` is returned.
+pub fn normalize_public_key(public_key: &str) -> Result<String, CliError> {
+    let public_key = public_key.trim().to_lowercase();
+    let is_hex = public_key.chars().all(|c| c.is_ascii_hexdigit());
+
+    match (public_key.len(), public_key.get(..2)) {
+        (COMPRESSED_KEY_LEN, Some("02")) | (COMPRESSED_KEY_LEN, Some("03")) if is_hex => {
+            Ok(public_key)
+        }
+        (UNCOMPRESSED_KEY_LEN, Some("04")) if is_hex => {
+            // The compressed form is the x coordinate, prefixed by the parity
+            // of the y coordinate
+            let x = &public_key[2..66];
+            let y_is_odd = u8::from_str_radix(&public_key[128..], 16)
+                .map(|last_byte| last_byte % 2 == 1)
+                .unwrap_or(false);
+            Ok(format!("{}{}", if y_is_odd { "03" } else { "02" }, x))
+        }
+        _ => Err(CliError::InvalidInputError(format!(
+            "Invalid public key: {}. Please provide a secp256k1 public key in hex, \
+             either compressed (66 characters) or uncompressed (130 characters)",
+            public_key
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_public_key_test() {
+        let x = "ab".repeat(32);
+        let compressed = format!("02{}", x);
+        assert_eq!(normalize_public_key(&compressed).unwrap(), compressed);
+        assert_eq!(
+            normalize_public_key(&compressed.to_uppercase()).unwrap(),
+            compressed
+        );
+
+        // The prefix of the compressed key follows the parity of y
+        let even_y = format!("04{}{}", x, "cd".repeat(31) + "02");
+        let odd_y = format!("04{}{}", x, "cd".repeat(31) + "03");
+        assert_eq!(normalize_public_key(&even_y).unwrap(), format!("02{}", x));
+        assert_eq!(normalize_public_key(&odd_y).unwrap(), format!("03{}", x));
+
+        assert!(normalize_public_key(&format!("04{}", x)).is_err());
+        assert!(normalize_public_key(&format!("02{}", "zz".repeat(32))).is_err());
+        assert!(normalize_public_key("02ab").is_err());
+    }
+
+    #[test]
+    fn unsigned_signing_context_test() {
+        // The compressed generator point of secp256k1
+        let public_key = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let options = TransactionOptions {
+            unsigned_public_key: Some(public_key.to_string()),
+            ..TransactionOptions::default()
+        };
+
+        // No key is loaded, so a missing key is never looked for
+        let signing_context = SigningContext::new(Some("csrc-test-missing-key"), &options)
+            .expect("Failed to create an unsigned signing context");
+        let signer = signing_context.signer();
+        assert_eq!(signer.get_public_key().unwrap().as_hex(), public_key);
+        assert_eq!(
+            signer.sign(b"header").unwrap(),
+            placeholder_signature(b"header")
+        );
+        assert!(signing_context.batcher().is_none());
+    }
+
+    #[test]
+    fn check_signing_key_test() {
+        match check_signing_key(Some("csrc-test-missing-key")) {
+            Err(CliError::UserError(message)) => assert!(
+                message.starts_with("signing key 'csrc-test-missing-key' not found at "),
+                message
+            ),
+            result => panic!("Expected a missing key error, got {:?}", result),
+        }
+    }
 }
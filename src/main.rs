@@ -15,22 +15,40 @@
 #[macro_use]
 extern crate clap;
 
+mod batch_file;
 mod commands;
+mod config;
+mod contact;
 mod error;
+mod explain;
+mod interrupt;
 mod key;
+mod metrics;
+mod output;
+mod prompt;
+mod render;
+mod settings;
 mod submit;
 mod transaction;
 
-use clap::ArgMatches;
+use clap::{App, ArgMatches, Shell};
+use commands::CommandOutcome;
 use error::CliError;
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The name the CLI is installed under, which completions are generated for
+const BIN_NAME: &str = "csrc";
+
 fn main() {
     let args = parse_args();
 
-    let result = match args.subcommand() {
+    if let Err(err) = interrupt::install() {
+        eprintln!("Warning: unable to handle Ctrl-C: {}", err);
+    }
+
+    let result = key::check_key_args(leaf_matches(&args)).and_then(|_| match args.subcommand() {
         ("agent", Some(args)) => commands::agent::run(args),
         ("genesis", Some(args)) => commands::genesis::run(args),
         ("organization", Some(args)) => commands::organization::run(args),
@@ -38,41 +56,200 @@ fn main() {
         ("standard", Some(args)) => commands::standard::run(args),
         ("accreditation", Some(args)) => commands::accreditation::run(args),
         ("assertion", Some(args)) => commands::assertion::run(args),
+        ("status", Some(args)) => commands::status::run(args),
+        ("ping", Some(args)) => commands::ping::run(args),
+        ("info", Some(args)) => commands::info::run(args),
+        ("address", Some(args)) => commands::address::run(args),
+        ("key", Some(args)) => commands::key::run(args),
+        ("batch", Some(args)) => commands::batch::run(args),
+        ("completion", Some(args)) => run_completion_command(args),
         _ => Err(CliError::InvalidInputError(String::from(
             "Invalid subcommand. Pass --help for usage",
         ))),
-    };
+    });
 
-    std::process::exit(match result {
-        Ok(_) => 0,
-        Err(err) => {
-            eprintln!("Error: {}", err);
-            1
+    std::process::exit(
+        match result.and_then(|outcome| print_outcome(&outcome, &args)) {
+            Ok(_) => 0,
+            Err(err) => {
+                eprintln!("Error: {}", err);
+                match err {
+                    CliError::InterruptedError(_) => interrupt::INTERRUPTED_EXIT_CODE,
+                    _ => 1,
+                }
+            }
+        },
+    );
+}
+
+/// Returns the matches of the innermost subcommand given
+///
+/// Global arguments given after a subcommand are only visible on its matches.
+fn leaf_matches<'a, 'b>(args: &'b ArgMatches<'a>) -> &'b ArgMatches<'a> {
+    let mut args = args;
+    while let (_, Some(sub_args)) = args.subcommand() {
+        args = sub_args;
+    }
+    args
+}
+
+/// Prints the outcome of a command in the format selected by `--format`
+fn print_outcome(outcome: &CommandOutcome, args: &ArgMatches) -> Result<(), CliError> {
+    let args = leaf_matches(args);
+
+    match (args.value_of("format"), &outcome.listing) {
+        (Some("json"), _) => println!("{}", output::to_json(outcome, args.is_present("pretty"))?),
+        (Some("table"), Some(listing)) => {
+            println!("{}", listing.table.render(max_col_width_from_args(args)?))
         }
-    });
+        _ => {
+            if !outcome.message.is_empty() {
+                println!("{}", outcome.message);
+            }
+            match outcome.transaction_ids.as_slice() {
+                [] => (),
+                [transaction_id] => println!("Transaction id: {}", transaction_id),
+                transaction_ids => println!("Transaction ids:\n  {}", transaction_ids.join("\n  ")),
+            }
+            if let (Some(seconds), false) = (outcome.commit_seconds, args.is_present("quiet")) {
+                println!("Committed in {:.1}s", seconds);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the width `--format table` truncates cells to, given by
+/// `--max-col-width`
+///
+/// # Errors
+///
+/// If the width is not a positive number, a `CliError::InvalidInputError` is
+/// returned.
+fn max_col_width_from_args(args: &ArgMatches) -> Result<Option<usize>, CliError> {
+    args.value_of("max_col_width")
+        .map(|width| match width.parse::<usize>() {
+            Ok(width) if width > 0 => Ok(width),
+            _ => Err(CliError::InvalidInputError(format!(
+                "Invalid maximum column width: {}. Please provide a positive number of characters",
+                width
+            ))),
+        })
+        .transpose()
+}
+
+/// Writes the completion script for the given shell to stdout
+fn run_completion_command(args: &ArgMatches) -> Result<CommandOutcome, CliError> {
+    let shell = commands::require_arg(args, "shell")?
+        .parse::<Shell>()
+        .map_err(CliError::InvalidInputError)?;
+    build_app().gen_completions_to(BIN_NAME, shell, &mut std::io::stdout());
+    Ok(CommandOutcome::completed(String::new()))
 }
 
 fn parse_args<'a>() -> ArgMatches<'a> {
-    let app = clap_app!(csrc =>
+    build_app().get_matches()
+}
+
+fn build_app() -> App<'static, 'static> {
+    clap_app!(csrc =>
         (name: APP_NAME)
         (version: VERSION)
         (about: "Consensource CLI")
         (@setting SubcommandRequiredElseHelp)
+        (@arg family_version: --("family-version") +takes_value +global
+         "Transaction family version to set on transaction headers. Defaults to the version of the consensource-common crate the CLI was built with; override it only when the network's transaction processor expects a different version")
+        (@arg format: --format +takes_value +global possible_values(&["human", "json", "table"])
+         "Format of the command's result: human (default), json, or table, which shows the objects found by list commands as a table and is otherwise the same as human")
+        (@arg pretty: --pretty +global "Pretty-print JSON output")
+        (@arg max_col_width: --("max-col-width") +takes_value +global
+         "With --format table, truncate cells longer than this many characters with an ellipsis")
+        (@arg config: --config +takes_value +global env("CSRC_CONFIG")
+         "Configuration file, which may map organization ids to the names of the signing keys to use for them when -k is not given. Defaults to $HOME/.sawtooth/csrc.yaml, if it exists")
+        (@arg language: --language +takes_value +global env("CSRC_LANGUAGE")
+         "Default ISO 639-1 language code for organization contacts")
+        (@arg strict_contact: --("strict-contact") +global
+         "Validate organization contact phone numbers, storing them in E.164 format")
+        (@arg api_prefix: --("api-prefix") +takes_value +global visible_alias("gateway-prefix")
+         "Path the REST API is mounted under, for deployments behind a reverse proxy. Defaults to /api")
+        (@arg insecure: --insecure +global
+         "Don't verify the TLS certificate of an https --url, for development clusters with self-signed certificates")
+        (@arg header: --header +takes_value +multiple number_of_values(1) +global
+         "Extra HTTP header to send with every REST API request, as \"Name: Value\". May be given more than once")
+        (@arg trace_file: --("trace-file") +takes_value +global
+         "File to append a JSON record of every submitted batch to, for auditing")
+        (@arg error_log: --("error-log") +takes_value +global
+         "File to append a JSON line to for every transaction of a rejected batch")
+        (@arg metrics_url: --("metrics-url") +takes_value +global
+         "URL of a Prometheus Pushgateway to push the outcome of batch commands to, under the job csrc")
+        (@arg quiet: -q --quiet +global "Don't report progress while processing batch files")
+        (@arg chunk_report: --("chunk-report") +takes_value +global
+         "Number of batch file entries to process between progress reports. Defaults to 500")
+        (@arg no_wait: --("no-wait") +global
+         "Submit batches without waiting for them to be committed. Use the status command to check on them later")
+        (@arg wait_for_block: --("wait-for-block") +global conflicts_with[no_wait]
+         "Once a batch is committed, find the block it was included in and report its number and id")
+        (@arg max_body_size: --("max-body-size") +takes_value +global
+         "Largest REST API response to read, in bytes or with a K, M or G suffix, so that listing a huge collection fails instead of exhausting memory. Defaults to 64M")
+        (@arg poll_interval: --("poll-interval") +takes_value +global
+         "Milliseconds to wait between batch status checks, varied randomly by up to 20% so that clients sharing a validator do not poll in lockstep. Defaults to 250")
+        (@arg max_retries: --("max-retries") +takes_value +global
+         "Maximum number of times to recheck the status of a batch before giving up on it. Unlimited by default")
+        (@arg batcher_key: --("batcher-key") +takes_value +global
+         "Name of a signing key used to sign batches, when the batcher is a different identity than the transaction signer")
+        (@arg signing_algorithm: --("signing-algorithm") +takes_value +global possible_values(&[key::SECP_256K1])
+         "Algorithm transactions and batches are signed with. Only secp256k1 is supported for now")
+        (@arg explain: --explain +global
+         "Print the state addresses each transaction reads and writes, labeled with the kind of object stored there, before submitting it")
+        (@arg output_batch: --("output-batch") +takes_value +global
+         "Write batches to this file instead of submitting them")
+        (@arg sign_later: --("sign-later") +global requires[output_batch signer_public_key] conflicts_with[batcher_key]
+         "With --output-batch, leave the batches unsigned, to be signed with `csrc batch sign`. No private key is loaded: the headers name the key given with --public-key, and only that key can sign them")
+        (@arg signer_public_key: --("public-key") +takes_value +global requires[sign_later]
+         "With --sign-later, the public key of the key which will sign the batches, as compressed or uncompressed secp256k1 hex")
+        (@arg batch_manifest: --("batch-manifest") +global
+         "When writing a batch file, with genesis, --output-batch or `batch sign`, also write <file>.json listing each batch's id, transaction ids and SHA-256, and the SHA-256 of the whole file")
+        (@arg batch_format: --("batch-format") +takes_value +global possible_values(&["protobuf", "base64", "json"])
+         "How batch files are written by --output-batch and genesis, and read by `batch sign` and genesis --append: protobuf, the bytes `sawtooth batch submit` reads; base64, those bytes base64 encoded; or json, the batch and transaction ids with base64 headers and payloads. Defaults to protobuf")
         (@subcommand agent =>
             (about: "manage the agent")
             (@subcommand create =>
                 (about: "create an agent")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg name: +required "Name of the agent to be created")
+                (@arg repeat: --repeat +takes_value
+                 "For load testing, create this many agents instead, named after the given name with a numbered suffix and each signed by a new random key which is not kept. The signing key batches them. Reports how many were committed, and how long it took")
+                (@arg batch_size: --("batch-size") +takes_value requires[repeat]
+                 "Number of agents to submit per batch with --repeat. Defaults to 1000")
                 (@arg key: -k --key +takes_value "Signing key name")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
             (@subcommand authorize =>
                 (about: "authorize an agent")
-                (@arg authorize_agent: +required "Pub key of the agent we are authorizing")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
+                (@arg authorize_agent: +required
+                 "Pub key of the agent we are authorizing, as compressed or uncompressed secp256k1 hex")
                 (@arg org_id: +required "Organization agent is associated with")
-                (@arg role: +required "Role of the agent: 1 (ADMIN) or 2 (TRANSACTOR)")
+                (@arg role: +required +multiple
+                 "Role of the agent: 1 (ADMIN) or 2 (TRANSACTOR). Give both to grant both roles in one batch")
                 (@arg key: -k --key +takes_value "Signing key of the admin doing the authoriation")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+                (@arg check: --check "Check that the organization exists before submitting the authorization")
+            )
+            (@subcommand batch_authorize =>
+                (about: "authorize a batch of agents")
+                (@arg filepath: +required "File path to read JSON or YAML data of agent authorizations. Use - to read from stdin")
+                (@arg input: --input +takes_value possible_values(&["json", "yaml"])
+                 "Format of the input file. Detected from the file extension, or else the contents, if not given")
+                (@arg key: -k --key +takes_value "Signing key of the admin doing the authoriation")
+                (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+                (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+                 "Maximum number of invalid transactions to print if the batch is rejected")
+                (@arg retry_failed: --("retry-failed") +takes_value
+                 "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
             )
         )
 
@@ -82,51 +259,92 @@ fn parse_args<'a>() -> ArgMatches<'a> {
              "Processes the input and generates the transactions, but does not generate the output")
             (@arg output: -o --output +takes_value default_value("consensource-genesis.batch")
              "Output file for the resulting batches")
+            (@arg append: --append
+             "Appends the resulting batches to the output file if it already exists, instead of overwriting it")
+            (@arg max_batches_per_file: --("max-batches-per-file") +takes_value conflicts_with[append]
+             "Split the output across numbered files, such as consensource-genesis.001.batch, of at most this many batches each, keeping the batches in order. Only applies if there are more batches than this")
             (@arg descriptor: -g --("genesis-descriptor") +takes_value default_value("genesis.yaml")
              "The genesis descriptor yaml file")
-            (@arg keys_directory: -K --("keys-directory") +takes_value
-             "An optional directory to write out the keys used when generating the various transactions"))
+            (@arg keys_directory: -K --("keys-directory") +takes_value visible_alias("output-dir")
+             "An optional directory to write out the keys used when generating the various transactions. Created if it does not exist")
+            (@arg seed: --seed +takes_value
+             "Hex seed from which the generated keys are derived, so that the same seed always yields the same keys. Anyone who knows the seed can recover the keys: use this for test networks only, never in production. Batches still get fresh nonces and timestamps")
+            (@arg manifest: --manifest +takes_value
+             "An optional file to write a JSON manifest of the generated agents' emails, organizations and public keys to. Use - for stdout")
+            (@arg settings_key: --("settings-key") +takes_value
+             "Name of the signing key for the descriptor's settings proposals. A key is generated if omitted")
+            (@arg check_existing: --("check-existing") requires[url seed]
+             "Leave out the agents, organizations, standards and accreditations which the REST API given by --url already knows of, to top up a running network. Organizations are matched by name and type, and agents by public key, so the --seed the network was created with must be given")
+            (@arg submit: --submit requires[url]
+             "Also submit the generated batches to the REST API given by --url, one at a time, waiting for each to be committed. Batches appended to with --append are not resubmitted")
+            (@arg assume_yes: -y --("assume-yes") "Skip the confirmation prompt before submitting the batches")
+            (@arg url: --url +takes_value "URL to the ConsenSource REST API"))
 
         (@subcommand organization =>
             (about: "manage the organization")
             (@subcommand create =>
                 (about: "create an organization")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg name: +required "Name of the organization to be created")
                 (@arg org_type: +required "Type of the organization to be created:
-                1 (CERTIFYING_BODY), 2 (STANDARDS_BODY), 3 (FACTORY), or 4 (INGESTION)")
+                1 (CERTIFYING_BODY), 2 (STANDARDS_BODY), 3 (FACTORY), or 4 (INGESTION).
+                A factory also needs --street_address, --city and --country; the other types need only a name and contact")
                 (@arg contact_name: +required "Name of the organization's contact")
                 (@arg contact_phone_number: +required "Phone number of the organization's contact")
-                (@arg contact_language_code: +required "Language of the organization's contact")
+                (@arg contact_language_code: "Language of the organization's contact, as an ISO 639-1 code. Defaults to --language")
                 (@arg street_address: --street_address +takes_value "Street address of the organization's contact")
+                (@arg street_line_2: --street_line_2 +takes_value "Second line of the factory's street address")
                 (@arg city: --city +takes_value "City of the factory")
+                (@arg state_province: --state_province +takes_value "State or province of the factory")
+                (@arg postal_code: --postal_code +takes_value "Postal code of the factory")
                 (@arg country: --country +takes_value "Country of the factory")
+                (@arg verify: --verify
+                 "After the organization is committed, check that the REST API reports it with the name and type it was created with")
                 (@arg key: -k --key +takes_value "Signing key name")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
             (@subcommand update =>
                 (about: "update an organization")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg id: +required "ID of the organization to be updated")
                 (@arg name: "New name of the organization")
                 (@arg contact_name:  "New name of the organization's contact")
                 (@arg contact_phone_number:  "New phone number of the organization's contact")
-                (@arg contact_language_code:  "New language of the organization's contact")
+                (@arg contact_language_code:  "New language of the organization's contact, as an ISO 639-1 code")
                 (@arg street_address: --street_address +takes_value "New street address of the organization's contact")
                 (@arg city: --city +takes_value "New city of the factory")
                 (@arg country: --country +takes_value "New country of the factory")
+                (@arg org_type: --("org-type") +takes_value "New type of the organization. The transaction family does not support changing the type, so this is always rejected")
                 (@arg key: -k --key +takes_value "Signing key name")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+                (@arg assume_yes: -y --("assume-yes") "Skip the confirmation prompt before modifying state on chain")
+                (@arg dry_run: --("dry-run")
+                 "Show how the update would change the organization, without submitting it")
             )
             (@subcommand batch_update =>
                 (about: "create a batch of organization updates")
-                (@arg filepath: +required "File path to read JSON data of org updates")
+                (@arg filepath: +required "File path to read JSON or YAML data of org updates. Use - to read from stdin")
+                (@arg input: --input +takes_value possible_values(&["json", "yaml"])
+                 "Format of the input file. Detected from the file extension, or else the contents, if not given")
+                (@arg from_csv: --("from-csv") conflicts_with[input]
+                 "Read the org updates as CSV, with the columns id, name, contact_name, contact_phone_number, contact_language_code, street_address, city and country. Blank cells leave the field unchanged")
                 (@arg key: -k --key +takes_value "Signing key name")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+                (@arg assume_yes: -y --("assume-yes") "Skip the confirmation prompt before modifying state on chain")
+                (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+                 "Maximum number of invalid transactions to print if the batch is rejected")
+                (@arg retry_failed: --("retry-failed") +takes_value
+                 "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
             )
         )
         (@subcommand certificate =>
             (about: "manage the certificate")
             (@subcommand create =>
                 (about: "issue a certificate")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg id: +required "Id of the certificate to be issued")
                 (@arg certifying_body_id: +required "Certifying body that is issuing the certificate")
                 (@arg factory_id: +required "Factory the certificate is being issued to")
@@ -136,41 +354,81 @@ fn parse_args<'a>() -> ArgMatches<'a> {
                 2 (INDEPENDENT):  it means the IssueCertificateAction is not associated with a request made by a factory.
                 The field factory_name must passed as well")
                 (@arg request_id: --request_id +takes_value "Id of the certificate request made by the factory")
-                (@arg standard_id: +required "Standard that this certificate is for")
+                (@arg standard_id: +required "Standard that this certificate is for. A certificate covers a single standard")
                 (@arg cert_data: -cd --cert_data +takes_value +multiple "Optional cert data")
-                (@arg valid_from: +required "Start timestamp of the certificate")
-                (@arg valid_to: +required "End timestamp of the certificate")
+                (@arg valid_from: +required "Start timestamp of the certificate. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
+                (@arg valid_to: "End timestamp of the certificate. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d. Required unless --valid-for is given")
+                (@arg valid_for: --("valid-for") +takes_value
+                 "How long the certificate is valid from its start timestamp, instead of valid_to, e.g. 365d or 52w. Units: s, m, h, d, w")
                 (@arg key: -k --key +takes_value "Signing key name")
+                (@arg preflight: --preflight
+                 "Check that the factory and standard exist before submitting the certificate")
+                (@arg replace_existing: --("replace-existing")
+                 "Submit the certificate even if a certificate with this id already exists, overwriting it")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
             (@subcommand update =>
                 (about: "update a certificate")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg id: +required "Id of the certificate to be issued")
                 (@arg certifying_body_id: +required "Certifying body that is issuing the certificate")
-                (@arg valid_from: +required "Start timestamp of the certificate")
-                (@arg valid_to: +required "End timestamp of the certificate")
+                (@arg valid_from: +required "Start timestamp of the certificate. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
+                (@arg valid_to: +required "End timestamp of the certificate. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
                 (@arg cert_data: -cd --cert_data +takes_value +multiple "Optional cert data")
+                (@arg merge_cert_data: --("merge-cert-data")
+                 "Keep the certificate's existing cert data, setting the given fields over it, instead of replacing it")
+                (@arg request_id: --request_id +takes_value "Id of the certificate request, if the certificate was issued FROM_REQUEST")
                 (@arg key: -k --key +takes_value "Signing key name")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+                (@arg assume_yes: -y --("assume-yes") "Skip the confirmation prompt before modifying state on chain")
             )
             (@subcommand batch_update =>
               (about: "update a batch of certificates")
-              (@arg filepath: +required "File path to read JSON data of certificate updates")
+              (@arg filepath: +required "File path to read JSON or YAML data of certificate updates. Use - to read from stdin")
+              (@arg input: --input +takes_value possible_values(&["json", "yaml"])
+               "Format of the input file. Detected from the file extension, or else the contents, if not given")
               (@arg key: -k --key +takes_value "Signing key name")
               (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+              (@arg assume_yes: -y --("assume-yes") "Skip the confirmation prompt before modifying state on chain")
+              (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+               "Maximum number of invalid transactions to print if the batch is rejected")
+              (@arg retry_failed: --("retry-failed") +takes_value
+               "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
+            )
+            (@subcommand list =>
+                (about: "list certificates")
+                (@arg expiring_within: --("expiring-within") +takes_value
+                 "Only list certificates which expire within the given duration from now, soonest first, e.g. 30d. Units: s, m, h, d, w")
+                (@arg since: --since +takes_value
+                 "Only list certificates whose valid_from is at or after this time, given as seconds since Unix epoch or YYYY-MM-DD")
+                (@arg until: --until +takes_value
+                 "Only list certificates whose valid_from is at or before this time, given as seconds since Unix epoch or YYYY-MM-DD")
+                (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+            )
+            (@subcommand verify =>
+                (about: "check that a certificate is currently valid, and was issued under an accreditation for its standard")
+                (@arg id: +required "Certificate ID")
+                (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
         )
         (@subcommand standard =>
             (about: "manage standards")
             (@subcommand create =>
                 (about: "create a new standard")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg name: +required "Name of the standard")
                 (@arg version: +required "Current version of the standard.")
                 (@arg description: +required "Short description of the standard")
                 (@arg link: +required "Link to the standard's documentation.")
                 (@arg organization_id: +required "Id of the organization creating the standard")
-                (@arg approval_date: +required "Date the standard is officially issued. Format: seconds since Unix epoch")
+                (@arg approval_date: +required "Date the standard is officially issued. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
                 (@arg key: -k --key +takes_value "Signing key name")
+                (@arg replace_existing: --("replace-existing")
+                 "Submit the standard even if a standard with this name already exists, overwriting it")
+                (@arg print_id_only: --("print-id-only")
+                 "Print the id the standard would be created with, which is derived from its name, without signing or submitting anything")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
         )
@@ -178,14 +436,28 @@ fn parse_args<'a>() -> ArgMatches<'a> {
             (about: "manage accreditations")
             (@subcommand create =>
                 (about: "accredit an certifying body to an standard")
+                (@arg payload_only: --("payload-only")
+                 "Print the payload this command would submit, decoded, without signing or submitting it")
                 (@arg certifying_body_id: +required "Id of the certifying body that is being accredited.")
                 (@arg standards_body_id: +required "Id of the standards body that is issuing the accreditation.")
                 (@arg standard_id: +required "Id of the standard that the certifying body is being accredited for.")
-                (@arg valid_from: +required "Time the accreditation was issued. Format: seconds since Unix epoch")
-                (@arg valid_to: +required "When the accreditation will become invalid. Format: seconds since Unix epoch")
+                (@arg valid_from: +required "Time the accreditation was issued. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
+                (@arg valid_to: +required "When the accreditation will become invalid. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
                 (@arg key: -k --key +takes_value "Signing key name")
                 (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
+            (@subcommand batch_create =>
+                (about: "accredit a batch of certifying bodies to standards")
+                (@arg filepath: +required "File path to read a JSON or YAML array of accreditations from, each with certifying_body_id, standards_body_id, standard_id, valid_from and valid_to. Use - to read from stdin")
+                (@arg input: --input +takes_value possible_values(&["json", "yaml"])
+                 "Format of the input file. Detected from the file extension, or else the contents, if not given")
+                (@arg key: -k --key +takes_value "Signing key name")
+                (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+                (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+                 "Maximum number of invalid transactions to print if the batch is rejected")
+                (@arg retry_failed: --("retry-failed") +takes_value
+                 "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
+            )
         )
         (@subcommand assertion =>
           (about: "manage an assertion by type")
@@ -193,11 +465,13 @@ fn parse_args<'a>() -> ArgMatches<'a> {
             (about: "manage a factory assertion")
             (@subcommand create =>
               (about: "create an factory assertion")
+              (@arg payload_only: --("payload-only")
+               "Print the payload this command would submit, decoded, without signing or submitting it")
               (@arg asserter_organization_id: +required "ID of the organization the asserter belongs to")
               (@arg name: +required "Name of the organization to be created")
-              (@arg contact_name: +required "Name of the organization's contact")
-              (@arg contact_phone_number: +required "Phone number of the organization's contact")
-              (@arg contact_language_code: +required "Language of the organization's contact")
+              (@arg contact_name: "Name of the organization's contact. The contact is optional, but needs both a name and a phone number")
+              (@arg contact_phone_number: "Phone number of the organization's contact")
+              (@arg contact_language_code: "Language of the organization's contact, as an ISO 639-1 code. Defaults to --language")
               (@arg street_address: --street_address +takes_value "Street address of the organization's contact")
               (@arg city: --city +takes_value "City of the factory")
               (@arg state_province: --state_province +takes_value "State or province of the factory")
@@ -209,42 +483,65 @@ fn parse_args<'a>() -> ArgMatches<'a> {
             )
             (@subcommand batch_create =>
               (about: "create a batch of factory assertions")
-              (@arg filepath: +required "File path to read JSON data of factories")
+              (@arg filepath: +required "File path to read JSON or YAML data of factories. Use - to read from stdin")
+              (@arg input: --input +takes_value possible_values(&["json", "yaml"])
+               "Format of the input file. Detected from the file extension, or else the contents, if not given")
+              (@arg json_lines: --("json-lines") conflicts_with[input]
+               "Read one JSON factory per line, with its factory id under \"id\", submitting them --batch-size at a time instead of holding the whole file in memory")
+              (@arg batch_size: --("batch-size") +takes_value requires[json_lines]
+               "Number of factories to submit per batch with --json-lines. Defaults to 1000")
               (@arg key: -k --key +takes_value "Signing key name")
               (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+              (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+               "Maximum number of invalid transactions to print if the batch is rejected")
+              (@arg retry_failed: --("retry-failed") +takes_value
+               "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
             )
           )
           (@subcommand certificate =>
             (about: "manage a certificate assertion")
             (@subcommand create =>
               (about: "create an certificate assertion")
+              (@arg payload_only: --("payload-only")
+               "Print the payload this command would submit, decoded, without signing or submitting it")
+              (@setting AllowMissingPositional)
               (@arg asserter_organization_id: +required "ID of the organization the asserter belongs to")
               (@arg factory_id: +required "Factory the certificate is being issued to")
-              (@arg valid_from: +required "Start timestamp of the certificate")
-              (@arg valid_to: +required "End timestamp of the certificate")
-              (@arg standard_id: +required "Standard that this certificate is for")
+              (@arg valid_from: +required "Start timestamp of the certificate. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
+              (@arg valid_to: "End timestamp of the certificate. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d. Required unless --valid-for is given")
+              (@arg standard_id: +required "Standard that this certificate is for. A certificate covers a single standard")
               (@arg id: --id +takes_value "Certificate ID (if none provided, then a randomly generated ID will be used)")
               (@arg cert_data: -cd --cert_data +takes_value +multiple "Optional cert data")
+              (@arg valid_for: --("valid-for") +takes_value
+               "How long the certificate is valid from its start timestamp, instead of valid_to, e.g. 365d or 52w. Units: s, m, h, d, w")
               (@arg key: -k --key +takes_value "Signing key name")
               (@arg url: --url +takes_value "URL to the ConsenSource REST API")
             )
             (@subcommand batch_create =>
               (about: "create a batch of certificate assertions")
-              (@arg filepath: +required "File path to read JSON data of certificates")
+              (@arg filepath: +required "File path to read JSON or YAML data of certificates. Use - to read from stdin")
+              (@arg input: --input +takes_value possible_values(&["json", "yaml"])
+               "Format of the input file. Detected from the file extension, or else the contents, if not given")
               (@arg key: -k --key +takes_value "Signing key name")
               (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+              (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+               "Maximum number of invalid transactions to print if the batch is rejected")
+              (@arg retry_failed: --("retry-failed") +takes_value
+               "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
             )
           )
           (@subcommand standard =>
             (about: "manage a standards assertion")
             (@subcommand create =>
               (about: "create an standards assertion")
+              (@arg payload_only: --("payload-only")
+               "Print the payload this command would submit, decoded, without signing or submitting it")
               (@arg asserter_organization_id: +required "ID of the organization the asserter belongs to")
               (@arg name: +required "Name of the standard")
               (@arg version: +required "Current version of the standard.")
               (@arg description: +required "Short description of the standard")
               (@arg link: +required "Link to the standard's documentation.")
-              (@arg approval_date: +required "Date the standard is officially issued. Format: seconds since Unix epoch")
+              (@arg approval_date: +required "Date the standard is officially issued. Seconds since Unix epoch, YYYY-MM-DD, or now with an optional offset such as now+30d")
               (@arg id: --id +takes_value "Standard ID (if none provided, then a randomly generated ID will be used)")
               (@arg key: -k --key +takes_value "Signing key name")
               (@arg url: --url +takes_value "URL to the ConsenSource REST API")
@@ -252,11 +549,96 @@ fn parse_args<'a>() -> ArgMatches<'a> {
           )
           (@subcommand transfer =>
               (about: "transfer an assertion of any type")
+              (@arg payload_only: --("payload-only")
+               "Print the payload this command would submit, decoded, without signing or submitting it")
               (@arg id: +required "Assertion ID")
+              (@arg new_owner: --("new-owner") +takes_value
+               "Public key of the agent the assertion is transferred to. Defaults to the signer's public key")
               (@arg key: -k --key +takes_value "Signing key name")
               (@arg url: --url +takes_value "URL to the ConsenSource REST API")
           )
+          (@subcommand batch_transfer =>
+              (about: "transfer a batch of assertions")
+              (@arg filepath: +required "File path to read assertion ids from, as a JSON array or one per line. Use - to read from stdin")
+              (@arg new_owner: --("new-owner") +takes_value
+               "Public key of the agent the assertions are transferred to. Defaults to the signer's public key")
+              (@arg key: -k --key +takes_value "Signing key name")
+              (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+              (@arg max_invalid_display: --("max-invalid-display") +takes_value default_value("10")
+               "Maximum number of invalid transactions to print if the batch is rejected")
+              (@arg retry_failed: --("retry-failed") +takes_value
+               "Error log written with --error-log by a previous run. Only the entries which failed in that run are submitted")
+          )
+          (@subcommand list =>
+              (about: "list assertions")
+              (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+          )
+          (@subcommand show =>
+              (about: "show one or more assertions")
+              (@arg id: +required +multiple "Assertion ID")
+              (@arg concurrency: --concurrency +takes_value default_value("4")
+               "Maximum number of assertions to fetch from the REST API at once")
+              (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+          )
         )
-    );
-    app.get_matches()
+        (@subcommand address =>
+            (about: "compute the state address of an object, for debugging")
+            (@subcommand agent =>
+                (about: "compute the address of an agent")
+                (@arg public_key: +required
+                 "Public key of the agent, as compressed or uncompressed secp256k1 hex")
+            )
+            (@subcommand organization =>
+                (about: "compute the address of an organization")
+                (@arg id: +required "Id of the organization")
+            )
+            (@subcommand certificate =>
+                (about: "compute the address of a certificate")
+                (@arg id: +required "Id of the certificate")
+            )
+            (@subcommand standard =>
+                (about: "compute the address of a standard")
+                (@arg id: +required "Id of the standard")
+            )
+            (@subcommand assertion =>
+                (about: "compute the address of an assertion")
+                (@arg id: +required "Id of the assertion")
+            )
+        )
+        (@subcommand key =>
+            (about: "inspect signing keys")
+            (@subcommand show =>
+                (about: "show the public key which identifies an agent, in compressed secp256k1 hex")
+                (@arg key: -k --key +takes_value "Name of the signing key")
+            )
+        )
+        (@subcommand batch =>
+            (about: "work with batch files written by --output-batch")
+            (@subcommand sign =>
+                (about: "sign a batch file written with --output-batch --sign-later")
+                (@arg input: +required "Unsigned batch file")
+                (@arg output: +required "File to write the signed batches to")
+                (@arg key: -k --key +takes_value "Name of the signing key")
+            )
+        )
+        (@subcommand completion =>
+            (about: "print a shell completion script, e.g. `csrc completion bash > /etc/bash_completion.d/csrc`")
+            (@arg shell: +required possible_values(&["bash", "zsh", "fish", "powershell", "elvish"])
+             "Shell to generate the completion script for")
+        )
+        (@subcommand info =>
+            (about: "show the transaction family, namespace and defaults the CLI targets")
+        )
+        (@subcommand ping =>
+            (about: "check that the REST API is reachable")
+            (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+        )
+        (@subcommand status =>
+            (about: "check the status of a previously submitted batch")
+            (@arg batch: +required "Id of the batch, or the batch status link returned on submission")
+            (@arg timeout: -t --timeout +takes_value
+             "Seconds to keep polling while the batch is pending (checks once if omitted)")
+            (@arg url: --url +takes_value "URL to the ConsenSource REST API")
+        )
+    )
 }
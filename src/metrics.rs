@@ -0,0 +1,135 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains the metrics of batch commands, as pushed to a Prometheus
+//! Pushgateway with `--metrics-url`
+
+use crate::submit::Status;
+
+use sawtooth_sdk::messages::batch::BatchList;
+use std::time::Duration;
+
+/// The Pushgateway job the metrics are grouped under
+const JOB_NAME: &str = "csrc";
+
+/// The outcome of submitting a batch list
+#[derive(Debug, PartialEq)]
+pub struct BatchMetrics {
+    submitted: usize,
+    committed: usize,
+    invalid: usize,
+    elapsed: Duration,
+}
+
+impl BatchMetrics {
+    /// Returns the metrics of a batch list which ended with the given status,
+    /// `elapsed` after the command started
+    ///
+    /// A batch is committed or rejected as a whole, so either all of its
+    /// transactions are committed or none are.
+    pub fn new(batch_list: &BatchList, status: &Status, elapsed: Duration) -> BatchMetrics {
+        let submitted = batch_list
+            .get_batches()
+            .iter()
+            .map(|batch| batch.get_transactions().len())
+            .sum();
+
+        let (committed, invalid) = match status.status.as_str() {
+            "COMMITTED" => (submitted, 0),
+            "INVALID" => (0, status.invalid_transactions.len()),
+            // Batches which were not waited on are not known to be either
+            _ => (0, 0),
+        };
+
+        BatchMetrics {
+            submitted,
+            committed,
+            invalid,
+            elapsed,
+        }
+    }
+
+    /// Returns the metrics in the Prometheus text exposition format
+    ///
+    /// The metrics describe a single run, so they are gauges, replaced by the
+    /// next run's push, rather than counters.
+    pub fn to_text(&self) -> String {
+        let metrics = [
+            (
+                "csrc_transactions_submitted",
+                "Transactions submitted by the last run",
+                self.submitted.to_string(),
+            ),
+            (
+                "csrc_transactions_committed",
+                "Transactions of the last run which were committed",
+                self.committed.to_string(),
+            ),
+            (
+                "csrc_transactions_invalid",
+                "Transactions of the last run which were rejected as invalid",
+                self.invalid.to_string(),
+            ),
+            (
+                "csrc_elapsed_seconds",
+                "Duration of the last run",
+                self.elapsed.as_secs_f64().to_string(),
+            ),
+        ];
+
+        metrics
+            .iter()
+            .map(|(name, help, value)| {
+                format!(
+                    "# HELP {} {}\n# TYPE {} gauge\n{} {}\n",
+                    name, help, name, name, value
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns the url metrics are pushed to on the Pushgateway at the given url
+pub fn push_url(metrics_url: &str) -> String {
+    format!(
+        "{}/metrics/job/{}",
+        metrics_url.trim_end_matches('/'),
+        JOB_NAME
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_text_test() {
+        let metrics = BatchMetrics {
+            submitted: 3,
+            committed: 0,
+            invalid: 1,
+            elapsed: Duration::from_millis(1500),
+        };
+        let text = metrics.to_text();
+
+        assert!(text
+            .contains("# TYPE csrc_transactions_submitted gauge\ncsrc_transactions_submitted 3\n"));
+        assert!(text.contains("\ncsrc_transactions_invalid 1\n"));
+        assert!(text.contains("\ncsrc_elapsed_seconds 1.5\n"));
+        assert_eq!(
+            push_url("http://pushgateway:9091/"),
+            "http://pushgateway:9091/metrics/job/csrc"
+        );
+    }
+}
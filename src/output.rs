@@ -0,0 +1,37 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which assist with formatting the output of commands
+
+use crate::error::CliError;
+
+use serde::Serialize;
+
+/// Returns the JSON representation of the given value
+///
+/// The JSON is compact unless `pretty` is set (by `--pretty`), in which case
+/// it is indented for humans to read.
+///
+/// # Errors
+///
+/// If the value cannot be serialized, a `CliError::UserError` is returned.
+pub fn to_json<T: Serialize + ?Sized>(value: &T, pretty: bool) -> Result<String, CliError> {
+    let json = if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    };
+
+    json.map_err(|err| CliError::UserError(format!("Unable to serialize output: {}", err)))
+}
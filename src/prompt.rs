@@ -0,0 +1,128 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which ask the user to confirm an operation
+
+use crate::error::CliError;
+
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Write};
+
+/// The terminal of the process, which is asked for confirmation when stdin
+/// holds a command's input
+const TTY_PATH: &str = "/dev/tty";
+
+/// Asks the user to confirm an operation which modifies state on chain
+///
+/// The prompt is printed to stderr, keeping stdout for the command's output,
+/// and the answer is read from stdin. Only "y" or "yes" (in any case) confirm
+/// the operation. If `assume_yes` is set, the user is not asked and the
+/// operation is confirmed.
+///
+/// # Errors
+///
+/// If the user does not confirm the operation, a `CliError::UserError` is
+/// returned. If stdin or stderr cannot be used, a `CliError::IoError` is
+/// returned.
+pub fn confirm(prompt: &str, assume_yes: bool) -> Result<(), CliError> {
+    if assume_yes {
+        return Ok(());
+    }
+
+    let stdin = io::stdin();
+    let stderr = io::stderr();
+    confirmed_or_cancelled(confirm_with(prompt, &mut stdin.lock(), &mut stderr.lock())?)
+}
+
+/// Asks the user to confirm an operation on the input read from `filepath`
+///
+/// If the input was read from stdin ("-"), stdin cannot also hold the answer,
+/// so the prompt is shown on the terminal, `/dev/tty`, and the answer read
+/// from it. Otherwise this is the same as `confirm`.
+///
+/// # Errors
+///
+/// As for `confirm`. If the input was read from stdin and there is no
+/// terminal to ask, a `CliError::UserError` asking for `--assume-yes` is
+/// returned.
+pub fn confirm_input(prompt: &str, filepath: &str, assume_yes: bool) -> Result<(), CliError> {
+    if assume_yes || filepath != "-" {
+        return confirm(prompt, assume_yes);
+    }
+
+    let tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(TTY_PATH)
+        .map_err(|_| {
+            CliError::UserError(String::from(
+                "The input was read from stdin and there is no terminal to confirm on. \
+                 Pass --assume-yes to skip this confirmation",
+            ))
+        })?;
+    let mut output = tty.try_clone()?;
+    confirmed_or_cancelled(confirm_with(prompt, &mut BufReader::new(tty), &mut output)?)
+}
+
+fn confirmed_or_cancelled(confirmed: bool) -> Result<(), CliError> {
+    if confirmed {
+        Ok(())
+    } else {
+        Err(CliError::UserError(String::from(
+            "Operation cancelled. Pass --assume-yes to skip this confirmation",
+        )))
+    }
+}
+
+fn confirm_with<R: BufRead, W: Write>(
+    prompt: &str,
+    input: &mut R,
+    output: &mut W,
+) -> Result<bool, CliError> {
+    write!(output, "{} Continue? [y/N] ", prompt)?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answer(input: &str) -> bool {
+        let mut output = vec![];
+        confirm_with(
+            "This will modify org-id on chain.",
+            &mut input.as_bytes(),
+            &mut output,
+        )
+        .expect("Failed to read confirmation")
+    }
+
+    #[test]
+    fn confirm_accepts_yes_test() {
+        assert!(answer("y\n"));
+        assert!(answer("YES\n"));
+    }
+
+    #[test]
+    fn confirm_defaults_to_no_test() {
+        assert!(!answer("\n"));
+        assert!(!answer("n\n"));
+        assert!(!answer(""));
+    }
+}
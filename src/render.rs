@@ -0,0 +1,148 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which render the objects listed by read commands as a
+//! table, for `--format table`
+
+/// An object which can be shown as a row of a table
+pub trait TableRow {
+    /// The headers of the table's columns
+    const COLUMNS: &'static [&'static str];
+
+    /// Returns the row's cells, one for each of `COLUMNS`
+    fn cells(&self) -> Vec<String>;
+}
+
+/// Rows of cells under named columns
+#[derive(Debug)]
+pub struct Table {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// The character a truncated cell ends with
+const ELLIPSIS: char = '…';
+
+impl Table {
+    /// Returns a table with a row for each of the given objects
+    pub fn from_rows<T: TableRow>(rows: &[T]) -> Self {
+        Table {
+            columns: T::COLUMNS.to_vec(),
+            rows: rows.iter().map(TableRow::cells).collect(),
+        }
+    }
+
+    /// Renders the table, its header underlined, with each column as wide as
+    /// its widest cell
+    ///
+    /// Cells wider than `max_col_width` characters are truncated with an
+    /// ellipsis. A table without rows is rendered as a "No results" line.
+    pub fn render(&self, max_col_width: Option<usize>) -> String {
+        if self.rows.is_empty() {
+            return String::from("No results");
+        }
+
+        let fit = |cell: &str| truncate(cell, max_col_width);
+        let header: Vec<String> = self.columns.iter().map(|column| fit(column)).collect();
+        let rows: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| fit(cell)).collect())
+            .collect();
+
+        let mut widths: Vec<usize> = header.iter().map(|cell| cell.chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let underline: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+        let mut lines = vec![
+            render_row(&header, &widths),
+            render_row(&underline, &widths),
+        ];
+        lines.extend(rows.iter().map(|row| render_row(row, &widths)));
+        lines.join("\n")
+    }
+}
+
+/// Returns the cell, cut down to `max_width` characters if it is longer, with
+/// an ellipsis as its last character
+fn truncate(cell: &str, max_width: Option<usize>) -> String {
+    match max_width {
+        Some(max_width) if cell.chars().count() > max_width => {
+            let mut truncated: String = cell.chars().take(max_width.saturating_sub(1)).collect();
+            truncated.push(ELLIPSIS);
+            truncated
+        }
+        _ => cell.to_string(),
+    }
+}
+
+/// Returns the cells padded to the widths of their columns, two spaces apart
+fn render_row(cells: &[String], widths: &[usize]) -> String {
+    cells
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| format!("{:width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Certificate(&'static str, &'static str);
+
+    impl TableRow for Certificate {
+        const COLUMNS: &'static [&'static str] = &["id", "standard"];
+
+        fn cells(&self) -> Vec<String> {
+            vec![self.0.to_string(), self.1.to_string()]
+        }
+    }
+
+    #[test]
+    fn render_test() {
+        let table = Table::from_rows(&[
+            Certificate("cert-1", "ISO 9001"),
+            Certificate("certificate-2", "Fair Trade"),
+        ]);
+
+        assert_eq!(
+            table.render(None),
+            "id             standard\n\
+             -------------  ----------\n\
+             cert-1         ISO 9001\n\
+             certificate-2  Fair Trade"
+        );
+        assert_eq!(
+            table.render(Some(6)),
+            "id      stand…\n\
+             ------  ------\n\
+             cert-1  ISO 9…\n\
+             certi…  Fair …"
+        );
+    }
+
+    #[test]
+    fn render_empty_test() {
+        let table = Table::from_rows::<Certificate>(&[]);
+        assert_eq!(table.render(None), "No results");
+    }
+}
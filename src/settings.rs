@@ -0,0 +1,132 @@
+// Copyright 2018 Cargill Incorporated
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains functions which assist with the creation of Sawtooth Settings
+//! transactions
+//!
+//! The Sawtooth SDK does not ship the settings family's protobuf messages, so
+//! the payloads are encoded by hand, following the definitions of
+//! `SettingsPayload` and `SettingProposal` in sawtooth-core.
+
+use crate::error::CliError;
+use crate::transaction::TransactionOptions;
+
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+use protobuf::CodedOutputStream;
+
+const SETTINGS_FAMILY_NAME: &str = "sawtooth_settings";
+const SETTINGS_FAMILY_VERSION: &str = "1.0";
+pub const SETTINGS_NAMESPACE: &str = "000000";
+
+const MAX_KEY_PARTS: usize = 4;
+const ADDRESS_PART_SIZE: usize = 16;
+
+/// `SettingsPayload.Action.PROPOSE`
+const PROPOSE_ACTION: i32 = 1;
+
+const PROPOSALS_KEY: &str = "sawtooth.settings.vote.proposals";
+const AUTHORIZED_KEYS_KEY: &str = "sawtooth.settings.vote.authorized_keys";
+const APPROVAL_THRESHOLD_KEY: &str = "sawtooth.settings.vote.approval_threshold";
+
+/// Returns the state address of the given setting
+///
+/// The setting key is split on '.' into at most four parts, padded with empty
+/// parts, and each part contributes the first 16 hex characters of its sha256
+/// hash to the address.
+pub fn make_setting_address(key: &str) -> String {
+    let mut parts: Vec<&str> = key.splitn(MAX_KEY_PARTS, '.').collect();
+    parts.resize(MAX_KEY_PARTS, "");
+
+    let mut address = String::from(SETTINGS_NAMESPACE);
+    for part in parts {
+        let mut sha = Sha256::new();
+        sha.input_str(part);
+        address.push_str(&sha.result_str()[..ADDRESS_PART_SIZE]);
+    }
+    address
+}
+
+/// Returns the serialized `SettingsPayload` proposing a value for a setting
+///
+/// # Errors
+///
+/// If an error occurs while encoding the payload, a `CliError::ProtobufError`
+/// is returned.
+pub fn create_setting_proposal_payload(
+    key: &str,
+    value: &str,
+    nonce: &str,
+) -> Result<Vec<u8>, CliError> {
+    let mut proposal = vec![];
+    {
+        let mut out = CodedOutputStream::vec(&mut proposal);
+        out.write_string(1, key)?;
+        out.write_string(2, value)?;
+        out.write_string(3, nonce)?;
+        out.flush()?;
+    }
+
+    let mut payload = vec![];
+    {
+        let mut out = CodedOutputStream::vec(&mut payload);
+        out.write_enum(1, PROPOSE_ACTION)?;
+        out.write_bytes(2, &proposal)?;
+        out.flush()?;
+    }
+
+    Ok(payload)
+}
+
+/// Returns the input and output addresses of a proposal for the given setting
+pub fn create_setting_transaction_addresses(key: &str) -> (Vec<String>, Vec<String>) {
+    let setting_address = make_setting_address(key);
+    let proposals_address = make_setting_address(PROPOSALS_KEY);
+    let inputs = vec![
+        proposals_address.clone(),
+        make_setting_address(AUTHORIZED_KEYS_KEY),
+        make_setting_address(APPROVAL_THRESHOLD_KEY),
+        setting_address.clone(),
+    ];
+    let outputs = vec![proposals_address, setting_address];
+    (inputs, outputs)
+}
+
+/// Returns the given transaction options, retargeted at the settings family
+pub fn settings_transaction_options(options: &TransactionOptions) -> TransactionOptions {
+    TransactionOptions {
+        family_name: String::from(SETTINGS_FAMILY_NAME),
+        family_version: String::from(SETTINGS_FAMILY_VERSION),
+        ..options.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn make_setting_address_test() {
+        let address = make_setting_address("sawtooth.settings.vote.authorized_keys");
+        assert_eq!(address.len(), 70);
+        assert!(address.starts_with(SETTINGS_NAMESPACE));
+
+        // Keys with fewer than four parts are padded with empty parts
+        let short_address = make_setting_address("sawtooth.identity");
+        let mut sha = Sha256::new();
+        sha.input_str("");
+        let empty_part = &sha.result_str()[..ADDRESS_PART_SIZE];
+        assert!(short_address.ends_with(&format!("{}{}", empty_part, empty_part)));
+    }
+}
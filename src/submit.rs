@@ -14,15 +14,29 @@
 
 //! Contains functions which assist with batch submission to a REST API
 
+use crate::batch_file::{self, BatchFormat};
 use crate::error::CliError;
+use crate::explain;
+use crate::interrupt;
+use crate::metrics::{self, BatchMetrics};
+use crate::output;
+use crate::transaction::{bytes_to_hex_str, unsign_batch_list};
 
-use futures::Stream;
-use futures::{future, Future};
-use hyper::header::{ContentLength, ContentType};
-use hyper::{Client, Method, Request, Uri};
+use clap::ArgMatches;
 use protobuf::Message;
+use reqwest::blocking::{Client, Response};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::{StatusCode, Url};
 use sawtooth_sdk::messages::batch::BatchList;
-use serde_derive::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Instant;
+use std::{env, thread, time};
 
 #[derive(Deserialize, Debug)]
 struct Link {
@@ -41,6 +55,113 @@ pub struct Status {
     pub id: String,
     pub invalid_transactions: Vec<InvalidTransactions>,
     pub status: String,
+    /// The header signatures of the transactions in the submitted batches,
+    /// which the REST API doesn't report, so they are filled in by the client
+    #[serde(default)]
+    pub transaction_ids: Vec<String>,
+    /// The block the batch was found in, if it was looked up with
+    /// `--wait-for-block`
+    #[serde(skip)]
+    pub block: Option<BlockInclusion>,
+    /// How long the batch took to be committed after it was submitted, if the
+    /// client waited for it
+    #[serde(skip)]
+    pub elapsed: Option<time::Duration>,
+}
+
+/// The committed block a batch was included in
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BlockInclusion {
+    pub block_id: String,
+    pub block_num: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct CertificateResponse {
+    data: CertificateRecord,
+}
+
+#[derive(Deserialize, Debug)]
+struct CertificateRecord {
+    #[serde(default)]
+    certificate_data: Vec<CertificateDataRecord>,
+}
+
+/// A field of certificate data, as stored on chain
+#[derive(Deserialize, Debug)]
+pub struct CertificateDataRecord {
+    pub field: String,
+    pub data: String,
+}
+
+/// A page of a collection listed by the REST API
+#[derive(Deserialize, Debug)]
+struct Page<T> {
+    data: Vec<T>,
+    #[serde(default)]
+    paging: Option<Paging>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Paging {
+    /// The link to the next page, which is empty or missing on the last page
+    #[serde(default)]
+    next: Option<String>,
+}
+
+/// An error of a request made on a worker thread
+///
+/// `CliError` can't be sent between threads, since a signing error may hold
+/// any error, but the errors a request fails with can, so they are passed
+/// back as these and keep their type.
+#[derive(Debug)]
+enum WorkerError {
+    Http(reqwest::Error),
+    Io(std::io::Error),
+    User(String),
+}
+
+impl From<CliError> for WorkerError {
+    fn from(err: CliError) -> Self {
+        match err {
+            CliError::HttpError(err) => WorkerError::Http(err),
+            CliError::IoError(err) => WorkerError::Io(err),
+            CliError::UserError(message) => WorkerError::User(message),
+            err => WorkerError::User(err.to_string()),
+        }
+    }
+}
+
+impl From<WorkerError> for CliError {
+    fn from(err: WorkerError) -> Self {
+        match err {
+            WorkerError::Http(err) => CliError::HttpError(err),
+            WorkerError::Io(err) => CliError::IoError(err),
+            WorkerError::User(message) => CliError::UserError(message),
+        }
+    }
+}
+
+/// A record of an invalid transaction, written to the `--error-log`
+#[derive(Serialize, Debug)]
+struct ErrorLogEntry<'a> {
+    timestamp: String,
+    batch_id: &'a str,
+    transaction_id: &'a str,
+    /// The key of the batch file entry the transaction was created for
+    entry: Option<&'a str>,
+    message: String,
+}
+
+/// A record of a submitted batch list, written to the `--trace-file`
+#[derive(Serialize, Debug)]
+struct TraceEntry<'a> {
+    timestamp: String,
+    command: Vec<String>,
+    batch_ids: Vec<&'a str>,
+    transaction_ids: Vec<&'a str>,
+    /// The serialized batch list, in hex
+    batch_list: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -50,60 +171,1921 @@ pub struct InvalidTransactions {
     pub message: String,
 }
 
-pub fn submit_batch_list(url: &str, batch_list: &BatchList) -> Result<String, CliError> {
-    let post_url = String::from(url) + "/api/batches";
-    let hyper_uri = post_url.parse::<Uri>()?;
+/// A client for the ConsenSource REST API
+///
+/// A single client is meant to be created per command and reused for every
+/// request the command makes, so that the underlying HTTP client (and its
+/// connection pool) is only set up once. Connections are kept alive between
+/// requests, so a command which submits several batches and polls their
+/// statuses pays for the TCP handshake once rather than on every request.
+#[derive(Clone)]
+pub struct ApiClient {
+    client: Client,
+    base_url: String,
+    api_prefix: String,
+    poll_interval: time::Duration,
+    max_retries: Option<u32>,
+    wait_for_block: bool,
+    wait: bool,
+    trace_file: Option<String>,
+    error_log: Option<String>,
+    metrics_url: Option<String>,
+    max_body_size: u64,
+    /// Where batches go before, or instead of, being submitted
+    batch_sink: BatchSink,
+    /// Whether batch status requests ask the REST API to hold them until the
+    /// status changes. Cleared, for this client and its clones, once the REST
+    /// API turns out not to support it
+    server_wait: Arc<AtomicBool>,
+    /// Where batches are submitted to, if not the REST API
+    submitter: Option<Arc<dyn Submitter + Send + Sync>>,
+    /// When the client was created, which is taken as the start of the command
+    started: Instant,
+}
 
-    match hyper_uri.scheme() {
-        Some(scheme) => {
-            if scheme != "http" {
+/// What happens to a command's batches on their way to the REST API: their
+/// addresses are printed with `--explain`, and with `--output-batch` they are
+/// written to a file instead of being submitted
+#[derive(Clone)]
+pub struct BatchSink {
+    /// The file batches are written to instead of being submitted, if any
+    output_batch: Option<String>,
+    /// Whether batches are written without their signatures
+    sign_later: bool,
+    batch_format: BatchFormat,
+    batch_manifest: bool,
+    explain: bool,
+}
+
+impl Default for BatchSink {
+    fn default() -> Self {
+        BatchSink {
+            output_batch: None,
+            sign_later: false,
+            batch_format: BatchFormat::Protobuf,
+            batch_manifest: false,
+            explain: false,
+        }
+    }
+}
+
+impl BatchSink {
+    /// Returns the batch sink configured by the command line arguments
+    ///
+    /// With `--output-batch`, batches are written to the given file instead of
+    /// being submitted, without their signatures if `--sign-later` is given,
+    /// in the format given by `--batch-format` and with a manifest if
+    /// `--batch-manifest` is given. With `--explain` the addresses each
+    /// transaction touches are printed before it is submitted.
+    ///
+    /// # Errors
+    ///
+    /// If the batch format is unknown, or the `--output-batch` file already
+    /// exists, a `CliError::InvalidInputError` is returned.
+    pub fn from_args(args: &ArgMatches) -> Result<BatchSink, CliError> {
+        let output_batch = args.value_of("output_batch").map(String::from);
+        if let Some(output_batch) = &output_batch {
+            batch_file::check_new_batch_file(output_batch)?;
+        }
+
+        Ok(BatchSink {
+            output_batch,
+            sign_later: args.is_present("sign_later"),
+            batch_format: BatchFormat::from_args(args)?,
+            batch_manifest: args.is_present("batch_manifest"),
+            explain: args.is_present("explain"),
+        })
+    }
+
+    /// Prints the addresses the batch list's transactions touch, if the sink
+    /// was created with `--explain`
+    ///
+    /// # Errors
+    ///
+    /// If a transaction header cannot be read, a `CliError::ProtobufError` is
+    /// returned.
+    fn explain(&self, batch_list: &BatchList) -> Result<(), CliError> {
+        if self.explain {
+            for line in explain::describe_addresses(batch_list)? {
+                eprintln!("{}", line);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the batch list to the `--output-batch` file and returns the
+    /// status of its first batch, `WRITTEN_STATUS`, or returns `None` if the
+    /// sink has no file and the batch list is to be submitted
+    ///
+    /// The batch list is written in the sink's batch format, its signatures
+    /// replaced with placeholders with `--sign-later`, and its manifest is
+    /// written with `--batch-manifest`. A command may write several batch
+    /// lists, such as one per chunk of a batch file, so the batches are added
+    /// after those already in the file. The file did not exist when the sink
+    /// was created, so it only holds batches written by this command.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be read or written, a `CliError::IoError` is
+    /// returned, and if the batches cannot be encoded or decoded the error
+    /// from `batch_file` is.
+    fn write(&self, batch_list: &BatchList) -> Result<Option<Status>, CliError> {
+        let path = match &self.output_batch {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let batch_list = if self.sign_later {
+            unsign_batch_list(batch_list)?
+        } else {
+            batch_list.clone()
+        };
+
+        let mut written = match fs::read(path) {
+            Ok(bytes) => batch_file::decode_batch_list(&bytes, self.batch_format)?,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => BatchList::new(),
+            Err(err) => return Err(err.into()),
+        };
+        for batch in batch_list.get_batches() {
+            written.mut_batches().push(batch.clone());
+        }
+        batch_file::write_batch_file(&written, path, self.batch_format, self.batch_manifest)?;
+
+        Ok(Some(Status {
+            id: batch_list
+                .get_batches()
+                .first()
+                .map(|batch| batch.get_header_signature().to_string())
+                .unwrap_or_default(),
+            invalid_transactions: vec![],
+            status: String::from(WRITTEN_STATUS),
+            block: None,
+            elapsed: None,
+            // Unsigned transactions only get their ids once signed
+            transaction_ids: if self.sign_later {
+                vec![]
+            } else {
+                transaction_ids(&batch_list)
+            },
+        }))
+    }
+}
+
+/// The REST API used when no `--url` is given
+pub const DEFAULT_URL: &str = "http://localhost:9009";
+
+/// The status reported for a batch which was submitted with `--no-wait`, and
+/// so is not known to be committed yet
+pub const SUBMITTED_STATUS: &str = "SUBMITTED";
+
+/// The status reported for a batch which was written to the `--output-batch`
+/// file rather than submitted
+pub const WRITTEN_STATUS: &str = "WRITTEN";
+
+/// The largest response body read from the REST API when no
+/// `--max-body-size` is given, 64 MiB
+const DEFAULT_MAX_BODY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The path the REST API is mounted under when no `--api-prefix` is given
+const DEFAULT_API_PREFIX: &str = "/api";
+
+/// How long an idle connection is kept open for reuse by later requests
+const POOL_IDLE_TIMEOUT: time::Duration = time::Duration::from_secs(90);
+
+/// How many idle connections to the REST API are kept open for reuse, enough
+/// for the reads of `fetch_data_concurrently` at the default `--concurrency`
+/// and a few more
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// How often TCP keep-alive probes are sent on open connections
+const TCP_KEEPALIVE: time::Duration = time::Duration::from_secs(60);
+
+/// How many blocks are fetched at a time when `--wait-for-block` searches for
+/// a committed batch
+const BLOCK_SEARCH_DEPTH: usize = 100;
+
+/// How many pages of `BLOCK_SEARCH_DEPTH` blocks, going back from the most
+/// recent, `--wait-for-block` searches before giving up
+const BLOCK_SEARCH_PAGES: usize = 10;
+
+/// How quickly a batch status request asking the REST API to wait can come
+/// back with a pending batch before the wait is taken to have been ignored
+const IGNORED_WAIT_THRESHOLD: time::Duration = time::Duration::from_secs(1);
+
+/// How long to wait between batch status checks when no `--poll-interval`
+/// is given
+const DEFAULT_POLL_INTERVAL: time::Duration = time::Duration::from_millis(250);
+
+impl ApiClient {
+    /// Returns a client for the REST API at the given base url, which sends
+    /// the given headers with every request
+    ///
+    /// The REST API may be served over HTTP or HTTPS. Its TLS certificate is
+    /// verified unless `accept_invalid_certs` is set, which is only meant for
+    /// development clusters with self-signed certificates.
+    ///
+    /// # Errors
+    ///
+    /// If the url cannot be parsed or uses an unsupported scheme, a
+    /// `CliError::UserError` is returned. If the HTTP client cannot be set up,
+    /// a `CliError::HttpError` is returned.
+    pub fn new(
+        url: &str,
+        headers: HeaderMap,
+        accept_invalid_certs: bool,
+    ) -> Result<ApiClient, CliError> {
+        let base_url = parse_url(url)?;
+
+        match base_url.scheme() {
+            "http" | "https" => (),
+            scheme => {
                 return Err(CliError::UserError(format!(
                     "Unsupported scheme ({}) in URL: {}",
                     scheme, url
                 )));
             }
         }
-        None => {
-            return Err(CliError::UserError(format!("No scheme in URL: {}", url)));
+
+        let client = Client::builder()
+            .default_headers(headers)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .danger_accept_invalid_certs(accept_invalid_certs)
+            .build()?;
+
+        Ok(ApiClient {
+            client,
+            base_url: url.trim_end_matches('/').to_string(),
+            api_prefix: String::from(DEFAULT_API_PREFIX),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            max_retries: None,
+            wait_for_block: false,
+            wait: true,
+            trace_file: None,
+            error_log: None,
+            metrics_url: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            batch_sink: BatchSink::default(),
+            server_wait: Arc::new(AtomicBool::new(true)),
+            submitter: None,
+            started: Instant::now(),
+        })
+    }
+
+    /// Returns a client configured by the command line arguments
+    ///
+    /// The REST API is given by `--url`, defaulting to `DEFAULT_URL`, and its
+    /// TLS certificate is not verified with `--insecure`. The path it is
+    /// mounted under is given by `--api-prefix`, any extra request headers
+    /// by `--header`, and the time between batch status checks by
+    /// `--poll-interval`, which is jittered and checked at most `--max-retries`
+    /// times. With `--no-wait`, submitted batches are not waited on, and with
+    /// `--wait-for-block` the block each committed batch landed in is looked
+    /// up. With `--trace-file` they are recorded to the given file. With
+    /// `--error-log`, the invalid transactions of rejected batches are
+    /// recorded to the given file, and with `--metrics-url` the outcome of
+    /// batch commands is pushed to the given Prometheus Pushgateway.
+    /// Responses larger than `--max-body-size` are refused rather than read
+    /// into memory. The client's batches go through a `BatchSink` configured
+    /// by the same arguments.
+    ///
+    /// # Errors
+    ///
+    /// If the url or a header is invalid, a `CliError::UserError` is returned.
+    /// If the poll interval is not a positive number of milliseconds, or the
+    /// maximum body size is not a positive size, a
+    /// `CliError::InvalidInputError` is returned, as it is for invalid batch
+    /// output settings by `BatchSink::from_args`.
+    pub fn from_args(args: &ArgMatches) -> Result<ApiClient, CliError> {
+        let mut headers = HeaderMap::new();
+        for header in args.values_of("header").into_iter().flatten() {
+            let (name, value) = parse_header(header)?;
+            headers.append(name, value);
+        }
+
+        let insecure = args.is_present("insecure");
+        if insecure {
+            eprintln!(
+                "WARNING: --insecure is set, so the REST API's TLS certificate is not verified \
+                 and the connection can be intercepted. Only use it against development clusters"
+            );
+        }
+
+        let mut client = ApiClient::new(
+            args.value_of("url").unwrap_or(DEFAULT_URL),
+            headers,
+            insecure,
+        )?;
+
+        client.wait = !args.is_present("no_wait");
+        client.wait_for_block = args.is_present("wait_for_block");
+        client.trace_file = args.value_of("trace_file").map(String::from);
+        client.error_log = args.value_of("error_log").map(String::from);
+        client.metrics_url = args.value_of("metrics_url").map(String::from);
+        client.batch_sink = BatchSink::from_args(args)?;
+
+        if let Some(api_prefix) = args.value_of("api_prefix") {
+            client.api_prefix = normalize_api_prefix(api_prefix);
+        }
+
+        if let Some(max_body_size) = args.value_of("max_body_size") {
+            client.max_body_size = parse_body_size(max_body_size)?;
+        }
+
+        if let Some(poll_interval) = args.value_of("poll_interval") {
+            client.poll_interval = match poll_interval.parse::<u64>() {
+                Ok(millis) if millis > 0 => time::Duration::from_millis(millis),
+                _ => {
+                    return Err(CliError::InvalidInputError(format!(
+                        "Invalid poll interval: {}. Please provide a number of milliseconds",
+                        poll_interval
+                    )));
+                }
+            };
+        }
+
+        if let Some(max_retries) = args.value_of("max_retries") {
+            client.max_retries = Some(max_retries.parse::<u32>().map_err(|_| {
+                CliError::InvalidInputError(format!(
+                    "Invalid maximum number of retries: {}. Please provide a number",
+                    max_retries
+                ))
+            })?);
         }
+
+        Ok(client)
     }
 
-    let mut core = tokio_core::reactor::Core::new()?;
-    let handle = core.handle();
-    let client = Client::configure().build(&handle);
+    /// Returns the base url of the REST API
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
 
-    let bytes = batch_list.write_to_bytes()?;
+    /// Returns the url of the given path of the REST API
+    fn api_url(&self, path: &str) -> Result<Url, CliError> {
+        parse_url(&format!("{}{}{}", self.base_url, self.api_prefix, path))
+    }
 
-    let mut req = Request::new(Method::Post, hyper_uri);
-    req.headers_mut().set(ContentType::octet_stream());
-    req.headers_mut().set(ContentLength(bytes.len() as u64));
-    req.set_body(bytes);
+    /// Returns the backoff to follow between batch status checks
+    pub fn backoff(&self) -> Backoff {
+        Backoff::new(self.poll_interval, self.max_retries, jitter_seed())
+    }
 
-    let work = client.request(req).and_then(|res| {
-        res.body()
-            .concat2()
-            .and_then(move |chunks| future::ok(serde_json::from_slice::<Link>(&chunks).unwrap()))
-    });
+    /// Returns a client which submits batches and checks their statuses
+    /// through the given submitter instead of the REST API
+    ///
+    /// Every other request, such as listing or fetching objects, still goes to
+    /// the REST API.
+    pub fn with_submitter(mut self, submitter: Arc<dyn Submitter + Send + Sync>) -> ApiClient {
+        self.submitter = Some(submitter);
+        self
+    }
 
-    let batch_link = core.run(work)?;
-    Ok(batch_link.link)
-}
+    /// Returns what batches are submitted through: the REST API, unless the
+    /// client was given another submitter
+    fn submitter(&self) -> &dyn Submitter {
+        match &self.submitter {
+            Some(submitter) => submitter.as_ref(),
+            None => self,
+        }
+    }
+
+    /// Sends a request to a known endpoint of the REST API, returning the
+    /// status it responded with and how long the round trip took
+    pub fn ping(&self) -> Result<(StatusCode, time::Duration), CliError> {
+        let url = self.api_url("/organizations?limit=1")?;
+
+        let start = Instant::now();
+        let response = self.client.get(url).send()?;
+        Ok((response.status(), start.elapsed()))
+    }
 
-pub fn wait_for_status(base_url: &str, batch_status_link: &str) -> Result<StatusData, CliError> {
-    let link = format!("{}/api{}{}", base_url, batch_status_link, "&wait=true");
-    let req = Request::new(Method::Get, link.parse::<Uri>()?);
+    /// Returns whether the REST API knows of the organization with the given id
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with an error other than 404 Not Found, a
+    /// `CliError::UserError` is returned.
+    pub fn organization_exists(&self, org_id: &str) -> Result<bool, CliError> {
+        self.resource_exists("organizations", "organization", org_id)
+    }
+
+    /// Returns whether the REST API knows of the agent with the given public
+    /// key
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with an error other than 404 Not Found, a
+    /// `CliError::UserError` is returned.
+    pub fn agent_exists(&self, public_key: &str) -> Result<bool, CliError> {
+        self.resource_exists("agents", "agent", public_key)
+    }
 
-    // Create client
-    let mut core = tokio_core::reactor::Core::new()?;
-    let handle = core.handle();
-    let client = Client::configure().build(&handle);
+    /// Returns whether the REST API knows of the standard with the given id
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with an error other than 404 Not Found, a
+    /// `CliError::UserError` is returned.
+    pub fn standard_exists(&self, standard_id: &str) -> Result<bool, CliError> {
+        self.resource_exists("standards", "standard", standard_id)
+    }
+
+    /// Returns whether the REST API knows of the certificate with the given id
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with an error other than 404 Not Found, a
+    /// `CliError::UserError` is returned.
+    pub fn certificate_exists(&self, cert_id: &str) -> Result<bool, CliError> {
+        self.resource_exists("certificates", "certificate", cert_id)
+    }
 
-    let work = client.request(req).and_then(|res| {
-        res.body().concat2().and_then(move |chunks| {
-            future::ok(serde_json::from_slice::<StatusData>(&chunks).unwrap())
+    /// Fetches the given path of the REST API, returning the `data` of its
+    /// response, or `None` if the REST API responds with 404 Not Found
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with any other error, a `CliError::UserError`
+    /// is returned.
+    pub fn fetch_data(&self, path: &str) -> Result<Option<serde_json::Value>, CliError> {
+        let response = self.client.get(self.api_url(path)?).send()?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let mut body = self.read_json::<serde_json::Value>(response)?;
+        Ok(Some(body["data"].take()))
+    }
+
+    /// Fetches every item of the collection at the given path of the REST API,
+    /// following the `paging.next` link of each page until the last one
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with an error, or any item is not of the
+    /// expected type, a `CliError::UserError` is returned.
+    pub fn fetch_all_data<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, CliError> {
+        let mut url = self.api_url(path)?;
+        let mut visited = HashSet::new();
+        let mut items = vec![];
+        loop {
+            let response = self.client.get(url.clone()).send()?;
+            let page = self.read_json::<Page<T>>(response)?;
+            let last_page = page.data.is_empty();
+            items.extend(page.data);
+            visited.insert(url.to_string());
+
+            let next = page.paging.and_then(|paging| paging.next);
+            match next_page_url(&url, next.as_deref())? {
+                // A link back to a page already fetched would never end
+                Some(next) if !last_page && !visited.contains(next.as_str()) => url = next,
+                _ => return Ok(items),
+            }
+        }
+    }
+
+    /// Fetches each of the given paths of the REST API as `fetch_data` does,
+    /// on a pool of up to `concurrency` worker threads
+    ///
+    /// Each worker takes the next path as soon as it is done with the last, so
+    /// a slow request only holds up its own worker. The results are returned
+    /// in the order of the paths.
+    ///
+    /// # Errors
+    ///
+    /// If any request fails, its error is returned and no more requests are
+    /// started.
+    pub fn fetch_data_concurrently(
+        &self,
+        paths: &[String],
+        concurrency: usize,
+    ) -> Result<Vec<Option<serde_json::Value>>, CliError> {
+        let paths = Arc::new(paths.to_vec());
+        let next_path = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = mpsc::channel();
+
+        let workers: Vec<_> = (0..concurrency.max(1).min(paths.len()))
+            .map(|_| {
+                let client = self.clone();
+                let paths = Arc::clone(&paths);
+                let next_path = Arc::clone(&next_path);
+                let sender = sender.clone();
+                thread::spawn(move || loop {
+                    let index = next_path.fetch_add(1, Ordering::SeqCst);
+                    let path = match paths.get(index) {
+                        Some(path) => path,
+                        None => return,
+                    };
+                    let result = client.fetch_data(path).map_err(WorkerError::from);
+                    if sender.send((index, result)).is_err() {
+                        return;
+                    }
+                })
+            })
+            .collect();
+        drop(sender);
+
+        let mut results = vec![None; paths.len()];
+        for (index, result) in receiver {
+            match result {
+                Ok(data) => results[index] = Some(data),
+                Err(err) => {
+                    // Keep the workers from starting any more requests
+                    next_path.store(paths.len(), Ordering::SeqCst);
+                    return Err(err.into());
+                }
+            }
+        }
+
+        let panicked = || CliError::UserError(String::from("A request to the REST API panicked"));
+        for worker in workers {
+            worker.join().map_err(|_| panicked())?;
+        }
+        results
+            .into_iter()
+            .map(|result| result.ok_or_else(panicked))
+            .collect()
+    }
+
+    fn resource_exists(&self, collection: &str, kind: &str, id: &str) -> Result<bool, CliError> {
+        let mut url = self.api_url(&format!("/{}/", collection))?;
+        url = url
+            .join(id)
+            .map_err(|err| CliError::UserError(format!("Invalid {} id: {}", kind, err)))?;
+
+        let response = self.client.get(url).send()?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        check_response(response)?;
+        Ok(true)
+    }
+
+    /// Returns the certificate data currently stored for the certificate with
+    /// the given id
+    ///
+    /// # Errors
+    ///
+    /// If the certificate does not exist, or the REST API responds with an
+    /// error, a `CliError::UserError` is returned.
+    pub fn certificate_data(&self, cert_id: &str) -> Result<Vec<CertificateDataRecord>, CliError> {
+        let mut url = self.api_url("/certificates/")?;
+        url = url
+            .join(cert_id)
+            .map_err(|err| CliError::UserError(format!("Invalid certificate id: {}", err)))?;
+
+        let response = self.client.get(url).send()?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(CliError::UserError(format!(
+                "Certificate {} does not exist",
+                cert_id
+            )));
+        }
+
+        let certificate = self.read_json::<CertificateResponse>(response)?;
+        Ok(certificate.data.certificate_data)
+    }
+
+    /// Checks the response for errors and parses its JSON body
+    ///
+    /// At most `--max-body-size` bytes of the body are read, so that listing
+    /// a huge collection by mistake fails rather than exhausting memory.
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responded with an error, the body is larger than the
+    /// limit, or it is not the expected JSON, a `CliError::UserError` is
+    /// returned.
+    fn read_json<T: DeserializeOwned>(&self, response: Response) -> Result<T, CliError> {
+        let too_large = || {
+            CliError::UserError(format!(
+                "The REST API response is larger than {} bytes. Narrow the request with a filter, or raise --max-body-size",
+                self.max_body_size
+            ))
+        };
+
+        let response = check_response(response)?;
+        if response.content_length().unwrap_or(0) > self.max_body_size {
+            return Err(too_large());
+        }
+
+        let mut body = vec![];
+        response
+            .take(self.max_body_size + 1)
+            .read_to_end(&mut body)?;
+        if body.len() as u64 > self.max_body_size {
+            return Err(too_large());
+        }
+
+        serde_json::from_slice(&body).map_err(|err| {
+            CliError::UserError(format!("Unexpected response from the REST API: {}", err))
         })
+    }
+
+    /// Submits the batch list and waits for the batch to be committed
+    ///
+    /// # Errors
+    ///
+    /// If the batch is rejected, a `CliError::InvalidTransactionError` is
+    /// returned. If the validator does not know about the batch, a
+    /// `CliError::UserError` is returned.
+    pub fn submit_and_wait(&self, batch_list: &BatchList) -> Result<Status, CliError> {
+        let status = self.submit_and_get_status(batch_list)?;
+
+        if status.status == "INVALID" {
+            return Err(invalid_transaction_error(&status));
+        }
+
+        Ok(status)
+    }
+
+    /// Submits a batch list containing many transactions and waits for the
+    /// batch to be committed
+    ///
+    /// Unlike `submit_and_wait`, if the batch is rejected every invalid
+    /// transaction is reported on stderr, showing at most `max_invalid_display`
+    /// of them, and recorded to the `--error-log`, before the error is
+    /// returned. `txn_entries` pairs the id of each transaction with the key
+    /// of the batch file entry it was created for.
+    pub fn submit_batch_and_wait(
+        &self,
+        batch_list: &BatchList,
+        txn_entries: &[(String, &str)],
+        max_invalid_display: usize,
+    ) -> Result<Status, CliError> {
+        let status = self.submit_and_get_status(batch_list)?;
+        self.push_metrics(batch_list, &status);
+
+        if status.status == "INVALID" {
+            self.log_invalid_transactions(&status, txn_entries)?;
+            report_invalid_transactions(&status, max_invalid_display, |invalid_transaction| {
+                format!(
+                    "Transaction {} is invalid: {}",
+                    invalid_transaction.id, invalid_transaction.message
+                )
+            });
+            return Err(invalid_transaction_error(&status));
+        }
+
+        Ok(status)
+    }
+
+    /// Appends every transaction of the given rejected batch to the
+    /// `--error-log`, as a line of JSON, if one was given
+    ///
+    /// A batch is committed or rejected as a whole, so the entries whose
+    /// transactions were valid failed along with the invalid ones, and are
+    /// logged too. Each transaction is logged with the key of the batch file
+    /// entry it was created for, as given by `txn_entries`, so that
+    /// `--retry-failed` can pick the failed entries out of the batch file. An
+    /// invalid transaction with no entry is logged with a null `entry`.
+    ///
+    /// # Errors
+    ///
+    /// If the error log cannot be written, a `CliError::IoError` is returned.
+    pub fn log_invalid_transactions(
+        &self,
+        status: &Status,
+        txn_entries: &[(String, &str)],
+    ) -> Result<(), CliError> {
+        let error_log = match &self.error_log {
+            Some(error_log) => error_log,
+            None => return Ok(()),
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(error_log)?;
+        let timestamp = chrono::Utc::now().to_rfc3339();
+        for entry in error_log_entries(status, txn_entries, &timestamp) {
+            writeln!(file, "{}", output::to_json(&entry, false)?)?;
+        }
+        Ok(())
+    }
+
+    /// Pushes the metrics of a submitted batch list to the `--metrics-url`, if
+    /// one was given
+    ///
+    /// The batch list has already been submitted by then, so failing to push
+    /// the metrics doesn't fail the command, and is only reported on stderr.
+    /// The extra `--header`s meant for the REST API are not sent along.
+    pub fn push_metrics(&self, batch_list: &BatchList, status: &Status) {
+        let metrics_url = match &self.metrics_url {
+            Some(metrics_url) if status.status != WRITTEN_STATUS => metrics_url,
+            _ => return,
+        };
+
+        let metrics = BatchMetrics::new(batch_list, status, self.started.elapsed());
+        let result = Client::new()
+            .post(&metrics::push_url(metrics_url))
+            .body(metrics.to_text())
+            .send()
+            .map_err(CliError::from)
+            .and_then(check_response);
+
+        if let Err(err) = result {
+            eprintln!(
+                "Warning: unable to push metrics to {}: {}",
+                metrics_url, err
+            );
+        }
+    }
+
+    /// Submits the batch list and returns the final status of its first batch,
+    /// without checking whether the batch was rejected
+    ///
+    /// If the client was created with `--no-wait`, the batch is not waited on
+    /// and its status is `SUBMITTED_STATUS`. If it was created with
+    /// `--output-batch`, the batch list is written to that file instead of
+    /// being submitted, and its status is `WRITTEN_STATUS`.
+    pub fn submit_and_get_status(&self, batch_list: &BatchList) -> Result<Status, CliError> {
+        self.batch_sink.explain(batch_list)?;
+        if let Some(status) = self.batch_sink.write(batch_list)? {
+            return Ok(status);
+        }
+
+        if let Some(trace_file) = &self.trace_file {
+            write_trace_entry(trace_file, batch_list, &batch_list.write_to_bytes()?)?;
+        }
+
+        let submitted_at = Instant::now();
+        let link = self.submitter().submit(batch_list)?;
+
+        if !self.wait {
+            return Ok(Status {
+                id: batch_list
+                    .get_batches()
+                    .first()
+                    .map(|batch| batch.get_header_signature().to_string())
+                    .unwrap_or_default(),
+                invalid_transactions: vec![],
+                status: String::from(SUBMITTED_STATUS),
+                transaction_ids: transaction_ids(batch_list),
+                block: None,
+                elapsed: None,
+            });
+        }
+
+        let mut status = self.wait_for_batch(&link)?;
+        status.transaction_ids = transaction_ids(batch_list);
+        if status.status == "COMMITTED" {
+            status.elapsed = Some(submitted_at.elapsed());
+        }
+        if self.wait_for_block && status.status == "COMMITTED" {
+            status.block = self.find_block(&status.id);
+        }
+        Ok(status)
+    }
+
+    /// Returns the committed block which includes the given batch, if it can
+    /// be found
+    ///
+    /// The batch status only says the batch was committed, so with
+    /// `--wait-for-block` the blocks are searched for it, as evidence of which
+    /// block made it final. The batch is committed either way, so a block
+    /// which cannot be found is only warned about on stderr.
+    fn find_block(&self, batch_id: &str) -> Option<BlockInclusion> {
+        match self.search_blocks(batch_id) {
+            Ok(Some(block)) => Some(block),
+            Ok(None) => {
+                eprintln!(
+                    "Warning: batch {} was committed, but is not in any of the last {} blocks",
+                    batch_id,
+                    BLOCK_SEARCH_DEPTH * BLOCK_SEARCH_PAGES
+                );
+                None
+            }
+            Err(err) => {
+                eprintln!(
+                    "Warning: batch {} was committed, but its block could not be found: {}",
+                    batch_id, err
+                );
+                None
+            }
+        }
+    }
+
+    /// Searches the blocks for the given batch, from the most recent back,
+    /// following the `paging.next` link of each page of blocks for up to
+    /// `BLOCK_SEARCH_PAGES` pages
+    ///
+    /// # Errors
+    ///
+    /// If the REST API responds with an error, or returns blocks in an
+    /// unexpected form, a `CliError::UserError` is returned.
+    fn search_blocks(&self, batch_id: &str) -> Result<Option<BlockInclusion>, CliError> {
+        let mut url = self.api_url(&format!("/blocks?limit={}", BLOCK_SEARCH_DEPTH))?;
+        for _ in 0..BLOCK_SEARCH_PAGES {
+            let response = self.client.get(url.clone()).send()?;
+            let page = self.read_json::<Page<serde_json::Value>>(response)?;
+            if let Some(block) = find_block_in(&page.data, batch_id)? {
+                return Ok(Some(block));
+            }
+
+            let next = page.paging.and_then(|paging| paging.next);
+            match next_page_url(&url, next.as_deref())? {
+                Some(next) if !page.data.is_empty() && next != url => url = next,
+                _ => break,
+            }
+        }
+        Ok(None)
+    }
+
+    /// Polls the batch status behind the given link until the batch is either
+    /// committed or rejected, returning its final status
+    ///
+    /// # Errors
+    ///
+    /// If the user presses Ctrl-C while waiting, a `CliError::InterruptedError`
+    /// saying how to check on the batch later is returned.
+    pub fn wait_for_batch(&self, batch_status_link: &str) -> Result<Status, CliError> {
+        interrupt::wait(|| self.poll_batch_status(batch_status_link))
+    }
+
+    fn poll_batch_status(&self, batch_status_link: &str) -> Result<Status, CliError> {
+        let mut poller = StatusPoller::default();
+        let mut backoff = self.backoff();
+        let mut batch_status = self.submitter().status(batch_status_link)?;
+
+        loop {
+            if interrupt::is_interrupted() {
+                return Err(interrupted_error(&self.base_url, batch_status_link));
+            }
+
+            let step = match batch_status.data.first() {
+                Some(status) => poller.next_step(status)?,
+                None => poller.next_empty_step(batch_status_link)?,
+            };
+
+            match step {
+                PollStep::Done => break Ok(batch_status.data.swap_remove(0)),
+                PollStep::Recheck => {
+                    if !backoff.wait() {
+                        return Err(retries_exhausted_error(
+                            batch_status_link,
+                            backoff.max_retries(),
+                        ));
+                    }
+                    batch_status = self.submitter().status(&batch_status.link)?;
+                }
+            }
+        }
+    }
+}
+
+/// Where batch lists are submitted to, and their statuses checked
+///
+/// `ApiClient` implements this for the Sawtooth REST API, and submits through
+/// it by default. Another gateway can be targeted by implementing this and
+/// passing it to `ApiClient::with_submitter`, without changing the commands.
+pub trait Submitter {
+    /// Submits the batch list and returns the link to its batch status
+    fn submit(&self, batch_list: &BatchList) -> Result<String, CliError>;
+
+    /// Fetches the batch statuses behind the given status link, waiting for
+    /// them to change on the server side
+    fn status(&self, batch_status_link: &str) -> Result<StatusData, CliError>;
+}
+
+impl Submitter for ApiClient {
+    fn submit(&self, batch_list: &BatchList) -> Result<String, CliError> {
+        let post_url = self.api_url("/batches")?;
+        let bytes = batch_list.write_to_bytes()?;
+
+        let response = self
+            .client
+            .post(post_url)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(bytes)
+            .send()?;
+
+        let batch_link = self.read_json::<Link>(response)?;
+        Ok(batch_link.link)
+    }
+
+    /// Older REST APIs reject or ignore `wait`, in which case the status is
+    /// fetched without it from then on, and the client's backoff between
+    /// rechecks does the waiting instead
+    fn status(&self, batch_status_link: &str) -> Result<StatusData, CliError> {
+        if self.server_wait.load(Ordering::Relaxed) {
+            let link = self.api_url(&format!("{}{}", batch_status_link, "&wait=true"))?;
+
+            let started = Instant::now();
+            let response = self.client.get(link).send()?;
+            if response.status() != StatusCode::BAD_REQUEST {
+                let batch_status = self.read_json::<StatusData>(response)?;
+                if server_wait_ignored(&batch_status, started.elapsed()) {
+                    self.server_wait.store(false, Ordering::Relaxed);
+                }
+                return Ok(batch_status);
+            }
+            self.server_wait.store(false, Ordering::Relaxed);
+        }
+
+        let response = self.client.get(self.api_url(batch_status_link)?).send()?;
+
+        let batch_status = self.read_json::<StatusData>(response)?;
+        Ok(batch_status)
+    }
+}
+
+/// Returns whether a batch status request which asked the REST API to wait
+/// came back too quickly, with a batch still pending, for the wait to have
+/// happened
+fn server_wait_ignored(batch_status: &StatusData, elapsed: time::Duration) -> bool {
+    elapsed < IGNORED_WAIT_THRESHOLD
+        && batch_status
+            .data
+            .iter()
+            .any(|status| status.status == "PENDING")
+}
+
+/// Returns the error reported when the user interrupts the wait for a batch
+///
+/// The REST API may give the batch status link relative to itself, so it is
+/// resolved against the base URL before the batch id is read from it.
+fn interrupted_error(base_url: &str, batch_status_link: &str) -> CliError {
+    let batch_id = Url::parse(base_url)
+        .and_then(|base_url| base_url.join(batch_status_link))
+        .ok()
+        .and_then(|url| {
+            url.query_pairs()
+                .find(|(name, _)| name == "id")
+                .map(|(_, id)| id.into_owned())
+        });
+
+    CliError::InterruptedError(match batch_id {
+        Some(batch_id) => format!(
+            "Stopped waiting on batch {}, which was submitted. Run `csrc status {}` to check whether it was committed",
+            batch_id, batch_id
+        ),
+        None => format!(
+            "Stopped waiting on the submitted batch. Its status is at {}",
+            batch_status_link
+        ),
+    })
+}
+
+/// Returns the error reported when a batch is still in flight after
+/// `--max-retries` status checks
+fn retries_exhausted_error(batch_status_link: &str, max_retries: Option<u32>) -> CliError {
+    CliError::UserError(format!(
+        "Batch was still in flight after {} status checks. Its status is at {}",
+        max_retries.unwrap_or_default(),
+        batch_status_link
+    ))
+}
+
+/// Parses the `data` of a REST API response as the given type
+///
+/// # Errors
+///
+/// If a field is missing or of the wrong type, a `CliError::UserError` naming
+/// it is returned.
+pub fn parse_data<T: DeserializeOwned>(data: serde_json::Value) -> Result<T, CliError> {
+    serde_json::from_value(data).map_err(|err| {
+        CliError::UserError(format!("Unexpected response from the REST API: {}", err))
+    })
+}
+
+/// Returns the URL of the page after the one at `current`, given the
+/// `paging.next` link the REST API reported for it, or `None` on the last page
+///
+/// The link may be absolute or relative to the current page.
+fn next_page_url(current: &Url, next: Option<&str>) -> Result<Option<Url>, CliError> {
+    match next.map(str::trim) {
+        Some(next) if !next.is_empty() => current
+            .join(next)
+            .map(Some)
+            .map_err(|err| CliError::UserError(format!("Invalid paging link {}: {}", next, err))),
+        _ => Ok(None),
+    }
+}
+
+/// Returns the `--error-log` records of a rejected batch: one for the
+/// transaction of each entry, followed by one for each invalid transaction
+/// which was not created for an entry
+fn error_log_entries<'a>(
+    status: &'a Status,
+    txn_entries: &'a [(String, &'a str)],
+    timestamp: &str,
+) -> Vec<ErrorLogEntry<'a>> {
+    let invalid_message = |txn_id: &str| {
+        status
+            .invalid_transactions
+            .iter()
+            .find(|invalid_transaction| invalid_transaction.id == txn_id)
+            .map(|invalid_transaction| invalid_transaction.message.clone())
+    };
+    let rejected_with = status
+        .invalid_transactions
+        .iter()
+        .map(|invalid_transaction| invalid_transaction.id.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let entries = txn_entries.iter().map(|(txn_id, entry)| ErrorLogEntry {
+        timestamp: timestamp.to_string(),
+        batch_id: &status.id,
+        transaction_id: txn_id,
+        entry: Some(*entry),
+        message: invalid_message(txn_id).unwrap_or_else(|| {
+            format!(
+                "Rejected along with the invalid transactions of its batch: {}",
+                rejected_with
+            )
+        }),
     });
+    let unmatched = status
+        .invalid_transactions
+        .iter()
+        .filter(|invalid_transaction| {
+            !txn_entries
+                .iter()
+                .any(|(txn_id, _)| *txn_id == invalid_transaction.id)
+        })
+        .map(|invalid_transaction| ErrorLogEntry {
+            timestamp: timestamp.to_string(),
+            batch_id: &status.id,
+            transaction_id: &invalid_transaction.id,
+            entry: None,
+            message: invalid_transaction.message.clone(),
+        });
+    entries.chain(unmatched).collect()
+}
+
+fn parse_url(url: &str) -> Result<Url, CliError> {
+    Url::parse(url).map_err(|err| CliError::UserError(format!("Invalid URL: {}", err)))
+}
+
+/// Returns the response if the REST API reported success, otherwise an error
+/// containing the status code and the body of the response
+fn check_response(response: Response) -> Result<Response, CliError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().unwrap_or_default();
+    Err(CliError::UserError(format!(
+        "REST API responded with {}: {}",
+        status, body
+    )))
+}
+
+/// How long to keep rechecking a batch whose status is missing from the
+/// REST API's response
+const MISSING_STATUS_TIMEOUT: time::Duration = time::Duration::from_secs(30);
+
+/// The fraction of the poll interval by which each wait is randomly lengthened
+/// or shortened, in thousandths
+const JITTER_PER_MILLE: u64 = 200;
+
+/// Spaces out batch status checks
+///
+/// Each wait is the poll interval, randomly lengthened or shortened by up to
+/// 20%, so that many clients polling one validator (such as parallel CI jobs)
+/// drift apart instead of checking in lockstep. If `max_retries` is set, only
+/// that many waits are allowed.
+///
+/// The jitter only needs to differ between processes, not be unpredictable,
+/// so it comes from a xorshift generator seeded from the clock rather than a
+/// cryptographic one.
+#[derive(Debug)]
+pub struct Backoff {
+    interval: time::Duration,
+    max_retries: Option<u32>,
+    retries: u32,
+    rng: u64,
+}
+
+impl Backoff {
+    fn new(interval: time::Duration, max_retries: Option<u32>, seed: u64) -> Backoff {
+        Backoff {
+            interval,
+            max_retries,
+            retries: 0,
+            // xorshift never leaves a zero state
+            rng: seed | 1,
+        }
+    }
+
+    /// Returns the maximum number of waits, if there is one
+    pub fn max_retries(&self) -> Option<u32> {
+        self.max_retries
+    }
+
+    /// Sleeps before the next status check, returning false without sleeping
+    /// if `--max-retries` waits have already been made
+    pub fn wait(&mut self) -> bool {
+        self.wait_with(thread::sleep)
+    }
+
+    fn wait_with<S: FnOnce(time::Duration)>(&mut self, sleep: S) -> bool {
+        if self.max_retries.map_or(false, |max| self.retries >= max) {
+            return false;
+        }
+        self.retries += 1;
+
+        let spread = self.next_random() % (2 * JITTER_PER_MILLE + 1);
+        let per_mille = u128::from(1000 - JITTER_PER_MILLE + spread);
+        sleep(time::Duration::from_nanos(
+            (self.interval.as_nanos() * per_mille / 1000) as u64,
+        ));
+        true
+    }
+
+    /// Advances the xorshift64 generator
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+/// Returns a seed for the jitter of a new `Backoff`, which differs between
+/// processes started at the same moment
+fn jitter_seed() -> u64 {
+    let nanos = time::SystemTime::now()
+        .duration_since(time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos ^ (u64::from(std::process::id()) << 32)
+}
+
+/// The next step to take while polling the status of a batch
+#[derive(Debug, PartialEq)]
+enum PollStep {
+    /// The batch reached a final state (COMMITTED or INVALID)
+    Done,
+    /// The batch is still in flight and its status should be checked again
+    Recheck,
+}
+
+/// Tracks the statuses seen while polling a batch, deciding when to stop
+///
+/// `PENDING` and `STATUS_UNSET` are rechecked until the batch is committed or
+/// rejected. `UNKNOWN` usually means the validator never received the batch,
+/// in which case it will never be committed, so it is only rechecked once to
+/// confirm before giving up.
+///
+/// The REST API may briefly report no status at all right after a batch is
+/// submitted, so an empty response is rechecked as well, until no status has
+/// been reported for `MISSING_STATUS_TIMEOUT`.
+#[derive(Debug, Default)]
+struct StatusPoller {
+    unknown_seen: bool,
+    missing_since: Option<Instant>,
+}
+
+impl StatusPoller {
+    fn next_step(&mut self, status: &Status) -> Result<PollStep, CliError> {
+        match status.status.as_ref() {
+            "COMMITTED" | "INVALID" => Ok(PollStep::Done),
+            "UNKNOWN" => {
+                if self.unknown_seen {
+                    Err(CliError::UserError(format!(
+                        "batch unknown to validator: {}",
+                        status.id
+                    )))
+                } else {
+                    self.unknown_seen = true;
+                    Ok(PollStep::Recheck)
+                }
+            }
+            // "PENDING" case where we should recheck
+            // "STATUS_UNSET" case where we should recheck
+            _ => Ok(PollStep::Recheck),
+        }
+    }
+
+    fn next_empty_step(&mut self, batch_status_link: &str) -> Result<PollStep, CliError> {
+        let missing_since = *self.missing_since.get_or_insert_with(Instant::now);
+        if missing_since.elapsed() >= MISSING_STATUS_TIMEOUT {
+            return Err(CliError::UserError(format!(
+                "No batch status was reported for {} after {} seconds",
+                batch_status_link,
+                MISSING_STATUS_TIMEOUT.as_secs()
+            )));
+        }
+
+        Ok(PollStep::Recheck)
+    }
+}
+
+/// Returns the value of the `--max-invalid-display` argument
+pub fn max_invalid_display_from_args(args: &ArgMatches) -> Result<usize, CliError> {
+    value_t!(args, "max_invalid_display", usize).map_err(|_| {
+        CliError::InvalidInputError(String::from(
+            "Invalid value for --max-invalid-display. Please provide a number",
+        ))
+    })
+}
+
+/// Appends a record of the batch list about to be submitted to the trace file,
+/// as a line of JSON
+fn write_trace_entry(
+    trace_file: &str,
+    batch_list: &BatchList,
+    batch_list_bytes: &[u8],
+) -> Result<(), CliError> {
+    let entry = TraceEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        command: env::args().skip(1).collect(),
+        batch_ids: batch_list
+            .get_batches()
+            .iter()
+            .map(|batch| batch.get_header_signature())
+            .collect(),
+        transaction_ids: batch_list
+            .get_batches()
+            .iter()
+            .flat_map(|batch| batch.get_transactions())
+            .map(|txn| txn.get_header_signature())
+            .collect(),
+        batch_list: bytes_to_hex_str(batch_list_bytes),
+    };
+    let line = output::to_json(&entry, false)?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(trace_file)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Returns the number of concurrent reads selected by `--concurrency`
+///
+/// # Errors
+///
+/// If the value is not a positive number, a `CliError::InvalidInputError` is
+/// returned.
+pub fn concurrency_from_args(args: &ArgMatches) -> Result<usize, CliError> {
+    match value_t!(args, "concurrency", usize) {
+        Ok(concurrency) if concurrency > 0 => Ok(concurrency),
+        _ => Err(CliError::InvalidInputError(String::from(
+            "Invalid value for --concurrency. Please provide a positive number",
+        ))),
+    }
+}
+
+/// Prints the invalid transactions of a rejected batch to stderr, one per
+/// line as described by `describe`, showing at most `max_display` of them
+pub fn report_invalid_transactions<F>(status: &Status, max_display: usize, describe: F)
+where
+    F: Fn(&InvalidTransactions) -> String,
+{
+    for invalid_transaction in status.invalid_transactions.iter().take(max_display) {
+        eprintln!("{}", describe(invalid_transaction));
+    }
+
+    if status.invalid_transactions.len() > max_display {
+        eprintln!(
+            "... and {} more",
+            status.invalid_transactions.len() - max_display
+        );
+    }
+}
+
+/// Returns the error reported for a batch that was rejected as INVALID
+pub fn invalid_transaction_error(status: &Status) -> CliError {
+    match status.invalid_transactions.get(0) {
+        Some(invalid_transaction) if status.invalid_transactions.len() == 1 => {
+            CliError::InvalidTransactionError(invalid_transaction.message.clone())
+        }
+        Some(invalid_transaction) => CliError::InvalidTransactionError(format!(
+            "{} transactions were invalid, starting with: {}",
+            status.invalid_transactions.len(),
+            invalid_transaction.message
+        )),
+        None => CliError::InvalidTransactionError(format!("Batch {} is invalid", status.id)),
+    }
+}
+
+/// Returns the path prefix of the REST API with a leading and no trailing '/',
+/// or empty if the REST API is mounted at the root
+fn normalize_api_prefix(api_prefix: &str) -> String {
+    let api_prefix = api_prefix.trim_matches('/');
+    if api_prefix.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", api_prefix)
+    }
+}
+
+/// Parses a size given as a number of bytes, optionally followed by one of the
+/// binary units K, M or G
+///
+/// # Errors
+///
+/// If the size is not a positive number of bytes, a
+/// `CliError::InvalidInputError` is returned.
+fn parse_body_size(size: &str) -> Result<u64, CliError> {
+    let invalid_size = || {
+        CliError::InvalidInputError(format!(
+            "Invalid value for --max-body-size: {}. Please provide a number of bytes, optionally followed by K, M or G",
+            size
+        ))
+    };
+
+    let size = size.trim();
+    let (number, multiplier) = match size.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (&size[..size.len() - 1], 1024),
+        Some('M') => (&size[..size.len() - 1], 1024 * 1024),
+        Some('G') => (&size[..size.len() - 1], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+
+    match number.parse::<u64>() {
+        Ok(number) if number > 0 => number.checked_mul(multiplier).ok_or_else(invalid_size),
+        _ => Err(invalid_size()),
+    }
+}
+
+/// Parses a header given as "Name: Value"
+///
+/// # Errors
+///
+/// If the header is not of that form, or its name or value contains characters
+/// not allowed in HTTP headers, a `CliError::UserError` is returned.
+fn parse_header(header: &str) -> Result<(HeaderName, HeaderValue), CliError> {
+    let invalid_header = || {
+        CliError::UserError(format!(
+            "Invalid header: {}. Please provide headers as \"Name: Value\"",
+            header
+        ))
+    };
+
+    let mut parts = header.splitn(2, ':');
+    let name = parts.next().unwrap_or("").trim();
+    let value = parts.next().ok_or_else(invalid_header)?.trim();
+
+    let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid_header())?;
+    let value = HeaderValue::from_str(value).map_err(|_| invalid_header())?;
+    Ok((name, value))
+}
+
+/// Returns the block among those listed by the REST API's `/blocks` endpoint
+/// which includes the given batch, if any
+///
+/// # Errors
+///
+/// If a block is not in the form the REST API renders blocks in, a
+/// `CliError::UserError` is returned.
+fn find_block_in(
+    blocks: &[serde_json::Value],
+    batch_id: &str,
+) -> Result<Option<BlockInclusion>, CliError> {
+    let unexpected = || {
+        CliError::UserError(String::from(
+            "REST API returned a block in an unexpected form",
+        ))
+    };
+
+    for block in blocks {
+        let header = &block["header"];
+        let includes_batch = header["batch_ids"]
+            .as_array()
+            .ok_or_else(unexpected)?
+            .iter()
+            .any(|id| id.as_str() == Some(batch_id));
+        if !includes_batch {
+            continue;
+        }
+
+        // The REST API renders 64 bit integers as strings
+        let block_num = match &header["block_num"] {
+            serde_json::Value::String(block_num) => block_num.parse().ok(),
+            block_num => block_num.as_u64(),
+        }
+        .ok_or_else(unexpected)?;
+        let block_id = block["header_signature"].as_str().ok_or_else(unexpected)?;
+        return Ok(Some(BlockInclusion {
+            block_id: block_id.to_string(),
+            block_num,
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Returns the header signatures of every transaction in the batch list
+fn transaction_ids(batch_list: &BatchList) -> Vec<String> {
+    batch_list
+        .get_batches()
+        .iter()
+        .flat_map(|batch| batch.get_transactions())
+        .map(|txn| txn.get_header_signature().to_string())
+        .collect()
+}
+
+/// A `Submitter` for testing commands without a REST API
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+
+    use sawtooth_sdk::messages::batch::Batch;
+    use sawtooth_sdk::messages::transaction::Transaction;
+    use std::sync::Mutex;
+
+    /// Accepts every batch, and reports the given statuses for it in turn,
+    /// repeating the last one once they run out
+    ///
+    /// An INVALID status is reported with a single invalid transaction,
+    /// `txn-id`, whose message is "Invalid".
+    pub struct MockSubmitter {
+        statuses: Vec<&'static str>,
+        status_checks: Mutex<usize>,
+    }
+
+    impl MockSubmitter {
+        pub fn new(statuses: &[&'static str]) -> MockSubmitter {
+            MockSubmitter {
+                statuses: statuses.to_vec(),
+                status_checks: Mutex::new(0),
+            }
+        }
+
+        /// Returns how many times a batch status was checked
+        pub fn status_checks(&self) -> usize {
+            *self.status_checks.lock().unwrap()
+        }
+    }
+
+    impl Submitter for MockSubmitter {
+        fn submit(&self, batch_list: &BatchList) -> Result<String, CliError> {
+            Ok(format!(
+                "/batch_statuses?id={}",
+                batch_list.get_batches()[0].get_header_signature()
+            ))
+        }
+
+        fn status(&self, batch_status_link: &str) -> Result<StatusData, CliError> {
+            let mut status_checks = self.status_checks.lock().unwrap();
+            let status = self.statuses[(*status_checks).min(self.statuses.len() - 1)];
+            *status_checks += 1;
+
+            let invalid_transactions = if status == "INVALID" {
+                vec![InvalidTransactions {
+                    id: String::from("txn-id"),
+                    message: String::from("Invalid"),
+                }]
+            } else {
+                vec![]
+            };
+            Ok(StatusData {
+                data: vec![Status {
+                    id: String::from("batch-id"),
+                    invalid_transactions,
+                    status: String::from(status),
+                    transaction_ids: vec![],
+                    block: None,
+                    elapsed: None,
+                }],
+                link: batch_status_link.to_string(),
+            })
+        }
+    }
+
+    /// Returns a client which submits through a `MockSubmitter` reporting the
+    /// given statuses, and barely waits between status checks
+    pub fn client(statuses: &[&'static str]) -> ApiClient {
+        client_with(Arc::new(MockSubmitter::new(statuses)))
+    }
+
+    /// Returns a client which submits through the given `MockSubmitter`, and
+    /// barely waits between status checks
+    pub fn client_with(submitter: Arc<MockSubmitter>) -> ApiClient {
+        let mut client = ApiClient::new(DEFAULT_URL, HeaderMap::new(), false)
+            .unwrap()
+            .with_submitter(submitter);
+        client.poll_interval = time::Duration::from_millis(1);
+        client
+    }
+
+    /// Returns a batch list holding a single batch, `batch-id`, of a single
+    /// transaction, `txn-id`
+    pub fn batch_list() -> BatchList {
+        let mut txn = Transaction::new();
+        txn.set_header_signature(String::from("txn-id"));
+
+        let mut batch = Batch::new();
+        batch.set_header_signature(String::from("batch-id"));
+        batch.set_transactions(protobuf::RepeatedField::from_vec(vec![txn]));
+
+        let mut batch_list = BatchList::new();
+        batch_list.set_batches(protobuf::RepeatedField::from_vec(vec![batch]));
+        batch_list
+    }
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(status: &str) -> Status {
+        Status {
+            id: String::from("batch-id"),
+            invalid_transactions: vec![],
+            status: String::from(status),
+            transaction_ids: vec![],
+            block: None,
+            elapsed: None,
+        }
+    }
+
+    #[test]
+    fn submit_and_wait_test() {
+        let status = mock::client(&["COMMITTED"])
+            .submit_and_wait(&mock::batch_list())
+            .unwrap();
+        assert_eq!(status.status, "COMMITTED");
+        assert_eq!(status.transaction_ids, vec![String::from("txn-id")]);
+        assert!(status.elapsed.is_some());
+
+        match mock::client(&["INVALID"]).submit_and_wait(&mock::batch_list()) {
+            Err(CliError::InvalidTransactionError(message)) => assert_eq!(message, "Invalid"),
+            result => panic!("Expected an invalid transaction error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn pending_then_committed_test() {
+        let submitter = Arc::new(mock::MockSubmitter::new(&[
+            "PENDING",
+            "PENDING",
+            "COMMITTED",
+        ]));
+        let client = mock::client_with(submitter.clone());
+
+        let status = client.submit_and_wait(&mock::batch_list()).unwrap();
+        assert_eq!(status.status, "COMMITTED");
+        assert_eq!(submitter.status_checks(), 3);
+    }
+
+    #[test]
+    fn max_retries_test() {
+        let mut client = mock::client(&["PENDING"]);
+        client.max_retries = Some(2);
+
+        match client.submit_and_wait(&mock::batch_list()) {
+            Err(CliError::UserError(message)) => assert!(message.contains("2 status checks")),
+            result => panic!("Expected the batch to be given up on, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn find_block_in_test() {
+        let blocks = vec![
+            serde_json::json!({"header_signature": "block-2", "header": {"block_num": "2", "batch_ids": ["other"]}}),
+            serde_json::json!({"header_signature": "block-1", "header": {"block_num": "1", "batch_ids": ["batch-id"]}}),
+        ];
+
+        assert_eq!(
+            find_block_in(&blocks, "batch-id").unwrap(),
+            Some(BlockInclusion {
+                block_id: String::from("block-1"),
+                block_num: 1,
+            })
+        );
+        assert_eq!(find_block_in(&blocks, "missing").unwrap(), None);
+
+        // A block without its batch ids can't be searched
+        let blocks = vec![serde_json::json!({"header_signature": "block-1", "block_num": 1})];
+        assert!(find_block_in(&blocks, "batch-id").is_err());
+    }
+
+    #[test]
+    fn client_scheme_test() {
+        assert!(ApiClient::new("https://rest-api:8443", HeaderMap::new(), false).is_ok());
+        assert!(ApiClient::new("https://rest-api:8443", HeaderMap::new(), true).is_ok());
+        match ApiClient::new("ftp://rest-api", HeaderMap::new(), false) {
+            Err(CliError::UserError(message)) => assert!(message.contains("Unsupported scheme")),
+            result => panic!(
+                "Expected the scheme to be rejected, got {:?}",
+                result.is_ok()
+            ),
+        }
+    }
+
+    #[test]
+    fn server_wait_ignored_test() {
+        let batch_status = |statuses: &[&str]| StatusData {
+            data: statuses.iter().map(|s| status(s)).collect(),
+            link: String::from("/batch_statuses?id=batch-id"),
+        };
+        let instantly = time::Duration::from_millis(5);
+
+        assert!(server_wait_ignored(&batch_status(&["PENDING"]), instantly));
+        // A wait that ran its course, or ended because the batch was settled,
+        // was honored
+        assert!(!server_wait_ignored(
+            &batch_status(&["PENDING"]),
+            IGNORED_WAIT_THRESHOLD
+        ));
+        assert!(!server_wait_ignored(
+            &batch_status(&["COMMITTED"]),
+            instantly
+        ));
+    }
+
+    #[test]
+    fn backoff_jitter_test() {
+        let interval = time::Duration::from_millis(1000);
+        let mut backoff = Backoff::new(interval, None, 42);
+        let mut waits = vec![];
+        for _ in 0..100 {
+            assert!(backoff.wait_with(|wait| waits.push(wait)));
+        }
+
+        assert!(waits.iter().all(|wait| *wait >= interval * 4 / 5));
+        assert!(waits.iter().all(|wait| *wait <= interval * 6 / 5));
+        assert!(waits.iter().any(|wait| *wait != waits[0]));
+    }
+
+    #[test]
+    fn backoff_max_retries_test() {
+        let mut backoff = Backoff::new(time::Duration::from_millis(10), Some(2), 42);
+        let mut sleeps = 0;
+        assert!(backoff.wait_with(|_| sleeps += 1));
+        assert!(backoff.wait_with(|_| sleeps += 1));
+        assert!(!backoff.wait_with(|_| sleeps += 1));
+        assert_eq!(sleeps, 2);
+    }
 
-    let batch_status = core.run(work)?;
-    Ok(batch_status)
+    #[test]
+    fn committed_status_is_done_test() {
+        let mut poller = StatusPoller::default();
+        assert_eq!(
+            poller.next_step(&status("COMMITTED")).unwrap(),
+            PollStep::Done
+        );
+    }
+
+    #[test]
+    fn invalid_status_is_done_test() {
+        let mut poller = StatusPoller::default();
+        assert_eq!(
+            poller.next_step(&status("INVALID")).unwrap(),
+            PollStep::Done
+        );
+    }
+
+    #[test]
+    fn pending_status_is_rechecked_test() {
+        let mut poller = StatusPoller::default();
+        for _ in 0..3 {
+            assert_eq!(
+                poller.next_step(&status("PENDING")).unwrap(),
+                PollStep::Recheck
+            );
+        }
+    }
+
+    #[test]
+    fn status_unset_is_rechecked_test() {
+        let mut poller = StatusPoller::default();
+        for _ in 0..3 {
+            assert_eq!(
+                poller.next_step(&status("STATUS_UNSET")).unwrap(),
+                PollStep::Recheck
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_status_is_rechecked_once_test() {
+        let mut poller = StatusPoller::default();
+        assert_eq!(
+            poller.next_step(&status("UNKNOWN")).unwrap(),
+            PollStep::Recheck
+        );
+        assert!(matches!(
+            poller.next_step(&status("UNKNOWN")),
+            Err(CliError::UserError(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_then_committed_status_test() {
+        let mut poller = StatusPoller::default();
+        assert_eq!(
+            poller.next_step(&status("UNKNOWN")).unwrap(),
+            PollStep::Recheck
+        );
+        assert_eq!(
+            poller.next_step(&status("COMMITTED")).unwrap(),
+            PollStep::Done
+        );
+    }
+
+    #[test]
+    fn missing_status_is_rechecked_test() {
+        let mut poller = StatusPoller::default();
+        for _ in 0..3 {
+            assert_eq!(
+                poller
+                    .next_empty_step("/batch_statuses?id=batch-id")
+                    .unwrap(),
+                PollStep::Recheck
+            );
+        }
+    }
+
+    #[test]
+    fn missing_status_times_out_test() {
+        let mut poller = StatusPoller {
+            missing_since: Instant::now().checked_sub(MISSING_STATUS_TIMEOUT),
+            ..StatusPoller::default()
+        };
+        assert!(poller
+            .next_empty_step("/batch_statuses?id=batch-id")
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_transaction_error_test() {
+        let mut invalid = status("INVALID");
+        invalid.invalid_transactions.push(InvalidTransactions {
+            id: String::from("txn-id"),
+            message: String::from("Organization already exists"),
+        });
+
+        match invalid_transaction_error(&invalid) {
+            CliError::InvalidTransactionError(message) => {
+                assert_eq!(message, "Organization already exists")
+            }
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn parse_header_test() {
+        let (name, value) =
+            parse_header("Authorization: Bearer abc:123").expect("Failed to parse header");
+        assert_eq!(name, "authorization");
+        assert_eq!(value, "Bearer abc:123");
+
+        assert!(parse_header("Authorization").is_err());
+        assert!(parse_header(": value").is_err());
+        assert!(parse_header("Bad Name: value").is_err());
+    }
+
+    #[test]
+    fn parse_body_size_test() {
+        assert_eq!(parse_body_size("1500").unwrap(), 1500);
+        assert_eq!(parse_body_size("16k").unwrap(), 16 * 1024);
+        assert_eq!(parse_body_size("64M").unwrap(), DEFAULT_MAX_BODY_SIZE);
+
+        assert!(parse_body_size("").is_err());
+        assert!(parse_body_size("0").is_err());
+        assert!(parse_body_size("M").is_err());
+        assert!(parse_body_size("12T").is_err());
+    }
+
+    #[test]
+    fn normalize_api_prefix_test() {
+        assert_eq!(normalize_api_prefix("/api"), "/api");
+        assert_eq!(
+            normalize_api_prefix("consensource/api/"),
+            "/consensource/api"
+        );
+        assert_eq!(normalize_api_prefix("/"), "");
+    }
+
+    #[test]
+    fn error_log_entries_test() {
+        let mut rejected = status("INVALID");
+        rejected.invalid_transactions = vec![
+            InvalidTransactions {
+                id: String::from("txn-2"),
+                message: String::from("Factory already exists"),
+            },
+            InvalidTransactions {
+                id: String::from("txn-9"),
+                message: String::from("Unknown"),
+            },
+        ];
+        let txn_entries = vec![
+            (String::from("txn-1"), "factory-1"),
+            (String::from("txn-2"), "factory-2"),
+        ];
+
+        let entries = error_log_entries(&rejected, &txn_entries, "now");
+        // Every entry of the atomic batch failed, not only the invalid one
+        let logged: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.transaction_id, entry.entry))
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                ("txn-1", Some("factory-1")),
+                ("txn-2", Some("factory-2")),
+                ("txn-9", None)
+            ]
+        );
+        assert!(entries[0].message.contains("txn-2"));
+        assert_eq!(entries[1].message, "Factory already exists");
+    }
+
+    #[test]
+    fn output_batch_appends_test() {
+        let path = env::temp_dir().join(format!("csrc-output-batch-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut client = mock::client(&[]);
+        client.batch_sink.output_batch = Some(path.to_string());
+
+        // Each chunk of a batch file is written as a batch list of its own
+        let mut batch = sawtooth_sdk::messages::batch::Batch::new();
+        batch.set_header_signature(String::from("batch-id-2"));
+        let mut second = BatchList::new();
+        second.set_batches(protobuf::RepeatedField::from_vec(vec![batch]));
+        client.submit_and_get_status(&mock::batch_list()).unwrap();
+        let status = client.submit_and_get_status(&second).unwrap();
+        assert_eq!(status.id, "batch-id-2");
+
+        let written =
+            batch_file::decode_batch_list(&fs::read(path).unwrap(), BatchFormat::Protobuf).unwrap();
+        fs::remove_file(path).unwrap();
+        let batch_ids: Vec<&str> = written
+            .get_batches()
+            .iter()
+            .map(|batch| batch.get_header_signature())
+            .collect();
+        assert_eq!(batch_ids, vec!["batch-id", "batch-id-2"]);
+    }
+
+    #[test]
+    fn worker_error_keeps_type_test() {
+        let err = CliError::from(WorkerError::from(CliError::UserError(String::from(
+            "Certificate cert-1 does not exist",
+        ))));
+        match err {
+            CliError::UserError(message) => {
+                assert_eq!(message, "Certificate cert-1 does not exist")
+            }
+            other => panic!("Expected a UserError, got {:?}", other),
+        }
+
+        let err = CliError::from(WorkerError::from(CliError::IoError(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            "timed out",
+        ))));
+        assert!(matches!(err, CliError::IoError(_)));
+
+        // Nothing is fetched, so no worker is started
+        assert!(mock::client(&[])
+            .fetch_data_concurrently(&[], 4)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn next_page_url_test() {
+        let current = Url::parse("http://localhost:9009/api/certificates?limit=100").unwrap();
+        assert_eq!(
+            next_page_url(&current, Some("/api/certificates?limit=100&offset=100"))
+                .unwrap()
+                .map(String::from),
+            Some(String::from(
+                "http://localhost:9009/api/certificates?limit=100&offset=100"
+            ))
+        );
+        assert_eq!(
+            next_page_url(
+                &current,
+                Some("http://rest-api:9009/api/certificates?offset=100")
+            )
+            .unwrap()
+            .map(String::from),
+            Some(String::from(
+                "http://rest-api:9009/api/certificates?offset=100"
+            ))
+        );
+        assert!(next_page_url(&current, Some("")).unwrap().is_none());
+        assert!(next_page_url(&current, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn interrupted_error_names_batch_test() {
+        for link in &[
+            "http://localhost:9009/api/batch_statuses?id=abc123&wait",
+            "/api/batch_statuses?id=abc123&wait",
+            "batch_statuses?id=abc123",
+        ] {
+            match interrupted_error("http://localhost:9009", link) {
+                CliError::InterruptedError(message) => {
+                    assert!(message.contains("csrc status abc123"), "{}", message)
+                }
+                other => panic!("Expected an InterruptedError, got {:?}", other),
+            }
+        }
+    }
 }
@@ -16,7 +16,9 @@
 //! Transactions
 
 use crate::error::CliError;
+use crate::key::{normalize_public_key, SECP_256K1};
 
+use clap::ArgMatches;
 use common::addressing;
 use common::proto::payload;
 use crypto::digest::Digest;
@@ -25,8 +27,70 @@ use protobuf::{Message, RepeatedField};
 use sawtooth_sdk::messages::batch::{Batch, BatchHeader, BatchList};
 use sawtooth_sdk::messages::transaction::{Transaction, TransactionHeader};
 use sawtooth_sdk::signing::Signer;
+use std::collections::HashMap;
 use std::time::Instant;
 
+/// Settings applied to the header of every transaction created by a command
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransactionOptions {
+    /// The transaction family the transactions target
+    pub family_name: String,
+    /// The transaction family version the transactions target
+    pub family_version: String,
+    /// The name of the key used to sign batches, if it differs from the
+    /// transaction signing key
+    pub batcher_key: Option<String>,
+    /// The algorithm the transactions and batches are signed with
+    pub signing_algorithm: String,
+    /// The public key transactions and batches are built for when they are
+    /// left unsigned with `--sign-later`, so that no private key is loaded
+    pub unsigned_public_key: Option<String>,
+}
+
+impl Default for TransactionOptions {
+    fn default() -> Self {
+        TransactionOptions {
+            family_name: String::from(addressing::FAMILY_NAMESPACE),
+            family_version: String::from(addressing::FAMILY_VERSION),
+            batcher_key: None,
+            signing_algorithm: String::from(SECP_256K1),
+            unsigned_public_key: None,
+        }
+    }
+}
+
+impl TransactionOptions {
+    /// Returns the transaction options selected by the command line arguments
+    ///
+    /// # Errors
+    ///
+    /// If `--family-version` is given but empty, or `--public-key` is not a
+    /// secp256k1 public key, a `CliError::InvalidInputError` is returned.
+    pub fn from_args(args: &ArgMatches) -> Result<TransactionOptions, CliError> {
+        let mut options = TransactionOptions::default();
+
+        if let Some(family_version) = args.value_of("family_version") {
+            if family_version.trim().is_empty() {
+                return Err(CliError::InvalidInputError(String::from(
+                    "The family version must not be empty",
+                )));
+            }
+            options.family_version = family_version.trim().to_string();
+        }
+
+        options.batcher_key = args.value_of("batcher_key").map(String::from);
+        if let Some(signing_algorithm) = args.value_of("signing_algorithm") {
+            options.signing_algorithm = signing_algorithm.to_string();
+        }
+        options.unsigned_public_key = args
+            .value_of("signer_public_key")
+            .map(normalize_public_key)
+            .transpose()?;
+
+        Ok(options)
+    }
+}
+
 /// Creates a nonce appropriate for a TransactionHeader
 fn create_nonce() -> String {
     let elapsed = Instant::now().elapsed();
@@ -38,7 +102,7 @@ fn create_nonce() -> String {
 /// # Arguments
 ///
 /// * `b` - input bytes
-fn bytes_to_hex_str(b: &[u8]) -> String {
+pub fn bytes_to_hex_str(b: &[u8]) -> String {
     b.iter()
         .map(|b| format!("{:02x}", b))
         .collect::<Vec<_>>()
@@ -51,6 +115,13 @@ fn bytes_to_hex_str(b: &[u8]) -> String {
 ///
 /// * `payload` - a fully populated identity payload
 /// * `signer` - the signer to be used to sign the transaction
+/// * `batcher` - the signer that will sign the batch containing the
+///               transaction, if it differs from `signer`
+/// * `inputs` - the state addresses the transaction reads from
+/// * `outputs` - the state addresses the transaction writes to
+/// * `dependencies` - the header signatures of transactions which must be
+///                    processed before this one
+/// * `options` - the settings applied to the transaction header
 ///
 /// # Errors
 ///
@@ -62,36 +133,133 @@ fn bytes_to_hex_str(b: &[u8]) -> String {
 pub fn create_transaction(
     payload: &payload::CertificateRegistryPayload,
     signer: &Signer,
+    batcher: Option<&Signer>,
     inputs: Vec<String>,
     outputs: Vec<String>,
+    dependencies: Vec<String>,
+    options: &TransactionOptions,
 ) -> Result<Transaction, CliError> {
+    create_transaction_from_bytes(
+        payload.write_to_bytes()?,
+        signer,
+        batcher,
+        inputs,
+        outputs,
+        dependencies,
+        options,
+    )
+}
+
+/// Returns a Transaction for the given serialized payload and Signer
+///
+/// This allows creating transactions for families other than ConsenSource,
+/// whose family name is then set through `options`.
+///
+/// # Arguments
+///
+/// * `payload_bytes` - a serialized payload
+/// * `signer` - the signer to be used to sign the transaction
+/// * `batcher` - the signer that will sign the batch containing the
+///               transaction, if it differs from `signer`
+/// * `inputs` - the state addresses the transaction reads from
+/// * `outputs` - the state addresses the transaction writes to
+/// * `dependencies` - the header signatures of transactions which must be
+///                    processed before this one
+/// * `options` - the settings applied to the transaction header
+///
+/// # Errors
+///
+/// If an error occurs during serialization of the internally created
+/// `TransactionHeader`, a `CliError::ProtobufError` is returned.
+///
+/// If a signing error occurs, a `CliError::SigningError` is returned.
+pub fn create_transaction_from_bytes(
+    payload_bytes: Vec<u8>,
+    signer: &Signer,
+    batcher: Option<&Signer>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    dependencies: Vec<String>,
+    options: &TransactionOptions,
+) -> Result<Transaction, CliError> {
+    let txn_header = create_transaction_header(
+        &payload_bytes,
+        &signer.get_public_key()?.as_hex(),
+        &batcher.unwrap_or(signer).get_public_key()?.as_hex(),
+        inputs,
+        outputs,
+        dependencies,
+        options,
+    );
+
     let mut txn = Transaction::new();
+    txn.set_payload(payload_bytes);
+
+    let txn_header_bytes = txn_header.write_to_bytes()?;
+    txn.set_header(txn_header_bytes.clone());
+
+    let b: &[u8] = &txn_header_bytes;
+    txn.set_header_signature(signer.sign(b)?);
+
+    Ok(txn)
+}
+
+/// Returns the header of a transaction carrying the given serialized payload
+///
+/// The header names the keys which will sign the transaction and its batch,
+/// but is not signed itself.
+///
+/// # Arguments
+///
+/// * `payload_bytes` - a serialized payload
+/// * `signer_public_key` - the public key the transaction will be signed with
+/// * `batcher_public_key` - the public key the batch containing the
+///                          transaction will be signed with
+/// * `inputs` - the state addresses the transaction reads from
+/// * `outputs` - the state addresses the transaction writes to
+/// * `dependencies` - the header signatures of transactions which must be
+///                    processed before this one
+/// * `options` - the settings applied to the transaction header
+pub fn create_transaction_header(
+    payload_bytes: &[u8],
+    signer_public_key: &str,
+    batcher_public_key: &str,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    dependencies: Vec<String>,
+    options: &TransactionOptions,
+) -> TransactionHeader {
     let mut txn_header = TransactionHeader::new();
 
-    txn_header.set_family_name(String::from(addressing::FAMILY_NAMESPACE));
-    txn_header.set_family_version(String::from(addressing::FAMILY_VERSION));
+    txn_header.set_family_name(options.family_name.clone());
+    txn_header.set_family_version(options.family_version.clone());
     txn_header.set_nonce(create_nonce());
-    txn_header.set_signer_public_key(signer.get_public_key()?.as_hex());
-    txn_header.set_batcher_public_key(signer.get_public_key()?.as_hex());
+    txn_header.set_signer_public_key(signer_public_key.to_string());
+    txn_header.set_batcher_public_key(batcher_public_key.to_string());
 
     txn_header.set_inputs(RepeatedField::from_vec(inputs));
     txn_header.set_outputs(RepeatedField::from_vec(outputs));
+    txn_header.set_dependencies(RepeatedField::from_vec(dependencies));
 
-    let payload_bytes = payload.write_to_bytes()?;
     let mut sha = Sha512::new();
-    sha.input(&payload_bytes);
+    sha.input(payload_bytes);
     let hash: &mut [u8] = &mut [0; 64];
     sha.result(hash);
     txn_header.set_payload_sha512(bytes_to_hex_str(hash));
-    txn.set_payload(payload_bytes);
 
-    let txn_header_bytes = txn_header.write_to_bytes()?;
-    txn.set_header(txn_header_bytes.clone());
-
-    let b: &[u8] = &txn_header_bytes;
-    txn.set_header_signature(signer.sign(b)?);
+    txn_header
+}
 
-    Ok(txn)
+/// Returns the dependencies of the next transaction in a chain, so that it is
+/// only processed after the previous transaction, if any
+///
+/// # Arguments
+///
+/// * `previous` - the transaction the next transaction has to follow
+pub fn chain_dependencies(previous: Option<&Transaction>) -> Vec<String> {
+    previous
+        .map(|txn| vec![txn.get_header_signature().to_string()])
+        .unwrap_or_default()
 }
 
 /// Returns a Batch for the given Transaction and Signer
@@ -99,7 +267,8 @@ pub fn create_transaction(
 /// # Arguments
 ///
 /// * `txn` - a Transaction
-/// * `signer` - the signer to be used to sign the transaction
+/// * `signer` - the signer to be used to sign the batch
+/// * `batcher` - the signer to use in place of `signer`, if any
 ///
 /// # Errors
 ///
@@ -108,12 +277,17 @@ pub fn create_transaction(
 /// returned.
 ///
 /// If a signing error occurs, a `CliError::SigningError` is returned.
-pub fn create_batch(txn: Transaction, signer: &Signer) -> Result<Batch, CliError> {
+pub fn create_batch(
+    txn: Transaction,
+    signer: &Signer,
+    batcher: Option<&Signer>,
+) -> Result<Batch, CliError> {
+    let signer = batcher.unwrap_or(signer);
     let mut batch = Batch::new();
-    let mut batch_header = BatchHeader::new();
-
-    batch_header.set_transaction_ids(RepeatedField::from_vec(vec![txn.header_signature.clone()]));
-    batch_header.set_signer_public_key(signer.get_public_key()?.as_hex());
+    let batch_header = create_batch_header(
+        vec![txn.header_signature.clone()],
+        &signer.get_public_key()?.as_hex(),
+    );
     batch.set_transactions(RepeatedField::from_vec(vec![txn]));
 
     let batch_header_bytes = batch_header.write_to_bytes()?;
@@ -130,7 +304,8 @@ pub fn create_batch(txn: Transaction, signer: &Signer) -> Result<Batch, CliError
 /// # Arguments
 ///
 /// * `txns` - a vec of Transactions
-/// * `signer` - the signer to be used to sign the transaction
+/// * `signer` - the signer to be used to sign the batch
+/// * `batcher` - the signer to use in place of `signer`, if any
 ///
 /// # Errors
 ///
@@ -142,15 +317,16 @@ pub fn create_batch(txn: Transaction, signer: &Signer) -> Result<Batch, CliError
 pub fn create_batch_with_transactions(
     txns: Vec<Transaction>,
     signer: &Signer,
+    batcher: Option<&Signer>,
 ) -> Result<Batch, CliError> {
+    let signer = batcher.unwrap_or(signer);
     let mut batch = Batch::new();
-    let mut batch_header = BatchHeader::new();
-    batch_header.set_transaction_ids(RepeatedField::from_vec(
+    let batch_header = create_batch_header(
         txns.iter()
             .map(|txn| txn.header_signature.clone())
             .collect(),
-    ));
-    batch_header.set_signer_public_key(signer.get_public_key()?.as_hex());
+        &signer.get_public_key()?.as_hex(),
+    );
     batch.set_transactions(RepeatedField::from_vec(txns));
 
     let batch_header_bytes = batch_header.write_to_bytes()?;
@@ -162,6 +338,125 @@ pub fn create_batch_with_transactions(
     Ok(batch)
 }
 
+/// Returns the header of a batch of the transactions with the given ids,
+/// which will be signed with the given public key
+pub fn create_batch_header(transaction_ids: Vec<String>, signer_public_key: &str) -> BatchHeader {
+    let mut batch_header = BatchHeader::new();
+    batch_header.set_transaction_ids(RepeatedField::from_vec(transaction_ids));
+    batch_header.set_signer_public_key(signer_public_key.to_string());
+    batch_header
+}
+
+/// Returns a copy of the batch list whose transaction and batch signatures
+/// are placeholders, to be signed later with `sign_batch_list`
+///
+/// Each placeholder is the sha512 hash of the header it stands in for, so
+/// that placeholders are unique and transactions may still depend on one
+/// another.
+///
+/// # Errors
+///
+/// If a header cannot be read or written, a `CliError::ProtobufError` is
+/// returned.
+pub fn unsign_batch_list(batch_list: &BatchList) -> Result<BatchList, CliError> {
+    resign_batch_list(batch_list, |header_bytes| {
+        Ok(placeholder_signature(header_bytes))
+    })
+}
+
+/// Returns the placeholder standing in for the signature of the given header
+/// until it is signed with `sign_batch_list`: the header's sha512 hash
+pub fn placeholder_signature(header_bytes: &[u8]) -> String {
+    let mut sha = Sha512::new();
+    sha.input(header_bytes);
+    sha.result_str()
+}
+
+/// Returns a copy of the unsigned batch list, as written by
+/// `unsign_batch_list`, with every transaction and batch signed by `signer`
+///
+/// # Errors
+///
+/// If any transaction or batch is meant to be signed by another key, a
+/// `CliError::UserError` is returned. If a header cannot be read or written, a
+/// `CliError::ProtobufError` is returned, and if signing fails a
+/// `CliError::SigningError` is.
+pub fn sign_batch_list(batch_list: &BatchList, signer: &Signer) -> Result<BatchList, CliError> {
+    let public_key = signer.get_public_key()?.as_hex();
+    let wrong_key = |kind: &str, id: &str, key: &str| {
+        CliError::UserError(format!(
+            "{} {} is to be signed by {}, not by {}",
+            kind, id, key, public_key
+        ))
+    };
+
+    for batch in batch_list.get_batches() {
+        let batch_header: BatchHeader = protobuf::parse_from_bytes(batch.get_header())?;
+        if batch_header.get_signer_public_key() != public_key {
+            return Err(wrong_key(
+                "Batch",
+                batch.get_header_signature(),
+                batch_header.get_signer_public_key(),
+            ));
+        }
+
+        for txn in batch.get_transactions() {
+            let txn_header: TransactionHeader = protobuf::parse_from_bytes(txn.get_header())?;
+            if txn_header.get_signer_public_key() != public_key {
+                return Err(wrong_key(
+                    "Transaction",
+                    txn.get_header_signature(),
+                    txn_header.get_signer_public_key(),
+                ));
+            }
+        }
+    }
+
+    resign_batch_list(batch_list, |header_bytes| Ok(signer.sign(header_bytes)?))
+}
+
+/// Returns a copy of the batch list with every header signature replaced by
+/// `sign`, updating the ids transactions and batches refer to each other by
+fn resign_batch_list<F>(batch_list: &BatchList, sign: F) -> Result<BatchList, CliError>
+where
+    F: Fn(&[u8]) -> Result<String, CliError>,
+{
+    let mut batch_list = batch_list.clone();
+    let mut new_ids: HashMap<String, String> = HashMap::new();
+
+    for batch in batch_list.mut_batches().iter_mut() {
+        for txn in batch.mut_transactions().iter_mut() {
+            let mut txn_header: TransactionHeader = protobuf::parse_from_bytes(txn.get_header())?;
+            let dependencies = txn_header
+                .get_dependencies()
+                .iter()
+                .map(|id| new_ids.get(id).unwrap_or(id).clone())
+                .collect();
+            txn_header.set_dependencies(RepeatedField::from_vec(dependencies));
+
+            let txn_header_bytes = txn_header.write_to_bytes()?;
+            let new_id = sign(&txn_header_bytes)?;
+            new_ids.insert(txn.get_header_signature().to_string(), new_id.clone());
+            txn.set_header(txn_header_bytes);
+            txn.set_header_signature(new_id);
+        }
+
+        let mut batch_header: BatchHeader = protobuf::parse_from_bytes(batch.get_header())?;
+        let transaction_ids = batch
+            .get_transactions()
+            .iter()
+            .map(|txn| txn.get_header_signature().to_string())
+            .collect();
+        batch_header.set_transaction_ids(RepeatedField::from_vec(transaction_ids));
+
+        let batch_header_bytes = batch_header.write_to_bytes()?;
+        batch.set_header_signature(sign(&batch_header_bytes)?);
+        batch.set_header(batch_header_bytes);
+    }
+
+    Ok(batch_list)
+}
+
 /// Returns a BatchList containing the provided vector Batch structs
 ///
 /// # Arguments
@@ -194,8 +489,8 @@ mod tests {
     use common::proto::payload::{
         CertificateRegistryPayload, CertificateRegistryPayload_Action, CreateAgentAction,
     };
-    use sawtooth_sdk::messages::batch::Batch;
-    use sawtooth_sdk::messages::transaction::Transaction;
+    use sawtooth_sdk::messages::batch::{Batch, BatchHeader};
+    use sawtooth_sdk::messages::transaction::{Transaction, TransactionHeader};
     use sawtooth_sdk::signing;
     use sawtooth_sdk::signing::{CryptoFactory, Signer};
 
@@ -203,7 +498,7 @@ mod tests {
     fn create_transaction_test() {
         // Create test signer
         let context =
-            signing::create_context("secp256k1").expect("Failed to create secp256k1 context");
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
         let private_key = context
             .new_random_private_key()
             .expect("Failed to generate random private key");
@@ -215,11 +510,120 @@ mod tests {
         assert!(test_txn.is_ok());
     }
 
+    #[test]
+    fn create_transaction_family_version_test() {
+        // Create test signer
+        let context =
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
+        let private_key = context
+            .new_random_private_key()
+            .expect("Failed to generate random private key");
+        let factory = CryptoFactory::new(&*context);
+        let signer = factory.new_signer(&*private_key);
+
+        let (payload, inputs, outputs) = create_test_payload(&signer);
+        let options = TransactionOptions {
+            family_version: String::from("2.0"),
+            ..TransactionOptions::default()
+        };
+        let txn = create_transaction(&payload, &signer, None, inputs, outputs, vec![], &options)
+            .expect("Failed to create transaction");
+
+        let header: TransactionHeader = protobuf::parse_from_bytes(txn.get_header())
+            .expect("Failed to parse transaction header");
+        assert_eq!(header.get_family_version(), "2.0");
+    }
+
+    #[test]
+    fn chain_dependencies_test() {
+        let context =
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
+        let private_key = context
+            .new_random_private_key()
+            .expect("Failed to generate random private key");
+        let factory = CryptoFactory::new(&*context);
+        let signer = factory.new_signer(&*private_key);
+        let options = TransactionOptions::default();
+
+        assert!(chain_dependencies(None).is_empty());
+
+        let (payload1, inputs1, outputs1) = create_test_payload(&signer);
+        let txn1 = create_transaction(
+            &payload1,
+            &signer,
+            None,
+            inputs1,
+            outputs1,
+            vec![],
+            &options,
+        )
+        .expect("Failed to create transaction");
+
+        let (payload2, inputs2, outputs2) = create_test_payload(&signer);
+        let txn2 = create_transaction(
+            &payload2,
+            &signer,
+            None,
+            inputs2,
+            outputs2,
+            chain_dependencies(Some(&txn1)),
+            &options,
+        )
+        .expect("Failed to create transaction");
+
+        let header: TransactionHeader = protobuf::parse_from_bytes(txn2.get_header())
+            .expect("Failed to parse transaction header");
+        assert_eq!(
+            header.get_dependencies().to_vec(),
+            vec![txn1.get_header_signature().to_string()]
+        );
+    }
+
+    #[test]
+    fn create_batch_with_batcher_test() {
+        let context =
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
+        let private_key = context
+            .new_random_private_key()
+            .expect("Failed to generate random private key");
+        let batcher_key = context
+            .new_random_private_key()
+            .expect("Failed to generate random private key");
+        let factory = CryptoFactory::new(&*context);
+        let signer = factory.new_signer(&*private_key);
+        let batcher = factory.new_signer(&*batcher_key);
+        let batcher_public_key = batcher
+            .get_public_key()
+            .expect("Failed to get batcher's public key")
+            .as_hex();
+
+        let (payload, inputs, outputs) = create_test_payload(&signer);
+        let txn = create_transaction(
+            &payload,
+            &signer,
+            Some(&batcher),
+            inputs,
+            outputs,
+            vec![],
+            &TransactionOptions::default(),
+        )
+        .expect("Failed to create transaction");
+        let txn_header: TransactionHeader = protobuf::parse_from_bytes(txn.get_header())
+            .expect("Failed to parse transaction header");
+        assert_eq!(txn_header.get_batcher_public_key(), batcher_public_key);
+        assert_ne!(txn_header.get_signer_public_key(), batcher_public_key);
+
+        let batch = create_batch(txn, &signer, Some(&batcher)).expect("Failed to create batch");
+        let batch_header: BatchHeader =
+            protobuf::parse_from_bytes(batch.get_header()).expect("Failed to parse batch header");
+        assert_eq!(batch_header.get_signer_public_key(), batcher_public_key);
+    }
+
     #[test]
     fn create_batch_test() {
         // Create test signer
         let context =
-            signing::create_context("secp256k1").expect("Failed to create secp256k1 context");
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
         let private_key = context
             .new_random_private_key()
             .expect("Failed to generate random private key");
@@ -237,7 +641,7 @@ mod tests {
     fn create_batch_with_transactions_test() {
         // Create test signer
         let context =
-            signing::create_context("secp256k1").expect("Failed to create secp256k1 context");
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
         let private_key = context
             .new_random_private_key()
             .expect("Failed to generate random private key");
@@ -246,7 +650,7 @@ mod tests {
 
         let test_txns =
             create_test_transactions(&signer).expect("Failed to create test transactions");
-        let batches = create_batch_with_transactions(test_txns, &signer);
+        let batches = create_batch_with_transactions(test_txns, &signer, None);
 
         assert!(batches.is_ok());
     }
@@ -255,7 +659,7 @@ mod tests {
     fn create_batch_with_transactions_list_test() {
         // Create test signer
         let context =
-            signing::create_context("secp256k1").expect("Failed to create secp256k1 context");
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
         let private_key = context
             .new_random_private_key()
             .expect("Failed to generate random private key");
@@ -264,8 +668,8 @@ mod tests {
 
         let test_txns =
             create_test_transactions(&signer).expect("Failed to create test transactions");
-        let batch =
-            create_batch_with_transactions(test_txns, &signer).expect("Failed to create batches");
+        let batch = create_batch_with_transactions(test_txns, &signer, None)
+            .expect("Failed to create batches");
         let batch_list = create_batch_list(vec![batch.clone()]);
 
         assert!(batch_list.get_batches().len() == 1);
@@ -294,7 +698,7 @@ mod tests {
     fn create_batch_list_from_one_test() {
         // Create test signer
         let context =
-            signing::create_context("secp256k1").expect("Failed to create secp256k1 context");
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
         let private_key = context
             .new_random_private_key()
             .expect("Failed to generate random private key");
@@ -312,6 +716,77 @@ mod tests {
         assert_eq!(batch_list.get_batches().get(0), Some(&test_batch));
     }
 
+    #[test]
+    fn sign_batch_list_test() {
+        // Create test signers
+        let context =
+            signing::create_context(SECP_256K1).expect("Failed to create secp256k1 context");
+        let private_key = context
+            .new_random_private_key()
+            .expect("Failed to generate random private key");
+        let other_key = context
+            .new_random_private_key()
+            .expect("Failed to generate random private key");
+        let factory = CryptoFactory::new(&*context);
+        let signer = factory.new_signer(&*private_key);
+        let other_signer = factory.new_signer(&*other_key);
+
+        let mut test_txns =
+            create_test_transactions(&signer).expect("Failed to create test transactions");
+        let (payload, inputs, outputs) = create_test_payload(&signer);
+        let dependent_txn = create_transaction(
+            &payload,
+            &signer,
+            None,
+            inputs,
+            outputs,
+            chain_dependencies(test_txns.last()),
+            &TransactionOptions::default(),
+        )
+        .expect("Failed to create transaction");
+        test_txns.push(dependent_txn);
+        let batch = create_batch_with_transactions(test_txns, &signer, None)
+            .expect("Failed to create batch");
+        let batch_list = create_batch_list(vec![batch]);
+
+        let unsigned = unsign_batch_list(&batch_list).expect("Failed to unsign batch list");
+        let unsigned_txns = unsigned.get_batches()[0].get_transactions();
+        let unsigned_header: TransactionHeader =
+            protobuf::parse_from_bytes(unsigned_txns[2].get_header())
+                .expect("Failed to parse transaction header");
+        assert_eq!(
+            unsigned_header.get_dependencies(),
+            &[unsigned_txns[1].get_header_signature().to_string()]
+        );
+
+        assert!(sign_batch_list(&unsigned, &other_signer).is_err());
+
+        let signed = sign_batch_list(&unsigned, &signer).expect("Failed to sign batch list");
+        let signed_batch = &signed.get_batches()[0];
+        let signed_txns = signed_batch.get_transactions();
+        let signed_header: TransactionHeader =
+            protobuf::parse_from_bytes(signed_txns[2].get_header())
+                .expect("Failed to parse transaction header");
+        assert_eq!(
+            signed_header.get_dependencies(),
+            &[signed_txns[1].get_header_signature().to_string()]
+        );
+        assert_eq!(
+            signed_txns[0].get_header_signature(),
+            signer
+                .sign(signed_txns[0].get_header())
+                .expect("Failed to sign header")
+        );
+
+        let batch_header: BatchHeader = protobuf::parse_from_bytes(signed_batch.get_header())
+            .expect("Failed to parse batch header");
+        let txn_ids: Vec<String> = signed_txns
+            .iter()
+            .map(|txn| txn.get_header_signature().to_string())
+            .collect();
+        assert_eq!(batch_header.get_transaction_ids(), txn_ids.as_slice());
+    }
+
     fn create_test_transaction(signer: &Signer) -> Result<Transaction, CliError> {
         // Create test payload
         let since_the_epoch = std::time::SystemTime::now()
@@ -335,8 +810,16 @@ mod tests {
         let inputs = agent::create_agent_transaction_addresses(&pub_key.as_hex());
         let outputs = inputs.clone();
 
-        let txn = create_transaction(&payload, &signer, inputs, outputs)
-            .expect("Failed to create transaction");
+        let txn = create_transaction(
+            &payload,
+            &signer,
+            None,
+            inputs,
+            outputs,
+            vec![],
+            &TransactionOptions::default(),
+        )
+        .expect("Failed to create transaction");
 
         Ok(txn)
     }
@@ -345,16 +828,33 @@ mod tests {
         let (payload1, inputs1, outputs1) = create_test_payload(signer);
         let (payload2, inputs2, outputs2) = create_test_payload(signer);
 
-        let txn1 = create_transaction(&payload1, &signer, inputs1, outputs1)
-            .expect("Failed to create transaction");
-        let txn2 = create_transaction(&payload2, &signer, inputs2, outputs2)
-            .expect("Failed to create transaction");
+        let options = TransactionOptions::default();
+        let txn1 = create_transaction(
+            &payload1,
+            &signer,
+            None,
+            inputs1,
+            outputs1,
+            vec![],
+            &options,
+        )
+        .expect("Failed to create transaction");
+        let txn2 = create_transaction(
+            &payload2,
+            &signer,
+            None,
+            inputs2,
+            outputs2,
+            vec![],
+            &options,
+        )
+        .expect("Failed to create transaction");
 
         Ok(vec![txn1, txn2])
     }
 
     fn create_test_batch(txn: Transaction, signer: &Signer) -> Result<Batch, CliError> {
-        let batch = create_batch(txn, signer).expect("Failed to create batch");
+        let batch = create_batch(txn, signer, None).expect("Failed to create batch");
 
         Ok(batch)
     }